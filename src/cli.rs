@@ -1,6 +1,10 @@
 use clap::{Parser, Subcommand};
 use serde::Deserialize;
-use std::{fs, path::PathBuf, process};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
 
 use crate::driver;
 
@@ -34,7 +38,7 @@ const DEFAULT_CONFIG_PATH: &str = "fiber.toml";
 pub fn load_config(args: &Args) -> Option<CompilerConfig> {
     let mut config_path = DEFAULT_CONFIG_PATH;
     match args.config_path.as_str() {
-        ref path if *path != DEFAULT_CONFIG_PATH => {
+        path if path != DEFAULT_CONFIG_PATH => {
             config_path = path;
         }
         _ => {}
@@ -74,7 +78,7 @@ pub fn exec_command(args: Args) {
 }
 
 fn init_command(dir: &PathBuf) {
-    fs::create_dir_all(&dir).unwrap_or_else(|e| {
+    fs::create_dir_all(dir).unwrap_or_else(|e| {
         eprintln!("Error creating project directory: {e}");
         process::exit(1);
     });
@@ -84,7 +88,7 @@ fn init_command(dir: &PathBuf) {
     println!("Initialized new Fiber project at {}", dir.display());
 }
 
-fn build_command(file: &PathBuf, config: &CompilerConfig) {
+fn build_command(file: &Path, config: &CompilerConfig) {
     driver::run_pipeline(file, config.is_debug);
 }
 
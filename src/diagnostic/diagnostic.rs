@@ -0,0 +1,138 @@
+use std::fmt;
+
+use crate::token::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A secondary span shown next to the primary one, explaining its part in the problem.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A problem found in the program. The code identifies the kind of problem,
+/// see docs/development/diagnostics.md for the list.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Error, code, message.into(), span)
+    }
+
+    pub fn warning(code: &'static str, message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Warning, code, message.into(), span)
+    }
+
+    fn new(severity: Severity, code: &'static str, message: String, span: Span) -> Self {
+        Self {
+            severity,
+            code,
+            message,
+            span,
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Render the diagnostic the way rustc does: the message, the location,
+    /// every labeled source line once with each span on it underlined below,
+    /// then the notes.
+    pub fn render(&self, filename: &str, source: &str) -> String {
+        let lines: Vec<&str> = source.lines().collect();
+        // The primary span is underlined with '^', labels with '-'
+        let mut marks = vec![(self.span, '^', "")];
+        marks.extend(
+            self.labels
+                .iter()
+                .map(|label| (label.span, '-', label.message.as_str())),
+        );
+        marks.sort_by_key(|(span, _, _)| (span.line, span.column));
+        let width = marks
+            .iter()
+            .map(|(span, _, _)| span.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = " ".repeat(width);
+
+        let mut out = format!("{}[{}]: {}\n", self.severity, self.code, self.message);
+        out.push_str(&format!(
+            "{}--> {}:{}:{}\n",
+            gutter, filename, self.span.line, self.span.column
+        ));
+        out.push_str(&format!("{} |\n", gutter));
+        let mut previous_line = None;
+        for (span, mark, message) in marks {
+            let text = lines
+                .get(span.line.saturating_sub(1))
+                .copied()
+                .unwrap_or("");
+            // A span running over several lines is underlined up to the end of its first
+            let length = if span.end_line == span.line {
+                span.end_column.saturating_sub(span.column)
+            } else {
+                (text.chars().count() + 1).saturating_sub(span.column)
+            }
+            .max(1);
+            let underline = format!(
+                "{}{}",
+                " ".repeat(span.column.saturating_sub(1)),
+                mark.to_string().repeat(length)
+            );
+            if previous_line != Some(span.line) {
+                out.push_str(&format!("{:>width$} | {}\n", span.line, text));
+                previous_line = Some(span.line);
+            }
+            if message.is_empty() {
+                out.push_str(&format!("{} | {}\n", gutter, underline));
+            } else {
+                out.push_str(&format!("{} | {} {}\n", gutter, underline, message));
+            }
+        }
+        if !self.notes.is_empty() {
+            out.push_str(&format!("{} |\n", gutter));
+        }
+        for note in &self.notes {
+            out.push_str(&format!("{} = note: {}\n", gutter, note));
+        }
+        out
+    }
+}
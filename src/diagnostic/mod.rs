@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+pub mod diagnostic;
+mod tests;
+
+pub use diagnostic::Diagnostic;
@@ -0,0 +1,68 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::{diagnostic::Diagnostic, token::Span};
+
+    #[test]
+    fn test_render_primary_span() {
+        let source = "let x int = true;";
+        let diagnostic = Diagnostic::error("E0100", "Type mismatch", Span::new(1, 13, 1, 17));
+        assert_eq!(
+            diagnostic.render("main.fib", source),
+            "error[E0100]: Type mismatch
+ --> main.fib:1:13
+  |
+1 | let x int = true;
+  |             ^^^^
+"
+        );
+    }
+
+    #[test]
+    fn test_render_labels_and_notes() {
+        let source = "let x int = 1;\nprint_int(1);\nlet x int = 2;";
+        let diagnostic = Diagnostic::error("E0002", "Duplicate", Span::new(3, 5, 3, 10))
+            .with_label(Span::new(1, 5, 1, 10), "first declared here")
+            .with_note("shadow it in a new block instead");
+        assert_eq!(
+            diagnostic.render("main.fib", source),
+            "error[E0002]: Duplicate
+ --> main.fib:3:5
+  |
+1 | let x int = 1;
+  |     ----- first declared here
+3 | let x int = 2;
+  |     ^^^^^
+  |
+  = note: shadow it in a new block instead
+"
+        );
+    }
+
+    #[test]
+    fn test_render_labels_on_the_same_line() {
+        let source = "let q unique &int = p; let r int = deref p;";
+        let diagnostic =
+            Diagnostic::error("E0402", "Use of moved value 'p'", Span::new(1, 42, 1, 43))
+                .with_label(Span::new(1, 21, 1, 22), "value moved here");
+        assert_eq!(
+            diagnostic.render("main.fib", source),
+            "error[E0402]: Use of moved value 'p'
+ --> main.fib:1:42
+  |
+1 | let q unique &int = p; let r int = deref p;
+  |                     - value moved here
+  |                                          ^
+"
+        );
+    }
+
+    #[test]
+    fn test_render_warning_and_wide_gutter() {
+        let source = format!("{}return 1;", "\n".repeat(11));
+        let diagnostic = Diagnostic::warning("W0001", "Unreachable", Span::new(12, 1, 12, 9));
+        assert!(!diagnostic.is_error());
+        let rendered = diagnostic.render("main.fib", &source);
+        assert!(rendered.starts_with("warning[W0001]: Unreachable\n  --> main.fib:12:1\n   |\n12 | return 1;\n   | ^^^^^^^^\n"), "{}", rendered);
+    }
+}
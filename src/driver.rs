@@ -1,13 +1,17 @@
 use std::path::Path;
 use std::{fs, process};
 
+use crate::escape::check_escapes;
+use crate::hints::HintRegistry;
+use crate::hir::Hir;
+use crate::monomorphize::monomorphize;
 use crate::parser::{Ast, Parser};
 use crate::type_checker::TypeChecker;
 use crate::{lexer::Lexer, token::Token};
 
 pub fn run_pipeline(file: &Path, is_debug_mode: bool) {
     // Run pipeline
-    let src = fs::read_to_string(&file).unwrap_or_else(|e| {
+    let src = fs::read_to_string(file).unwrap_or_else(|e| {
         eprintln!("Failed to read '{:?}': {}", file, e);
         process::exit(1);
     });
@@ -17,20 +21,42 @@ pub fn run_pipeline(file: &Path, is_debug_mode: bool) {
         show_tokens(&tokens);
     }
     let ast_opt = run_parser(tokens, file.to_string_lossy().to_string(), src.clone());
-    let ast = match ast_opt {
+    let mut ast = match ast_opt {
         Some(a) => a,
         None => process::exit(1),
     };
     if is_debug_mode {
         show_ast(&ast);
     }
-    run_type_checking(&ast);
+    let filename = file.to_string_lossy();
+    let hint_errors = run_hint_validation(&mut ast, &filename, &src);
+    let hir = run_type_checking(&ast, &filename, &src);
+    let escape_errors = match &hir {
+        Ok(hir) => run_escape_analysis(hir, &filename, &src),
+        Err(_) => 0,
+    };
+    let hir = match hir {
+        Ok(hir) if escape_errors == 0 => run_monomorphization(&hir, &filename, &src),
+        hir => hir,
+    };
+    let errors = hint_errors + hir.as_ref().err().copied().unwrap_or(0) + escape_errors;
+    if errors > 0 {
+        eprintln!(
+            "error: aborting due to {} previous error{}",
+            errors,
+            if errors == 1 { "" } else { "s" }
+        );
+        process::exit(1);
+    }
+    if is_debug_mode && let Ok(hir) = &hir {
+        show_hir(hir);
+    }
 }
 
-pub fn run_lexer(src: &String) -> Vec<Token> {
-    let lexer = Lexer::new(&src);
-    let tokens = lexer.collect();
-    tokens
+pub fn run_lexer(src: &str) -> Vec<Token> {
+    let lexer = Lexer::new(src);
+
+    lexer.collect()
 }
 
 pub fn run_parser(tokens: Vec<Token>, filename: String, source: String) -> Option<Ast> {
@@ -45,9 +71,61 @@ pub fn run_parser(tokens: Vec<Token>, filename: String, source: String) -> Optio
     }
 }
 
-pub fn run_type_checking(ast: &Ast) {
+/// Bind the promises written before functions to their parameters, then
+/// render the diagnostics of hint validation, returning how many of them are errors.
+pub fn run_hint_validation(ast: &mut Ast, filename: &str, source: &str) -> usize {
+    let registry = HintRegistry::new();
+    registry.bind_promises(ast);
+    let diagnostics = registry.check_ast(ast);
+    for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic.render(filename, source));
+    }
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .count()
+}
+
+/// Render the type checker diagnostics. A program without errors is lowered
+/// to its HIR, otherwise the number of errors is returned.
+pub fn run_type_checking(ast: &Ast, filename: &str, source: &str) -> Result<Hir, usize> {
     let mut type_checker = TypeChecker::new(ast);
-    type_checker.check_ast();
+    let diagnostics = type_checker.check_ast();
+    for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic.render(filename, source));
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .count();
+    if errors > 0 {
+        return Err(errors);
+    }
+    Ok(type_checker.lower())
+}
+
+/// Render the diagnostics of the escape analysis of a program that type
+/// checked, returning how many of them are errors.
+pub fn run_escape_analysis(hir: &Hir, filename: &str, source: &str) -> usize {
+    let diagnostics = check_escapes(hir);
+    for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic.render(filename, source));
+    }
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .count()
+}
+
+/// Specialize the generic functions of a program that passed every check,
+/// rendering the diagnostics if that fails and returning how many there are.
+pub fn run_monomorphization(hir: &Hir, filename: &str, source: &str) -> Result<Hir, usize> {
+    monomorphize(hir).map_err(|diagnostics| {
+        for diagnostic in &diagnostics {
+            eprintln!("{}", diagnostic.render(filename, source));
+        }
+        diagnostics.len()
+    })
 }
 
 #[allow(dead_code)]
@@ -65,3 +143,10 @@ pub(crate) fn show_ast(ast: &Ast) {
     println!("{:#?}", ast);
     println!("====END AST============");
 }
+
+#[allow(dead_code)]
+pub(crate) fn show_hir(hir: &Hir) {
+    println!("====START HIR==========");
+    println!("{:#?}", hir);
+    println!("====END HIR============");
+}
@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+
+use crate::{
+    diagnostic::Diagnostic,
+    hir::{
+        Hir,
+        hir::{
+            BindingId, Expression, ExpressionKind, Parameter, Resolution, Statement, StatementKind,
+        },
+    },
+    token::{Operator, Span, TypeIdentifier},
+};
+
+/// Find the raw pointers that may outlive the variable they point to: the
+/// address of a local returned from its function, or stored in a variable
+/// that lives longer, directly or inside a closure that captured it.
+///
+/// Unique and shared pointers take over the variable they point to, so only
+/// raw pointers refer to storage on the stack.
+pub fn check_escapes(hir: &Hir) -> Vec<Diagnostic> {
+    let mut analysis = EscapeAnalysis {
+        hir,
+        scopes: vec!["the program ends".to_string()],
+        frames: Vec::new(),
+        lifetimes: HashMap::new(),
+        points_to: HashMap::new(),
+        diagnostics: Vec::new(),
+    };
+    for statement in &hir.statements {
+        analysis.check_statement(statement);
+    }
+    let mut diagnostics = analysis.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.span.line, diagnostic.span.column));
+    diagnostics
+}
+
+/// The address of a variable, held by a value.
+#[derive(Debug, Clone, PartialEq)]
+struct Reference {
+    // The variable whose storage is pointed to
+    target: BindingId,
+    // Where its address was taken
+    taken_at: Span,
+    // The variable through which a closure holds the address, when one does
+    captured: Option<BindingId>,
+}
+
+struct EscapeAnalysis<'a> {
+    hir: &'a Hir,
+    // The event that ends each block enclosing the statement being checked,
+    // outermost first. The variables of a later block die first.
+    scopes: Vec<String>,
+    // The outermost block of each enclosing function, as an index in scopes
+    frames: Vec<usize>,
+    // The block each variable is declared in, as an index in scopes, and the
+    // event that ends it
+    lifetimes: HashMap<BindingId, (usize, String)>,
+    // The addresses each variable may hold
+    points_to: HashMap<BindingId, Vec<Reference>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl EscapeAnalysis<'_> {
+    fn check_statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::Let { pattern, value } => {
+                let references = match value {
+                    Some(value) => {
+                        self.check_expr(value);
+                        self.references(value)
+                    }
+                    None => Vec::new(),
+                };
+                for id in pattern.bindings() {
+                    self.declare(id, references.clone());
+                }
+            }
+            StatementKind::Assignment { target, value } => {
+                self.check_expr(value);
+                let references = self.references(value);
+                self.check_store(*target, &references, value.span);
+                self.points_to.insert(*target, references);
+            }
+            // Where a pointer points is not tracked, so an address stored
+            // through one is not followed
+            StatementKind::Store { target, value } => {
+                self.check_expr(target);
+                self.check_expr(value);
+            }
+            StatementKind::CompoundAssignment { value, .. } => self.check_expr(value),
+            StatementKind::IncrementDecrement { .. } => {}
+            StatementKind::Expression(expr) => self.check_expr(expr),
+            StatementKind::Function(function) => self.check_function(
+                format!("'{}' returns", function.name),
+                &function.parameters,
+                &function.body,
+            ),
+            StatementKind::TypeDeclaration {
+                name, contracts, ..
+            } => {
+                for method in contracts.iter().flat_map(|contract| &contract.methods) {
+                    self.check_function(
+                        format!("'{}.{}' returns", name, method.name),
+                        &method.parameters,
+                        &method.body,
+                    );
+                }
+            }
+            StatementKind::Return(Some(value)) => {
+                self.check_expr(value);
+                self.check_return(value);
+            }
+            StatementKind::Return(None) => {}
+            StatementKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.check_expr(condition);
+                let before = self.points_to.clone();
+                self.check_block(then_branch, format!("the 'if' at {} ends", statement.span));
+                let after_then = std::mem::replace(&mut self.points_to, before);
+                if let Some(else_branch) = else_branch {
+                    self.check_block(
+                        else_branch,
+                        format!("the else branch of the 'if' at {} ends", statement.span),
+                    );
+                }
+                self.join(after_then);
+            }
+            // An iteration starts with what the ones before it stored, so
+            // the body is checked again until that stops growing. Only the
+            // diagnostics of the last pass are kept.
+            StatementKind::For { condition, body } => {
+                let diagnostics = self.diagnostics.len();
+                loop {
+                    self.diagnostics.truncate(diagnostics);
+                    let before = self.points_to.clone();
+                    self.check_expr(condition);
+                    self.check_block(
+                        body,
+                        format!("an iteration of the 'for' at {} ends", statement.span),
+                    );
+                    self.join(before.clone());
+                    let grew = self.points_to.iter().any(|(id, references)| {
+                        references.iter().any(|reference| {
+                            !before.get(id).is_some_and(|old| old.contains(reference))
+                        })
+                    });
+                    if !grew {
+                        break;
+                    }
+                }
+            }
+            StatementKind::Match { scrutinee, arms } => {
+                self.check_expr(scrutinee);
+                let references = self.references(scrutinee);
+                let before = self.points_to.clone();
+                let mut after_arms = Vec::new();
+                for arm in arms {
+                    self.points_to = before.clone();
+                    self.scopes
+                        .push(format!("the match arm at {} ends", arm.pattern.span));
+                    // What the scrutinee points to, the names bound from it may too
+                    for id in arm.pattern.bindings() {
+                        self.declare(id, references.clone());
+                    }
+                    for statement in &arm.body {
+                        self.check_statement(statement);
+                    }
+                    self.scopes.pop();
+                    after_arms.push(std::mem::take(&mut self.points_to));
+                }
+                self.points_to = before;
+                for after_arm in after_arms {
+                    self.join(after_arm);
+                }
+            }
+            StatementKind::Hinted { statement, .. } => self.check_statement(statement),
+        }
+    }
+
+    /// Check the lambdas an expression creates, each a function of its own.
+    fn check_expr(&mut self, expr: &Expression) {
+        match &expr.kind {
+            ExpressionKind::Lambda(lambda) => self.check_function(
+                format!("the anonymous function at {} returns", expr.span),
+                &lambda.parameters,
+                &lambda.body,
+            ),
+            ExpressionKind::Binary { left, right, .. } => {
+                self.check_expr(left);
+                self.check_expr(right);
+            }
+            ExpressionKind::Unary { expression, .. }
+            | ExpressionKind::Hinted { expression, .. }
+            | ExpressionKind::Cast { expression, .. }
+            | ExpressionKind::Field {
+                receiver: expression,
+                ..
+            }
+            | ExpressionKind::Propagate(expression) => self.check_expr(expression),
+            ExpressionKind::Call { callee, args } => {
+                self.check_expr(callee);
+                args.iter().for_each(|arg| self.check_expr(arg));
+            }
+            ExpressionKind::MethodCall { receiver, args, .. } => {
+                self.check_expr(receiver);
+                args.iter().for_each(|arg| self.check_expr(arg));
+            }
+            ExpressionKind::Tuple(elements) => {
+                elements.iter().for_each(|element| self.check_expr(element))
+            }
+            ExpressionKind::Struct(fields) => {
+                fields.iter().for_each(|(_, value)| self.check_expr(value))
+            }
+            ExpressionKind::Variant { payload, .. } | ExpressionKind::Panic(payload) => {
+                if let Some(payload) = payload {
+                    self.check_expr(payload);
+                }
+            }
+            ExpressionKind::Literal(_) | ExpressionKind::Name(_) | ExpressionKind::Move(_) => {}
+        }
+    }
+
+    fn check_block(&mut self, statements: &[Statement], ends: String) {
+        self.scopes.push(ends);
+        for statement in statements {
+            self.check_statement(statement);
+        }
+        self.scopes.pop();
+    }
+
+    /// Check the body of a function or lambda, whose parameters and outermost
+    /// variables die when it returns.
+    fn check_function(&mut self, returns: String, parameters: &[Parameter], body: &[Statement]) {
+        self.frames.push(self.scopes.len());
+        self.scopes.push(returns);
+        for parameter in parameters {
+            self.declare(parameter.binding, Vec::new());
+        }
+        for statement in body {
+            self.check_statement(statement);
+        }
+        self.scopes.pop();
+        self.frames.pop();
+    }
+
+    fn declare(&mut self, id: BindingId, references: Vec<Reference>) {
+        let block = self.scopes.len() - 1;
+        self.lifetimes
+            .insert(id, (block, self.scopes[block].clone()));
+        self.points_to.insert(id, references);
+    }
+
+    /// Merge in the addresses variables may hold at the end of another path.
+    fn join(&mut self, other: HashMap<BindingId, Vec<Reference>>) {
+        for (id, references) in other {
+            let joined = self.points_to.entry(id).or_default();
+            for reference in references {
+                if !joined.contains(&reference) {
+                    joined.push(reference);
+                }
+            }
+        }
+    }
+
+    /// The block a variable is declared in. Lower blocks live longer.
+    fn lifetime(&self, id: BindingId) -> usize {
+        self.lifetimes.get(&id).map_or(0, |(block, _)| *block)
+    }
+
+    /// The event that ends the block a variable is declared in.
+    fn ends(&self, id: BindingId) -> &str {
+        self.lifetimes
+            .get(&id)
+            .map_or("the program ends", |(_, ends)| ends)
+    }
+
+    /// The addresses of variables the value of `expr` may hold.
+    fn references(&self, expr: &Expression) -> Vec<Reference> {
+        match &expr.kind {
+            ExpressionKind::Unary {
+                operator: Operator::AddressOf,
+                expression,
+            } => match (&expression.kind, &expr.expr_type) {
+                (ExpressionKind::Name(Resolution::Local(id)), TypeIdentifier::Pointer(_)) => {
+                    vec![Reference {
+                        target: *id,
+                        taken_at: expr.span,
+                        captured: None,
+                    }]
+                }
+                _ => Vec::new(),
+            },
+            ExpressionKind::Name(Resolution::Local(id)) | ExpressionKind::Move(id) => {
+                self.points_to.get(id).cloned().unwrap_or_default()
+            }
+            // Pointer arithmetic stays within what the pointer points to
+            ExpressionKind::Binary {
+                left,
+                operator: Operator::Plus | Operator::Minus,
+                ..
+            } => self.references(left),
+            ExpressionKind::Hinted { expression, .. } => self.references(expression),
+            ExpressionKind::Tuple(elements) => elements
+                .iter()
+                .flat_map(|element| self.references(element))
+                .collect(),
+            ExpressionKind::Struct(fields) => fields
+                .iter()
+                .flat_map(|(_, value)| self.references(value))
+                .collect(),
+            ExpressionKind::Variant {
+                payload: Some(payload),
+                ..
+            } => self.references(payload),
+            // A closure holds the addresses its captured copies hold
+            ExpressionKind::Lambda(lambda) => lambda
+                .captures
+                .iter()
+                .flat_map(|captured| {
+                    self.points_to
+                        .get(captured)
+                        .into_iter()
+                        .flatten()
+                        .map(|reference| Reference {
+                            captured: reference.captured.or(Some(*captured)),
+                            ..reference.clone()
+                        })
+                })
+                .collect(),
+            // The callee is not looked into, so a call may return any
+            // address its arguments hold
+            ExpressionKind::Call { args, .. } | ExpressionKind::MethodCall { args, .. }
+                if may_hold_address(&expr.expr_type) =>
+            {
+                args.iter().flat_map(|arg| self.references(arg)).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Report the addresses a function returns of its own variables.
+    fn check_return(&mut self, value: &Expression) {
+        let Some(&frame) = self.frames.last() else {
+            return;
+        };
+        for reference in self.references(value) {
+            if self.lifetime(reference.target) < frame {
+                continue;
+            }
+            let target = self.name(reference.target);
+            let message = match reference.captured {
+                Some(captured) => format!(
+                    "Cannot return a closure that captures '{}', which holds the address of local '{}'",
+                    self.name(captured),
+                    target
+                ),
+                None => format!("Cannot return the address of local '{}'", target),
+            };
+            let diagnostic = Diagnostic::error("E0405", message, value.span);
+            self.report(diagnostic, &reference, value.span, None);
+        }
+    }
+
+    /// Report the addresses stored in `target` of variables that die before it.
+    fn check_store(&mut self, target: BindingId, references: &[Reference], span: Span) {
+        for reference in references {
+            if self.lifetime(target) >= self.lifetime(reference.target) {
+                continue;
+            }
+            let message = match reference.captured {
+                Some(captured) => format!(
+                    "Cannot store a closure in '{}' that captures '{}', which holds the address of '{}'",
+                    self.name(target),
+                    self.name(captured),
+                    self.name(reference.target)
+                ),
+                None => format!(
+                    "Cannot store the address of '{}' in '{}', which outlives it",
+                    self.name(reference.target),
+                    self.name(target)
+                ),
+            };
+            let diagnostic = Diagnostic::error("E0406", message, span);
+            self.report(diagnostic, reference, span, Some(target));
+        }
+    }
+
+    /// Point at where the address was taken, and explain when the variable
+    /// it belongs to dies and, for a store, how long the target lives.
+    fn report(
+        &mut self,
+        mut diagnostic: Diagnostic,
+        reference: &Reference,
+        span: Span,
+        target: Option<BindingId>,
+    ) {
+        if reference.taken_at != span {
+            diagnostic = diagnostic.with_label(
+                reference.taken_at,
+                format!("address of '{}' taken here", self.name(reference.target)),
+            );
+        }
+        let dies = format!(
+            "'{}' dies when {}",
+            self.name(reference.target),
+            self.ends(reference.target)
+        );
+        let note = match target {
+            Some(target) => {
+                diagnostic = diagnostic.with_label(
+                    self.hir.binding(target).span,
+                    format!("'{}' declared here", self.name(target)),
+                );
+                format!(
+                    "{}, but '{}' lives until {}",
+                    dies,
+                    self.name(target),
+                    self.ends(target)
+                )
+            }
+            None => dies,
+        };
+        self.diagnostics.push(diagnostic.with_note(note));
+    }
+
+    fn name(&self, id: BindingId) -> &str {
+        &self.hir.binding(id).name
+    }
+}
+
+/// Whether a value of the type may hold a raw pointer. A named type may be
+/// declared as one holding a raw pointer, so it is assumed to.
+fn may_hold_address(value_type: &TypeIdentifier) -> bool {
+    match value_type {
+        TypeIdentifier::Pointer(_)
+        | TypeIdentifier::Named(_)
+        | TypeIdentifier::UserDefinedType
+        | TypeIdentifier::Function { .. } => true,
+        TypeIdentifier::Tuple(elements) => elements.iter().any(may_hold_address),
+        TypeIdentifier::Labeled(fields) => fields
+            .iter()
+            .any(|(_, field_type)| may_hold_address(field_type)),
+        TypeIdentifier::Sum(variants) => variants
+            .iter()
+            .any(|(_, payload)| payload.as_ref().is_some_and(may_hold_address)),
+        _ => false,
+    }
+}
@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+pub mod escape;
+mod tests;
+
+pub use escape::check_escapes;
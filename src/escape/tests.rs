@@ -0,0 +1,181 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::{diagnostic::Diagnostic, escape::check_escapes, test_support::lower};
+
+    fn escapes(src: &str) -> Vec<Diagnostic> {
+        check_escapes(&lower(src))
+    }
+
+    fn messages(src: &str) -> Vec<String> {
+        escapes(src)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_returning_the_address_of_a_local() {
+        let diagnostics = escapes(
+            "function f() &int {
+                 let x int = 5;
+                 return &x;
+             }",
+        );
+        assert_eq!(diagnostics[0].code, "E0405");
+        assert_eq!(
+            diagnostics[0].message,
+            "Cannot return the address of local 'x'"
+        );
+        assert_eq!(diagnostics[0].notes, ["'x' dies when 'f' returns"]);
+        // Through a variable and pointer arithmetic, and for parameters too
+        let diagnostics = escapes(
+            "function f(n int) &int {
+                 let p &int = &n;
+                 let q &int = p + 1;
+                 return q;
+             }",
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "Cannot return the address of local 'n'"
+        );
+        assert_eq!(
+            diagnostics[0].labels[0].message,
+            "address of 'n' taken here"
+        );
+    }
+
+    #[test]
+    fn test_returning_an_address_through_a_call() {
+        let errors = messages(
+            "function id(p &int) &int {
+                 return p;
+             }
+             function f() &int {
+                 let x int = 5;
+                 return id(&x);
+             }
+             function g() &int {
+                 let x int = 5;
+                 let q &int = id(&x);
+                 return q;
+             }",
+        );
+        assert_eq!(
+            errors,
+            [
+                "Cannot return the address of local 'x'",
+                "Cannot return the address of local 'x'"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_addresses_carried_to_a_later_iteration() {
+        let errors = messages(
+            "function f(c bool) &int {
+                 let x int = 5;
+                 let q &int = null;
+                 let r &int = null;
+                 for c {
+                     r = q;
+                     q = &x;
+                 }
+                 return r;
+             }",
+        );
+        assert_eq!(errors, ["Cannot return the address of local 'x'"]);
+    }
+
+    #[test]
+    fn test_storing_in_a_longer_lived_binding() {
+        let diagnostics = escapes(
+            "function f(c bool) {
+                 let p &int = null;
+                 if c {
+                     let y int = 1;
+                     p = &y;
+                 }
+             }",
+        );
+        assert_eq!(diagnostics[0].code, "E0406");
+        assert_eq!(
+            diagnostics[0].message,
+            "Cannot store the address of 'y' in 'p', which outlives it"
+        );
+        assert_eq!(
+            diagnostics[0].notes,
+            ["'y' dies when the 'if' at 3:18 ends, but 'p' lives until 'f' returns"]
+        );
+        let diagnostics = escapes(
+            "let global &int = null;
+             function f() {
+                 let z int = 2;
+                 global = &z;
+             }",
+        );
+        assert_eq!(
+            diagnostics[0].notes,
+            ["'z' dies when 'f' returns, but 'global' lives until the program ends"]
+        );
+    }
+
+    #[test]
+    fn test_escaping_closures() {
+        let errors = messages(
+            "function make() () -> int {
+                 let x int = 5;
+                 let p &int = &x;
+                 return function() int { return deref p; };
+             }",
+        );
+        assert_eq!(
+            errors,
+            ["Cannot return a closure that captures 'p', which holds the address of local 'x'"]
+        );
+        let errors = messages(
+            "let keep () -> int = function() int { return 1; };
+             function f() {
+                 let x int = 5;
+                 let p &int = &x;
+                 keep = function() int { return deref p; };
+             }",
+        );
+        assert_eq!(
+            errors,
+            ["Cannot store a closure in 'keep' that captures 'p', which holds the address of 'x'"]
+        );
+    }
+
+    #[test]
+    fn test_references_that_do_not_escape() {
+        let errors = messages(
+            "let global int = 1;
+             function read(p &int) int {
+                 if p == null {
+                     return 1;
+                 }
+                 return deref p;
+             }
+             function same(p &int) &int {
+                 return p;
+             }
+             function to_global() &int {
+                 return &global;
+             }
+             function owned() unique &int {
+                 let x int = 5;
+                 return &x;
+             }
+             function local() int {
+                 let x int = 5;
+                 let p &int = &x;
+                 let f () -> int = function() int { return deref p; };
+                 let n int = read(&x);
+                 return f() + read(&x) + n;
+             }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+}
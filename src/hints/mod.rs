@@ -0,0 +1,5 @@
+mod promises;
+pub mod registry;
+mod tests;
+
+pub use registry::{HintRegistry, HintTarget};
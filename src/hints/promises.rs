@@ -0,0 +1,138 @@
+use crate::hints::{HintRegistry, HintTarget};
+use crate::parser::{
+    Ast, Expression, ExpressionKind, Function, FunctionBody, HintPlacement, Statement,
+    StatementKind,
+};
+
+impl HintRegistry {
+    /// Move the hints written before a function as `@hint(param)` onto that
+    /// parameter, as promises the function makes about it. Every other hint
+    /// before a function stays a hint on the function itself.
+    pub fn bind_promises(&self, ast: &mut Ast) {
+        self.bind_in_statements(&mut ast.statements);
+    }
+
+    fn bind_in_statements(&self, statements: &mut [Statement]) {
+        for statement in statements {
+            self.bind_in_statement(statement);
+        }
+    }
+
+    fn bind_in_statement(&self, statement: &mut Statement) {
+        match &mut statement.kind {
+            StatementKind::VariableDeclaration(var_decl) => {
+                if let Some(expression) = &mut var_decl.expression {
+                    self.bind_in_expr(expression);
+                }
+            }
+            StatementKind::Assignment { expr, .. }
+            | StatementKind::CompoundAssignment { expr, .. }
+            | StatementKind::Expression(expr)
+            | StatementKind::Return(Some(expr)) => self.bind_in_expr(expr),
+            StatementKind::Store { target, expr } => {
+                self.bind_in_expr(target);
+                self.bind_in_expr(expr);
+            }
+            StatementKind::IncrementDecrement { .. } | StatementKind::Return(None) => {}
+            StatementKind::FunctionDeclaration(function) => self.bind_in_function(function),
+            StatementKind::TypeDeclaration { contracts, .. } => {
+                for method in contracts
+                    .iter_mut()
+                    .flat_map(|contract| &mut contract.methods)
+                {
+                    self.bind_in_function(method);
+                }
+            }
+            StatementKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.bind_in_expr(condition);
+                self.bind_in_statements(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.bind_in_statements(else_branch);
+                }
+            }
+            StatementKind::For { condition, body } => {
+                self.bind_in_expr(condition);
+                self.bind_in_statements(body);
+            }
+            StatementKind::Hinted { statement, .. } => self.bind_in_statement(statement),
+            StatementKind::Match { scrutinee, arms } => {
+                self.bind_in_expr(scrutinee);
+                for arm in arms {
+                    self.bind_in_statements(&mut arm.body);
+                }
+            }
+        }
+    }
+
+    fn bind_in_function(&self, function: &mut Function) {
+        let parameters = &mut function.signature.parameters;
+        let mut function_hints = Vec::new();
+        for mut hint in std::mem::take(&mut function.hints) {
+            let bound_parameter = match hint.arguments.first() {
+                Some(Expression {
+                    kind: ExpressionKind::Identifier(name),
+                    ..
+                }) if self.allows(&hint.name, HintTarget::Parameter) => parameters
+                    .iter_mut()
+                    .find(|parameter| &parameter.parameter_name == name),
+                _ => None,
+            };
+            match bound_parameter {
+                Some(parameter) => {
+                    hint.arguments.remove(0);
+                    hint.placement = HintPlacement::Promise;
+                    parameter.hints.push(hint);
+                }
+                None => function_hints.push(hint),
+            }
+        }
+        function.hints = function_hints;
+        if let FunctionBody::Statements(statements) = &mut function.body {
+            self.bind_in_statements(statements);
+        }
+    }
+
+    /// Bind the promises of the functions declared in the lambdas of `expr`.
+    fn bind_in_expr(&self, expr: &mut Expression) {
+        match &mut expr.kind {
+            ExpressionKind::Lambda(lambda) => self.bind_in_statements(&mut lambda.body),
+            ExpressionKind::Binary { left, right, .. } => {
+                self.bind_in_expr(left);
+                self.bind_in_expr(right);
+            }
+            ExpressionKind::Unary { expression, .. }
+            | ExpressionKind::Grouping(expression)
+            | ExpressionKind::Hinted { expression, .. }
+            | ExpressionKind::Cast { expression, .. }
+            | ExpressionKind::Propagate(expression)
+            | ExpressionKind::Field {
+                receiver: expression,
+                ..
+            } => self.bind_in_expr(expression),
+            ExpressionKind::Call {
+                callee: receiver,
+                args,
+            }
+            | ExpressionKind::MethodCall { receiver, args, .. } => {
+                self.bind_in_expr(receiver);
+                args.iter_mut().for_each(|arg| self.bind_in_expr(arg));
+            }
+            ExpressionKind::Tuple(elements) => elements
+                .iter_mut()
+                .for_each(|element| self.bind_in_expr(element)),
+            ExpressionKind::Struct(fields) => fields
+                .iter_mut()
+                .for_each(|(_, value)| self.bind_in_expr(value)),
+            ExpressionKind::Variant { payload, .. } | ExpressionKind::Panic(payload) => {
+                if let Some(payload) = payload {
+                    self.bind_in_expr(payload);
+                }
+            }
+            ExpressionKind::Literal(_) | ExpressionKind::Identifier(_) => {}
+        }
+    }
+}
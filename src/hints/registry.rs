@@ -0,0 +1,443 @@
+use std::fmt;
+
+use crate::diagnostic::Diagnostic;
+use crate::parser::{
+    Ast, Expression, ExpressionKind, Function, Hint, HintPlacement, Statement, StatementKind,
+};
+use crate::token::Literal;
+
+/// The kind of element a hint is attached to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HintTarget {
+    Function,
+    Parameter,
+    Variable,
+    Loop,
+    Branch,
+    Statement,
+    Expression,
+}
+
+impl fmt::Display for HintTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HintTarget::Function => "a function",
+            HintTarget::Parameter => "a parameter",
+            HintTarget::Variable => "a variable",
+            HintTarget::Loop => "a loop",
+            HintTarget::Branch => "a branch",
+            HintTarget::Statement => "a statement",
+            HintTarget::Expression => "an expression",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The shape an argument of a hint must have.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HintArgument {
+    Integer,
+    Identifier,
+    Expression,
+}
+
+impl fmt::Display for HintArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HintArgument::Integer => "an integer",
+            HintArgument::Identifier => "a name",
+            HintArgument::Expression => "an expression",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug)]
+pub struct HintSpec {
+    pub name: &'static str,
+    pub required: &'static [HintArgument],
+    pub optional: &'static [HintArgument],
+    pub targets: &'static [HintTarget],
+}
+
+const fn spec(
+    name: &'static str,
+    required: &'static [HintArgument],
+    optional: &'static [HintArgument],
+    targets: &'static [HintTarget],
+) -> HintSpec {
+    HintSpec {
+        name,
+        required,
+        optional,
+        targets,
+    }
+}
+
+use HintArgument as A;
+use HintTarget as T;
+
+const NONE: &[HintArgument] = &[];
+const FUNCTION: &[HintTarget] = &[T::Function];
+const FUNCTION_OR_CALL: &[HintTarget] = &[T::Function, T::Expression];
+const BINDING: &[HintTarget] = &[T::Parameter, T::Variable];
+const LOOP: &[HintTarget] = &[T::Loop];
+const CHECKS: &[HintTarget] = &[T::Function, T::Loop, T::Statement, T::Expression];
+
+/// Every hint described in `docs/compiler_hints.md`.
+const HINTS: &[HintSpec] = &[
+    // Inlining
+    spec("inline", NONE, NONE, FUNCTION_OR_CALL),
+    spec("no_inline", NONE, NONE, FUNCTION_OR_CALL),
+    spec("flatten", NONE, NONE, FUNCTION_OR_CALL),
+    // Execution frequency
+    spec("hot", NONE, NONE, FUNCTION),
+    spec("cold", NONE, NONE, FUNCTION),
+    // Purity and side effects
+    spec("pure", NONE, NONE, FUNCTION),
+    spec("const", NONE, NONE, FUNCTION),
+    // Recursion
+    spec("recursive", NONE, NONE, FUNCTION),
+    spec("no_recurse", NONE, NONE, FUNCTION),
+    spec("tail_recursive", NONE, NONE, FUNCTION),
+    spec("leaf", NONE, NONE, FUNCTION),
+    // Control flow and return values
+    spec("no_return", NONE, NONE, FUNCTION),
+    spec("must_use", NONE, NONE, FUNCTION),
+    spec("deprecated", NONE, &[A::Expression], FUNCTION),
+    spec("throws", NONE, NONE, FUNCTION),
+    spec("no_throw", NONE, NONE, FUNCTION),
+    // Memory and allocation
+    spec("no_alloc", NONE, NONE, FUNCTION),
+    spec("stack_only", NONE, NONE, FUNCTION),
+    spec("allocator", NONE, NONE, FUNCTION),
+    // Memory access
+    spec("readonly", NONE, NONE, BINDING),
+    spec("writeonly", NONE, NONE, BINDING),
+    spec("restrict", NONE, NONE, BINDING),
+    spec("noalias", NONE, NONE, BINDING),
+    spec("volatile", NONE, NONE, BINDING),
+    spec("immutable", NONE, NONE, BINDING),
+    // Nullability
+    spec("nonnull", NONE, NONE, BINDING),
+    spec("nullable", NONE, NONE, BINDING),
+    // Alignment
+    spec("aligned", &[A::Integer], NONE, BINDING),
+    spec("packed", NONE, NONE, &[T::Variable]),
+    spec("cache_aligned", NONE, NONE, BINDING),
+    // Storage
+    spec("register", NONE, NONE, &[T::Variable]),
+    spec("thread_local", NONE, NONE, &[T::Variable]),
+    spec("unused", NONE, NONE, BINDING),
+    // Casting
+    spec("raw_cast", NONE, NONE, &[T::Variable]),
+    // Branch prediction and reachability
+    spec("likely", NONE, NONE, &[T::Branch, T::Expression]),
+    spec("unlikely", NONE, NONE, &[T::Branch, T::Expression]),
+    spec("unreachable", NONE, NONE, &[T::Branch, T::Statement]),
+    spec("assume", &[A::Expression], NONE, &[T::Statement]),
+    spec(
+        "expect",
+        &[A::Expression, A::Expression],
+        NONE,
+        &[T::Expression],
+    ),
+    // Loops
+    spec("unroll", NONE, &[A::Integer], LOOP),
+    spec("no_unroll", NONE, NONE, LOOP),
+    spec("vectorize", NONE, NONE, LOOP),
+    spec("no_vectorize", NONE, NONE, LOOP),
+    spec("ivdep", NONE, NONE, LOOP),
+    spec("parallelize", NONE, NONE, LOOP),
+    spec("distribute", NONE, NONE, LOOP),
+    // Concurrency
+    spec("thread_safe", NONE, NONE, FUNCTION),
+    spec("not_thread_safe", NONE, NONE, FUNCTION),
+    spec("reentrant", NONE, NONE, FUNCTION),
+    spec(
+        "atomic",
+        NONE,
+        NONE,
+        &[T::Variable, T::Statement, T::Expression],
+    ),
+    spec("lock_free", NONE, NONE, FUNCTION),
+    spec("synchronized", NONE, NONE, FUNCTION),
+    // Code generation
+    spec("optimize", &[A::Expression], NONE, FUNCTION),
+    spec("no_optimize", NONE, NONE, FUNCTION),
+    spec("target", &[A::Identifier], NONE, FUNCTION),
+    spec("section", &[A::Identifier], NONE, FUNCTION),
+    spec("export", NONE, NONE, FUNCTION),
+    spec("internal", NONE, NONE, FUNCTION),
+    spec("weak", NONE, NONE, FUNCTION),
+    spec("abi", &[A::Identifier], NONE, FUNCTION),
+    // Safety and verification
+    spec("bounds_check", NONE, NONE, CHECKS),
+    spec("no_bounds_check", NONE, NONE, CHECKS),
+    spec("overflow_check", NONE, NONE, CHECKS),
+    spec("no_overflow", NONE, NONE, CHECKS),
+    spec("wrapping", NONE, NONE, CHECKS),
+    spec("saturating", NONE, NONE, CHECKS),
+    spec(
+        "unsafe",
+        NONE,
+        NONE,
+        &[T::Function, T::Branch, T::Loop, T::Statement],
+    ),
+    spec(
+        "trusted",
+        NONE,
+        NONE,
+        &[T::Function, T::Branch, T::Loop, T::Statement],
+    ),
+    spec("pre", &[A::Expression], NONE, FUNCTION),
+    spec("post", &[A::Expression], NONE, FUNCTION),
+    spec("invariant", &[A::Expression], NONE, &[T::Function, T::Loop]),
+];
+
+/// Knows every hint the compiler understands and validates hint usage in an AST.
+pub struct HintRegistry {
+    specs: &'static [HintSpec],
+}
+
+impl HintRegistry {
+    pub fn new() -> Self {
+        Self { specs: HINTS }
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<&HintSpec> {
+        self.specs.iter().find(|spec| spec.name == name)
+    }
+
+    /// Returns true if `name` is a known hint that may be attached to `target`.
+    pub fn allows(&self, name: &str, target: HintTarget) -> bool {
+        self.lookup(name)
+            .is_some_and(|spec| spec.targets.contains(&target))
+    }
+
+    /// The misplaced and malformed hints of a program, in source order.
+    pub fn check_ast(&self, ast: &Ast) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for statement in &ast.statements {
+            self.check_statement(statement, &mut diagnostics);
+        }
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.span.line, diagnostic.span.column));
+        diagnostics
+    }
+
+    fn check_statement(&self, statement: &Statement, diagnostics: &mut Vec<Diagnostic>) {
+        match &statement.kind {
+            StatementKind::VariableDeclaration(var_decl) => {
+                if let Some(expression) = &var_decl.expression {
+                    self.check_expr(expression, diagnostics)
+                }
+            }
+            StatementKind::Assignment { expr, .. }
+            | StatementKind::CompoundAssignment { expr, .. } => self.check_expr(expr, diagnostics),
+            StatementKind::Store { target, expr } => {
+                self.check_expr(target, diagnostics);
+                self.check_expr(expr, diagnostics);
+            }
+            StatementKind::IncrementDecrement { .. } => {}
+            StatementKind::TypeDeclaration { contracts, .. } => {
+                for method in contracts.iter().flat_map(|contract| &contract.methods) {
+                    self.check_function(method, diagnostics);
+                }
+            }
+            StatementKind::Expression(expr) => self.check_expr(expr, diagnostics),
+            StatementKind::FunctionDeclaration(function) => {
+                self.check_function(function, diagnostics)
+            }
+            StatementKind::Return(expr) => {
+                if let Some(expr) = expr {
+                    self.check_expr(expr, diagnostics);
+                }
+            }
+            StatementKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.check_expr(condition, diagnostics);
+                for statement in then_branch.iter().chain(else_branch.iter().flatten()) {
+                    self.check_statement(statement, diagnostics);
+                }
+            }
+            StatementKind::For { condition, body } => {
+                self.check_expr(condition, diagnostics);
+                for statement in body {
+                    self.check_statement(statement, diagnostics);
+                }
+            }
+            StatementKind::Hinted { hints, statement } => {
+                let target = statement_target(statement);
+                for hint in hints {
+                    self.check_hint(hint, target, diagnostics);
+                }
+                self.check_statement(statement, diagnostics);
+            }
+            StatementKind::Match { scrutinee, arms } => {
+                self.check_expr(scrutinee, diagnostics);
+                for statement in arms.iter().flat_map(|arm| &arm.body) {
+                    self.check_statement(statement, diagnostics);
+                }
+            }
+        }
+    }
+
+    fn check_function(&self, function: &Function, diagnostics: &mut Vec<Diagnostic>) {
+        for hint in &function.hints {
+            self.check_hint(hint, HintTarget::Function, diagnostics);
+        }
+        for parameter in &function.signature.parameters {
+            for hint in &parameter.hints {
+                self.check_hint(hint, HintTarget::Parameter, diagnostics);
+            }
+        }
+        if let crate::parser::FunctionBody::Statements(statements) = &function.body {
+            for statement in statements {
+                self.check_statement(statement, diagnostics);
+            }
+        }
+    }
+
+    fn check_expr(&self, expr: &Expression, diagnostics: &mut Vec<Diagnostic>) {
+        match &expr.kind {
+            ExpressionKind::Binary { left, right, .. } => {
+                self.check_expr(left, diagnostics);
+                self.check_expr(right, diagnostics);
+            }
+            ExpressionKind::Unary { expression, .. }
+            | ExpressionKind::Grouping(expression)
+            | ExpressionKind::Cast { expression, .. }
+            | ExpressionKind::Propagate(expression)
+            | ExpressionKind::Field {
+                receiver: expression,
+                ..
+            } => self.check_expr(expression, diagnostics),
+            ExpressionKind::Variant { payload, .. } | ExpressionKind::Panic(payload) => {
+                if let Some(payload) = payload {
+                    self.check_expr(payload, diagnostics);
+                }
+            }
+            ExpressionKind::MethodCall { receiver, args, .. } => {
+                self.check_expr(receiver, diagnostics);
+                for arg in args {
+                    self.check_expr(arg, diagnostics);
+                }
+            }
+            ExpressionKind::Call { callee, args } => {
+                self.check_expr(callee, diagnostics);
+                for arg in args {
+                    self.check_expr(arg, diagnostics);
+                }
+            }
+            ExpressionKind::Tuple(elements) => {
+                for element in elements {
+                    self.check_expr(element, diagnostics);
+                }
+            }
+            ExpressionKind::Struct(fields) => {
+                for (_, value) in fields {
+                    self.check_expr(value, diagnostics);
+                }
+            }
+            ExpressionKind::Hinted { hints, expression } => {
+                for hint in hints {
+                    self.check_hint(hint, HintTarget::Expression, diagnostics);
+                }
+                self.check_expr(expression, diagnostics);
+            }
+            ExpressionKind::Lambda(lambda) => {
+                for parameter in &lambda.parameters {
+                    for hint in &parameter.hints {
+                        self.check_hint(hint, HintTarget::Parameter, diagnostics);
+                    }
+                }
+                for statement in &lambda.body {
+                    self.check_statement(statement, diagnostics);
+                }
+            }
+            ExpressionKind::Literal(_) | ExpressionKind::Identifier(_) => {}
+        }
+    }
+
+    fn check_hint(&self, hint: &Hint, target: HintTarget, diagnostics: &mut Vec<Diagnostic>) {
+        let Some(spec) = self.lookup(&hint.name) else {
+            diagnostics.push(Diagnostic::warning(
+                "W0003",
+                format!("Unknown hint '@{}' is ignored", hint.name),
+                hint.span,
+            ));
+            return;
+        };
+
+        if !spec.targets.contains(&target) {
+            diagnostics.push(Diagnostic::error(
+                "E0500",
+                format!("Hint '@{}' cannot be applied to {}", hint.name, target),
+                hint.span,
+            ));
+        }
+        if hint.placement == HintPlacement::Promise && target != HintTarget::Parameter {
+            diagnostics.push(Diagnostic::error(
+                "E0500",
+                format!("Hint '@{}' can only promise about a parameter", hint.name),
+                hint.span,
+            ));
+        }
+
+        let min = spec.required.len();
+        let max = min + spec.optional.len();
+        let count = hint.arguments.len();
+        if count < min || count > max {
+            let expected = if min == max {
+                format!("{}", min)
+            } else {
+                format!("{} to {}", min, max)
+            };
+            diagnostics.push(Diagnostic::error(
+                "E0501",
+                format!(
+                    "Hint '@{}' expects {} argument(s), got {}",
+                    hint.name, expected, count
+                ),
+                hint.span,
+            ));
+            return;
+        }
+
+        let kinds = spec.required.iter().chain(spec.optional.iter());
+        for (i, (argument, kind)) in hint.arguments.iter().zip(kinds).enumerate() {
+            let matches = match kind {
+                HintArgument::Integer => {
+                    matches!(argument.kind, ExpressionKind::Literal(Literal::Integer(_)))
+                }
+                HintArgument::Identifier => matches!(argument.kind, ExpressionKind::Identifier(_)),
+                HintArgument::Expression => true,
+            };
+            if !matches {
+                diagnostics.push(Diagnostic::error(
+                    "E0501",
+                    format!(
+                        "Argument {} of hint '@{}' must be {}",
+                        i + 1,
+                        hint.name,
+                        kind
+                    ),
+                    argument.span,
+                ));
+            }
+        }
+    }
+}
+
+/// Decide which kind of element a statement hint applies to.
+fn statement_target(statement: &Statement) -> HintTarget {
+    match &statement.kind {
+        StatementKind::VariableDeclaration(_) => HintTarget::Variable,
+        StatementKind::If { .. } => HintTarget::Branch,
+        StatementKind::For { .. } => HintTarget::Loop,
+        _ => HintTarget::Statement,
+    }
+}
@@ -0,0 +1,149 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::{
+        diagnostic::Diagnostic,
+        hints::HintRegistry,
+        parser::{ExpressionKind, FunctionBody, HintPlacement, StatementKind},
+        test_support::parse_with_promises as parse,
+        token::Literal,
+    };
+
+    fn check(src: &str) -> Vec<Diagnostic> {
+        HintRegistry::new().check_ast(&parse(src))
+    }
+
+    #[test]
+    fn test_function_hints() {
+        let ast = parse("@inline @aligned(32) function f() {}");
+        let StatementKind::FunctionDeclaration(function) = &ast.statements[0].kind else {
+            panic!("expected a function declaration");
+        };
+        assert_eq!(function.hints.len(), 2);
+        assert_eq!(function.hints[0].name, "inline");
+        assert!(matches!(
+            function.hints[1].arguments[0].kind,
+            ExpressionKind::Literal(Literal::Integer(32))
+        ));
+    }
+
+    #[test]
+    fn test_requirement_and_promise_placement() {
+        let ast = parse("@readonly(src) function copy(@nonnull src int, dest int) {}");
+        let StatementKind::FunctionDeclaration(function) = &ast.statements[0].kind else {
+            panic!("expected a function declaration");
+        };
+        assert!(function.hints.is_empty());
+        let src = &function.signature.parameters[0];
+        assert_eq!(src.hints.len(), 2);
+        assert_eq!(src.hints[0].name, "nonnull");
+        assert_eq!(src.hints[0].placement, HintPlacement::Inline);
+        assert_eq!(src.hints[1].name, "readonly");
+        assert_eq!(src.hints[1].placement, HintPlacement::Promise);
+        assert!(src.hints[1].arguments.is_empty());
+        assert!(function.signature.parameters[1].hints.is_empty());
+
+        // Promises are bound by the hint pass, for nested functions too
+        let ast = parse("function outer() { @readonly(x) function inner(x int) {} }");
+        let StatementKind::FunctionDeclaration(outer) = &ast.statements[0].kind else {
+            panic!("expected a function declaration");
+        };
+        let FunctionBody::Statements(body) = &outer.body else {
+            panic!("expected a body");
+        };
+        let StatementKind::FunctionDeclaration(inner) = &body[0].kind else {
+            panic!("expected a function declaration");
+        };
+        assert!(inner.hints.is_empty());
+        assert_eq!(
+            inner.signature.parameters[0].hints[0].placement,
+            HintPlacement::Promise
+        );
+    }
+
+    #[test]
+    fn test_statement_and_expression_hints() {
+        let ast = parse("@aligned(16) let x int = @likely 1;");
+        let StatementKind::Hinted { hints, statement } = &ast.statements[0].kind else {
+            panic!("expected a hinted statement");
+        };
+        assert_eq!(hints[0].name, "aligned");
+        let StatementKind::VariableDeclaration(var_decl) = &statement.kind else {
+            panic!("expected a variable declaration");
+        };
+        assert!(matches!(
+            var_decl.expression.as_ref().unwrap().kind,
+            ExpressionKind::Hinted { .. }
+        ));
+    }
+
+    #[test]
+    fn test_valid_hints() {
+        let diagnostics = check(
+            "@pure @deprecated function f(@aligned(32) a int) int { @likely if true { return a; } return a; }",
+        );
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn test_unknown_hint_warns() {
+        let diagnostics = check("@sparkly function f() {}");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(!diagnostics[0].is_error());
+        assert_eq!(diagnostics[0].code, "W0003");
+        assert_eq!(diagnostics[0].message, "Unknown hint '@sparkly' is ignored");
+    }
+
+    #[test]
+    fn test_hint_wrong_target() {
+        let diagnostics = check("@inline let x int = 1;");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "E0500");
+        assert_eq!(
+            diagnostics[0].message,
+            "Hint '@inline' cannot be applied to a variable"
+        );
+    }
+
+    #[test]
+    fn test_loop_hints() {
+        let diagnostics = check(
+            "function f(n int) {
+                 let i int = 1;
+                 @unroll(4) @vectorize for i < n {
+                     i++;
+                 }
+             }",
+        );
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        // A call is a plain statement, whatever the function is named
+        let diagnostics = check(
+            "function while(c bool) {}
+             @unroll(4) while(true);",
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "Hint '@unroll' cannot be applied to a statement"
+        );
+    }
+
+    #[test]
+    fn test_hint_wrong_arguments() {
+        let missing = check("function f(@aligned a int) {}");
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].code, "E0501");
+        assert_eq!(
+            missing[0].message,
+            "Hint '@aligned' expects 1 argument(s), got 0"
+        );
+
+        let wrong_kind = check("function f(@aligned(true) a int) {}");
+        assert_eq!(wrong_kind.len(), 1);
+        assert_eq!(wrong_kind[0].code, "E0501");
+        assert_eq!(
+            wrong_kind[0].message,
+            "Argument 1 of hint '@aligned' must be an integer"
+        );
+    }
+}
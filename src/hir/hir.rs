@@ -0,0 +1,262 @@
+use crate::parser::Hint;
+use crate::token::{Literal, Operator, Span, TypeIdentifier};
+
+/// The program after type checking. Every expression carries its type and
+/// every name refers to the binding it resolved to, so later phases never
+/// look a name up or work a type out again.
+#[derive(Debug, Clone)]
+pub struct Hir {
+    // Every variable and parameter of the program, indexed by BindingId
+    pub bindings: Vec<Binding>,
+    pub statements: Vec<Statement>,
+}
+
+impl Hir {
+    pub fn binding(&self, id: BindingId) -> &Binding {
+        &self.bindings[id.0]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BindingId(pub usize);
+
+/// A variable or parameter. Shadowing declares a new binding, so two
+/// bindings may share a name.
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub name: String,
+    pub binding_type: TypeIdentifier,
+    pub span: Span,
+}
+
+/// What a name used as a value refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    Local(BindingId),
+    // A named function, user-defined or from the standard library
+    Function(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub expr_type: TypeIdentifier,
+    pub span: Span,
+}
+
+// Groupings are gone, the tree shape already holds their meaning
+#[derive(Debug, Clone)]
+pub enum ExpressionKind {
+    Binary {
+        left: Box<Expression>,
+        operator: Operator,
+        right: Box<Expression>,
+    },
+    Unary {
+        operator: Operator,
+        expression: Box<Expression>,
+    },
+    Literal(Literal),
+    Name(Resolution),
+    // Reads a binding whose value moves out of it, a unique pointer or a value
+    // holding one. The binding no longer owns it and does not drop it.
+    Move(BindingId),
+    Call {
+        callee: Box<Expression>,
+        args: Vec<Expression>,
+    },
+    Hinted {
+        hints: Vec<Hint>,
+        expression: Box<Expression>,
+    },
+    Lambda(Box<Lambda>),
+    Tuple(Vec<Expression>),
+    Struct(Vec<(String, Expression)>),
+    // Converts the value of `expression` to the type of the cast
+    Cast {
+        expression: Box<Expression>,
+        kind: CastKind,
+    },
+    // A value of the sum type of the expression
+    Variant {
+        label: String,
+        payload: Option<Box<Expression>>,
+    },
+    Field {
+        receiver: Box<Expression>,
+        field: String,
+    },
+    // The method of the type of the receiver, or for a contract value of the
+    // type it holds
+    MethodCall {
+        receiver: Box<Expression>,
+        method: String,
+        args: Vec<Expression>,
+    },
+    // The 'Ok payload of a result, or a return of its 'Err from the function
+    Propagate(Box<Expression>),
+    // Stops the program, reporting the span of the expression, the optional
+    // string message and the call stack, and exits with PANIC_EXIT_CODE
+    Panic(Option<Box<Expression>>),
+}
+
+/// The status a program that panics exits with, apart from the 1 of a
+/// program that reports an error itself.
+#[allow(dead_code)]
+pub const PANIC_EXIT_CODE: i32 = 101;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CastKind {
+    // A conversion from the standard library. When the value might not fit
+    // the target type it is checked first, and the cast gives a result: 'Ok
+    // with the converted value, or 'Err with an Error for one that does not.
+    Safe(Option<RangeCheck>),
+    // The initializer of a @raw_cast let, its bits reinterpreted as they are
+    Raw,
+}
+
+/// What a safe cast checks at run time before converting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RangeCheck {
+    // The float is finite and, truncated, within the range of int
+    FloatToInt,
+    // The int is a Unicode scalar value: 0 to 0x10FFFF, surrogates excluded
+    IntToChar,
+    // The int is 0 or 1
+    IntToBool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub binding: BindingId,
+    pub hints: Vec<Hint>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub hints: Vec<Hint>,
+    pub parameters: Vec<Parameter>,
+    pub return_type: TypeIdentifier,
+    pub body: Vec<Statement>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Lambda {
+    pub parameters: Vec<Parameter>,
+    pub return_type: TypeIdentifier,
+    // Locals of the enclosing function the lambda reads, copied when it is created
+    pub captures: Vec<BindingId>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+// Forward declarations are gone, calls refer to the definition
+#[derive(Debug, Clone)]
+pub enum StatementKind {
+    Let {
+        pattern: Pattern,
+        value: Option<Expression>,
+    },
+    Assignment {
+        target: BindingId,
+        value: Expression,
+    },
+    // A write through the pointer the 'deref' target reads
+    Store {
+        target: Expression,
+        value: Expression,
+    },
+    CompoundAssignment {
+        target: BindingId,
+        operator: Operator,
+        value: Expression,
+    },
+    IncrementDecrement {
+        target: BindingId,
+        operator: Operator,
+    },
+    Expression(Expression),
+    Function(Function),
+    TypeDeclaration {
+        name: String,
+        definition: TypeIdentifier,
+        contracts: Vec<ContractImplementation>,
+    },
+    Return(Option<Expression>),
+    If {
+        condition: Expression,
+        then_branch: Vec<Statement>,
+        else_branch: Option<Vec<Statement>>,
+    },
+    // Runs the body while the condition holds, each time a block of its own
+    For {
+        condition: Expression,
+        body: Vec<Statement>,
+    },
+    Hinted {
+        hints: Vec<Hint>,
+        statement: Box<Statement>,
+    },
+    // Runs the first arm whose pattern matches, the arms cover every value
+    Match {
+        scrutinee: Expression,
+        arms: Vec<MatchArm>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContractImplementation {
+    pub contract: String,
+    pub methods: Vec<Function>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+impl Pattern {
+    /// The bindings this pattern declares, in source order.
+    pub fn bindings(&self) -> Vec<BindingId> {
+        match &self.kind {
+            PatternKind::Wildcard | PatternKind::Literal(_) => Vec::new(),
+            PatternKind::Binding(id) => vec![*id],
+            PatternKind::Tuple(elements) => elements.iter().flat_map(Pattern::bindings).collect(),
+            PatternKind::Labeled(fields) => fields
+                .iter()
+                .flat_map(|(_, pattern)| pattern.bindings())
+                .collect(),
+            PatternKind::Variant { payload, .. } => payload
+                .as_ref()
+                .map(|payload| payload.bindings())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PatternKind {
+    Wildcard,
+    Binding(BindingId),
+    Literal(Literal),
+    Tuple(Vec<Pattern>),
+    Labeled(Vec<(String, Pattern)>),
+    Variant {
+        label: String,
+        payload: Option<Box<Pattern>>,
+    },
+}
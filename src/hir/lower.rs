@@ -0,0 +1,397 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    hir::hir::{
+        Binding, BindingId, CastKind, ContractImplementation, Expression, ExpressionKind, Function,
+        Hir, Lambda, MatchArm, Parameter, Pattern, PatternKind, Resolution, Statement,
+        StatementKind,
+    },
+    parser::{self, Ast, FunctionBody, FunctionParameter},
+    token::{Span, TypeIdentifier},
+};
+
+/// What the type checker learned about the AST, keyed by the span of the
+/// node it is about. Lowering reads it back to build the HIR.
+#[derive(Debug, Clone, Default)]
+pub struct Annotations {
+    bindings: Vec<Binding>,
+    // The binding each declaring pattern or parameter introduced
+    declarations: HashMap<Span, BindingId>,
+    // What each name, or the target of each assignment, resolved to
+    resolutions: HashMap<Span, Resolution>,
+    types: HashMap<Span, TypeIdentifier>,
+    // Locals each lambda captures
+    captures: HashMap<Span, Vec<BindingId>>,
+    // How each cast converts. A @raw_cast let is keyed by its declaration.
+    casts: HashMap<Span, CastKind>,
+    // Names whose value moves out of their binding where they are read
+    moves: HashSet<Span>,
+}
+
+impl Annotations {
+    /// Register a new binding declared at `span`.
+    pub fn declare(&mut self, name: &str, binding_type: TypeIdentifier, span: Span) -> BindingId {
+        let id = BindingId(self.bindings.len());
+        self.bindings.push(Binding {
+            name: name.to_string(),
+            binding_type,
+            span,
+        });
+        self.declarations.insert(span, id);
+        id
+    }
+
+    pub fn resolve(&mut self, span: Span, resolution: Resolution) {
+        self.resolutions.insert(span, resolution);
+    }
+
+    pub fn record_type(&mut self, span: Span, expr_type: TypeIdentifier) {
+        self.types.insert(span, expr_type);
+    }
+
+    pub fn record_captures(&mut self, span: Span, captures: Vec<BindingId>) {
+        self.captures.insert(span, captures);
+    }
+
+    pub fn record_cast(&mut self, span: Span, kind: CastKind) {
+        self.casts.insert(span, kind);
+    }
+
+    pub fn record_move(&mut self, span: Span) {
+        self.moves.insert(span);
+    }
+}
+
+/// Build the HIR of a program that type checked without errors, from the
+/// annotations its check left behind.
+pub fn lower(ast: &Ast, annotations: &Annotations) -> Hir {
+    let lowering = Lowering { annotations };
+    Hir {
+        bindings: annotations.bindings.clone(),
+        statements: lowering.lower_block(&ast.statements),
+    }
+}
+
+struct Lowering<'a> {
+    annotations: &'a Annotations,
+}
+
+impl Lowering<'_> {
+    fn lower_block(&self, statements: &[parser::Statement]) -> Vec<Statement> {
+        statements
+            .iter()
+            .filter_map(|statement| self.lower_statement(statement))
+            .collect()
+    }
+
+    fn lower_statement(&self, statement: &parser::Statement) -> Option<Statement> {
+        let kind = match &statement.kind {
+            parser::StatementKind::VariableDeclaration(var_decl) => {
+                let pattern = self.lower_pattern(&var_decl.pattern);
+                let value = var_decl.expression.as_ref().map(|expr| {
+                    let value = self.lower_expr(expr);
+                    match self.annotations.casts.get(&var_decl.span) {
+                        // The initializer of a @raw_cast let takes the declared type
+                        Some(&kind) => Expression {
+                            expr_type: self.binding_type(&pattern),
+                            span: value.span,
+                            kind: ExpressionKind::Cast {
+                                expression: Box::new(value),
+                                kind,
+                            },
+                        },
+                        None => value,
+                    }
+                });
+                StatementKind::Let { pattern, value }
+            }
+            parser::StatementKind::Assignment { expr, .. } => StatementKind::Assignment {
+                target: self.target(statement.span),
+                value: self.lower_expr(expr),
+            },
+            parser::StatementKind::Store { target, expr } => StatementKind::Store {
+                target: self.lower_expr(target),
+                value: self.lower_expr(expr),
+            },
+            parser::StatementKind::CompoundAssignment { operator, expr, .. } => {
+                StatementKind::CompoundAssignment {
+                    target: self.target(statement.span),
+                    operator: *operator,
+                    value: self.lower_expr(expr),
+                }
+            }
+            parser::StatementKind::IncrementDecrement { operator, .. } => {
+                StatementKind::IncrementDecrement {
+                    target: self.target(statement.span),
+                    operator: *operator,
+                }
+            }
+            parser::StatementKind::Expression(expr) => {
+                StatementKind::Expression(self.lower_expr(expr))
+            }
+            parser::StatementKind::FunctionDeclaration(function) => {
+                StatementKind::Function(self.lower_function(function)?)
+            }
+            parser::StatementKind::TypeDeclaration {
+                name,
+                definition,
+                contracts,
+            } => StatementKind::TypeDeclaration {
+                name: name.clone(),
+                definition: definition.clone(),
+                contracts: contracts
+                    .iter()
+                    .map(|contract| ContractImplementation {
+                        contract: contract.contract.clone(),
+                        methods: contract
+                            .methods
+                            .iter()
+                            .filter_map(|method| self.lower_function(method))
+                            .collect(),
+                    })
+                    .collect(),
+            },
+            parser::StatementKind::Return(expr) => {
+                StatementKind::Return(expr.as_ref().map(|expr| self.lower_expr(expr)))
+            }
+            parser::StatementKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => StatementKind::If {
+                condition: self.lower_expr(condition),
+                then_branch: self.lower_block(then_branch),
+                else_branch: else_branch
+                    .as_ref()
+                    .map(|else_branch| self.lower_block(else_branch)),
+            },
+            parser::StatementKind::For { condition, body } => StatementKind::For {
+                condition: self.lower_expr(condition),
+                body: self.lower_block(body),
+            },
+            parser::StatementKind::Hinted { hints, statement } => StatementKind::Hinted {
+                hints: hints.clone(),
+                statement: Box::new(self.lower_statement(statement)?),
+            },
+            parser::StatementKind::Match { scrutinee, arms } => StatementKind::Match {
+                scrutinee: self.lower_expr(scrutinee),
+                arms: arms
+                    .iter()
+                    .map(|arm| MatchArm {
+                        pattern: self.lower_pattern(&arm.pattern),
+                        body: self.lower_block(&arm.body),
+                    })
+                    .collect(),
+            },
+        };
+        Some(Statement {
+            kind,
+            span: statement.span,
+        })
+    }
+
+    /// Lower a function or method definition. Forward declarations have
+    /// nothing to lower.
+    fn lower_function(&self, function: &parser::Function) -> Option<Function> {
+        let FunctionBody::Statements(body) = &function.body else {
+            return None;
+        };
+        Some(Function {
+            name: function.signature.name.clone(),
+            hints: function.hints.clone(),
+            parameters: self.lower_parameters(&function.signature.parameters),
+            return_type: function
+                .signature
+                .return_type
+                .clone()
+                .unwrap_or(TypeIdentifier::Unit),
+            body: self.lower_block(body),
+            span: function.span,
+        })
+    }
+
+    fn lower_expr(&self, expr: &parser::Expression) -> Expression {
+        let kind = match &expr.kind {
+            parser::ExpressionKind::Grouping(inner) => return self.lower_expr(inner),
+            parser::ExpressionKind::Binary {
+                left,
+                operator,
+                right,
+            } => ExpressionKind::Binary {
+                left: Box::new(self.lower_expr(left)),
+                operator: *operator,
+                right: Box::new(self.lower_expr(right)),
+            },
+            parser::ExpressionKind::Unary {
+                operator,
+                expression,
+            } => ExpressionKind::Unary {
+                operator: *operator,
+                expression: Box::new(self.lower_expr(expression)),
+            },
+            parser::ExpressionKind::Literal(literal) => ExpressionKind::Literal(literal.clone()),
+            parser::ExpressionKind::Identifier(_) => match self.resolution(expr.span) {
+                Resolution::Local(id) if self.annotations.moves.contains(&expr.span) => {
+                    ExpressionKind::Move(id)
+                }
+                resolution => ExpressionKind::Name(resolution),
+            },
+            parser::ExpressionKind::Call { callee, args } => ExpressionKind::Call {
+                callee: Box::new(self.lower_expr(callee)),
+                args: args.iter().map(|arg| self.lower_expr(arg)).collect(),
+            },
+            parser::ExpressionKind::Hinted { hints, expression } => ExpressionKind::Hinted {
+                hints: hints.clone(),
+                expression: Box::new(self.lower_expr(expression)),
+            },
+            parser::ExpressionKind::Lambda(lambda) => ExpressionKind::Lambda(Box::new(Lambda {
+                parameters: self.lower_parameters(&lambda.parameters),
+                return_type: lambda.return_type.clone().unwrap_or(TypeIdentifier::Unit),
+                captures: self
+                    .annotations
+                    .captures
+                    .get(&expr.span)
+                    .cloned()
+                    .unwrap_or_default(),
+                body: self.lower_block(&lambda.body),
+            })),
+            parser::ExpressionKind::Tuple(elements) => ExpressionKind::Tuple(
+                elements
+                    .iter()
+                    .map(|element| self.lower_expr(element))
+                    .collect(),
+            ),
+            parser::ExpressionKind::Struct(fields) => ExpressionKind::Struct(
+                fields
+                    .iter()
+                    .map(|(label, value)| (label.clone(), self.lower_expr(value)))
+                    .collect(),
+            ),
+            parser::ExpressionKind::Cast { expression, .. } => ExpressionKind::Cast {
+                expression: Box::new(self.lower_expr(expression)),
+                kind: *self
+                    .annotations
+                    .casts
+                    .get(&expr.span)
+                    .unwrap_or_else(|| panic!("cast at {} was never checked", expr.span)),
+            },
+            parser::ExpressionKind::Variant { label, payload, .. } => ExpressionKind::Variant {
+                label: label.clone(),
+                payload: payload
+                    .as_ref()
+                    .map(|payload| Box::new(self.lower_expr(payload))),
+            },
+            parser::ExpressionKind::Field { receiver, field } => ExpressionKind::Field {
+                receiver: Box::new(self.lower_expr(receiver)),
+                field: field.clone(),
+            },
+            parser::ExpressionKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => ExpressionKind::MethodCall {
+                receiver: Box::new(self.lower_expr(receiver)),
+                method: method.clone(),
+                args: args.iter().map(|arg| self.lower_expr(arg)).collect(),
+            },
+            parser::ExpressionKind::Propagate(expression) => {
+                ExpressionKind::Propagate(Box::new(self.lower_expr(expression)))
+            }
+            parser::ExpressionKind::Panic(message) => ExpressionKind::Panic(
+                message
+                    .as_ref()
+                    .map(|message| Box::new(self.lower_expr(message))),
+            ),
+        };
+        let expr_type = self
+            .annotations
+            .types
+            .get(&expr.span)
+            .cloned()
+            .unwrap_or_else(|| panic!("expression at {} was never type checked", expr.span));
+        Expression {
+            kind,
+            expr_type,
+            span: expr.span,
+        }
+    }
+
+    fn lower_pattern(&self, pattern: &parser::Pattern) -> Pattern {
+        let kind = match &pattern.kind {
+            parser::PatternKind::Wildcard => PatternKind::Wildcard,
+            parser::PatternKind::Binding { .. } => {
+                PatternKind::Binding(self.declaration(pattern.span))
+            }
+            parser::PatternKind::Literal(literal) => PatternKind::Literal(literal.clone()),
+            parser::PatternKind::Tuple(elements) => PatternKind::Tuple(
+                elements
+                    .iter()
+                    .map(|element| self.lower_pattern(element))
+                    .collect(),
+            ),
+            parser::PatternKind::Labeled(fields) => PatternKind::Labeled(
+                fields
+                    .iter()
+                    .map(|(label, field)| (label.clone(), self.lower_pattern(field)))
+                    .collect(),
+            ),
+            parser::PatternKind::Variant { label, payload } => PatternKind::Variant {
+                label: label.clone(),
+                payload: payload
+                    .as_ref()
+                    .map(|payload| Box::new(self.lower_pattern(payload))),
+            },
+        };
+        Pattern {
+            kind,
+            span: pattern.span,
+        }
+    }
+
+    fn lower_parameters(&self, parameters: &[FunctionParameter]) -> Vec<Parameter> {
+        parameters
+            .iter()
+            .map(|parameter| Parameter {
+                binding: self.declaration(parameter.span),
+                hints: parameter.hints.clone(),
+            })
+            .collect()
+    }
+
+    /// The type of the variable a single-binding pattern declares.
+    fn binding_type(&self, pattern: &Pattern) -> TypeIdentifier {
+        let PatternKind::Binding(id) = pattern.kind else {
+            panic!(
+                "raw cast at {} declares more than one variable",
+                pattern.span
+            );
+        };
+        self.annotations.bindings[id.0].binding_type.clone()
+    }
+
+    fn declaration(&self, span: Span) -> BindingId {
+        *self
+            .annotations
+            .declarations
+            .get(&span)
+            .unwrap_or_else(|| panic!("binding at {} was never declared", span))
+    }
+
+    fn resolution(&self, span: Span) -> Resolution {
+        self.annotations
+            .resolutions
+            .get(&span)
+            .cloned()
+            .unwrap_or_else(|| panic!("name at {} was never resolved", span))
+    }
+
+    /// The variable an assignment statement at `span` writes to.
+    fn target(&self, span: Span) -> BindingId {
+        match self.resolution(span) {
+            Resolution::Local(id) => id,
+            Resolution::Function(name) => {
+                panic!("assignment at {} targets function '{}'", span, name)
+            }
+        }
+    }
+}
@@ -0,0 +1,8 @@
+// Only the escape analysis and the debug dump read the HIR yet; lowering and the interpreter will
+#[allow(clippy::module_inception, dead_code)]
+pub mod hir;
+pub mod lower;
+mod tests;
+
+pub use hir::Hir;
+pub use lower::{Annotations, lower};
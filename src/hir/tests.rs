@@ -0,0 +1,295 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::{
+        hir::hir::{CastKind, ExpressionKind, PatternKind, RangeCheck, Resolution, StatementKind},
+        test_support::lower,
+        token::TypeIdentifier,
+    };
+
+    #[test]
+    fn test_names_resolve_to_their_binding() {
+        let hir = lower(
+            "let x int = 1;
+             if true {
+                 let x bool = true;
+                 let y bool = x;
+             }
+             let z int = x;",
+        );
+        let names: Vec<_> = hir.bindings.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, ["x", "x", "y", "z"]);
+
+        let StatementKind::If { then_branch, .. } = &hir.statements[1].kind else {
+            panic!("expected an if, got {:?}", hir.statements[1]);
+        };
+        let StatementKind::Let {
+            value: Some(inner), ..
+        } = &then_branch[1].kind
+        else {
+            panic!("expected a let, got {:?}", then_branch[1]);
+        };
+        let StatementKind::Let {
+            value: Some(outer), ..
+        } = &hir.statements[2].kind
+        else {
+            panic!("expected a let, got {:?}", hir.statements[2]);
+        };
+        let (
+            ExpressionKind::Name(Resolution::Local(inner)),
+            ExpressionKind::Name(Resolution::Local(outer)),
+        ) = (&inner.kind, &outer.kind)
+        else {
+            panic!("expected names, got {:?} and {:?}", inner, outer);
+        };
+        assert_eq!(hir.binding(*inner).binding_type, TypeIdentifier::Boolean);
+        assert_eq!(hir.binding(*outer).binding_type, TypeIdentifier::Number);
+    }
+
+    #[test]
+    fn test_for_body_is_a_block() {
+        let hir = lower(
+            "let i int = 1;
+             for i < 3 {
+                 let i bool = true;
+             }",
+        );
+        let StatementKind::For { condition, body } = &hir.statements[1].kind else {
+            panic!("expected a for, got {:?}", hir.statements[1]);
+        };
+        assert_eq!(condition.expr_type, TypeIdentifier::Boolean);
+        let StatementKind::Let { pattern, .. } = &body[0].kind else {
+            panic!("expected a let, got {:?}", body[0]);
+        };
+        let PatternKind::Binding(id) = pattern.kind else {
+            panic!("expected a binding, got {:?}", pattern);
+        };
+        assert_eq!(hir.binding(id).binding_type, TypeIdentifier::Boolean);
+    }
+
+    #[test]
+    fn test_expressions_carry_their_type() {
+        let hir = lower("let b bool = (1 + 2) < 4;");
+        let StatementKind::Let {
+            value: Some(value), ..
+        } = &hir.statements[0].kind
+        else {
+            panic!("expected a let, got {:?}", hir.statements[0]);
+        };
+        assert_eq!(value.expr_type, TypeIdentifier::Boolean);
+        // The grouping is gone, the sum sits right under the comparison
+        let ExpressionKind::Binary { left, .. } = &value.kind else {
+            panic!("expected a comparison, got {:?}", value);
+        };
+        assert!(matches!(left.kind, ExpressionKind::Binary { .. }));
+        assert_eq!(left.expr_type, TypeIdentifier::Number);
+    }
+
+    #[test]
+    fn test_functions_and_assignments() {
+        let hir = lower(
+            "function twice(n int) int;
+             let total int;
+             total = twice(2);
+             total++;
+             function twice(n int) int { return n * 2; }",
+        );
+        // The forward declaration is gone
+        assert_eq!(hir.statements.len(), 4);
+        let StatementKind::Assignment { target, value } = &hir.statements[1].kind else {
+            panic!("expected an assignment, got {:?}", hir.statements[1]);
+        };
+        assert_eq!(hir.binding(*target).name, "total");
+        let ExpressionKind::Call { callee, .. } = &value.kind else {
+            panic!("expected a call, got {:?}", value);
+        };
+        assert!(matches!(
+            &callee.kind,
+            ExpressionKind::Name(Resolution::Function(name)) if name == "twice"
+        ));
+        assert!(matches!(
+            hir.statements[2].kind,
+            StatementKind::IncrementDecrement { target: t, .. } if t == *target
+        ));
+        let StatementKind::Function(function) = &hir.statements[3].kind else {
+            panic!("expected a function, got {:?}", hir.statements[3]);
+        };
+        assert_eq!(hir.binding(function.parameters[0].binding).name, "n");
+        assert_eq!(function.return_type, TypeIdentifier::Number);
+    }
+
+    #[test]
+    fn test_lambdas_and_destructuring() {
+        let hir = lower(
+            "function apply(f (int) -> int, v int) int { return f(v); }
+             function outer(k int) int {
+                 let (a, _) = (k, true);
+                 return apply(function(n) int { return n + a; }, 1);
+             }",
+        );
+        let StatementKind::Function(outer) = &hir.statements[1].kind else {
+            panic!("expected a function, got {:?}", hir.statements[1]);
+        };
+        let StatementKind::Let { pattern, .. } = &outer.body[0].kind else {
+            panic!("expected a let, got {:?}", outer.body[0]);
+        };
+        let PatternKind::Tuple(elements) = &pattern.kind else {
+            panic!("expected a tuple pattern, got {:?}", pattern);
+        };
+        let PatternKind::Binding(a) = elements[0].kind else {
+            panic!("expected a binding, got {:?}", elements[0]);
+        };
+        assert!(matches!(elements[1].kind, PatternKind::Wildcard));
+
+        let StatementKind::Return(Some(call)) = &outer.body[1].kind else {
+            panic!("expected a return, got {:?}", outer.body[1]);
+        };
+        let ExpressionKind::Call { args, .. } = &call.kind else {
+            panic!("expected a call, got {:?}", call);
+        };
+        let ExpressionKind::Lambda(lambda) = &args[0].kind else {
+            panic!("expected a lambda, got {:?}", args[0]);
+        };
+        // The parameter type was inferred from 'apply'
+        let n = hir.binding(lambda.parameters[0].binding);
+        assert_eq!(
+            (n.name.as_str(), &n.binding_type),
+            ("n", &TypeIdentifier::Number)
+        );
+        assert_eq!(lambda.captures, [a]);
+    }
+
+    #[test]
+    fn test_casts_carry_their_check() {
+        let hir = lower(
+            "type Checked = 'Ok int + 'Err Error;
+             function f() Checked {
+                 let a int = 2.5f.cast_int()?;
+                 let b float = cast_float(a);
+                 @raw_cast let c float = a;
+                 return 'Ok a;
+             }",
+        );
+        let StatementKind::Function(function) = &hir.statements[1].kind else {
+            panic!("expected a function, got {:?}", hir.statements[1]);
+        };
+        let casts: Vec<_> = function.body[..3]
+            .iter()
+            .map(|statement| {
+                let mut statement = statement;
+                while let StatementKind::Hinted {
+                    statement: inner, ..
+                } = &statement.kind
+                {
+                    statement = inner;
+                }
+                let StatementKind::Let {
+                    value: Some(value), ..
+                } = &statement.kind
+                else {
+                    panic!("expected a let, got {:?}", statement);
+                };
+                let value = match &value.kind {
+                    ExpressionKind::Propagate(result) => result,
+                    _ => value,
+                };
+                let ExpressionKind::Cast { expression, kind } = &value.kind else {
+                    panic!("expected a cast, got {:?}", value);
+                };
+                (expression.expr_type.clone(), value.expr_type.clone(), *kind)
+            })
+            .collect();
+        assert_eq!(
+            casts,
+            [
+                (
+                    TypeIdentifier::Float,
+                    TypeIdentifier::Sum(vec![
+                        ("Ok".into(), Some(TypeIdentifier::Number)),
+                        ("Err".into(), Some(TypeIdentifier::Named("Error".into()))),
+                    ]),
+                    CastKind::Safe(Some(RangeCheck::FloatToInt))
+                ),
+                (
+                    TypeIdentifier::Number,
+                    TypeIdentifier::Float,
+                    CastKind::Safe(None)
+                ),
+                (TypeIdentifier::Number, TypeIdentifier::Float, CastKind::Raw),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_arms_bind_and_variants_take_their_sum_type() {
+        let hir = lower(
+            "type Option = 'None + 'Some int;
+             function get(o Option) int {
+                 match o {
+                 | 'Some n -> return n
+                 | 'None -> return 1
+                 }
+             }
+             let o Option = 'Some 2;",
+        );
+        let StatementKind::Function(get) = &hir.statements[1].kind else {
+            panic!("expected a function, got {:?}", hir.statements[1]);
+        };
+        let StatementKind::Match { scrutinee, arms } = &get.body[0].kind else {
+            panic!("expected a match, got {:?}", get.body[0]);
+        };
+        assert_eq!(scrutinee.expr_type, TypeIdentifier::Named("Option".into()));
+        let PatternKind::Variant {
+            label,
+            payload: Some(payload),
+        } = &arms[0].pattern.kind
+        else {
+            panic!("expected a variant pattern, got {:?}", arms[0].pattern);
+        };
+        assert_eq!(label, "Some");
+        let PatternKind::Binding(n) = payload.kind else {
+            panic!("expected a binding, got {:?}", payload);
+        };
+        assert_eq!(hir.binding(n).binding_type, TypeIdentifier::Number);
+
+        let StatementKind::Let {
+            value: Some(value), ..
+        } = &hir.statements[2].kind
+        else {
+            panic!("expected a let, got {:?}", hir.statements[2]);
+        };
+        assert!(matches!(&value.kind, ExpressionKind::Variant { label, .. } if label == "Some"));
+        assert_eq!(value.expr_type, TypeIdentifier::Named("Option".into()));
+    }
+
+    #[test]
+    fn test_moves_name_the_binding_they_empty() {
+        let hir = lower(
+            "let x int = 5;
+             let p unique &int = &x;
+             let q unique &int = p;
+             let n int = deref q;",
+        );
+        let values: Vec<&ExpressionKind> = hir.statements[2..]
+            .iter()
+            .map(|statement| match &statement.kind {
+                StatementKind::Let {
+                    value: Some(value), ..
+                } => &value.kind,
+                _ => panic!("expected a let, got {:?}", statement),
+            })
+            .collect();
+        let ExpressionKind::Move(moved) = values[0] else {
+            panic!("expected a move, got {:?}", values[0]);
+        };
+        assert_eq!(hir.binding(*moved).name, "p");
+        // Reading through a pointer leaves it where it is
+        let ExpressionKind::Unary { expression, .. } = values[1] else {
+            panic!("expected a deref, got {:?}", values[1]);
+        };
+        assert!(matches!(
+            expression.kind,
+            ExpressionKind::Name(Resolution::Local(_))
+        ));
+    }
+}
@@ -1,7 +1,9 @@
 use core::panic;
 use std::char;
 
-use crate::token::{Keyword, Literal, Operator, Punctuation, Token, TokenKind, TypeIdentifier};
+use crate::token::{
+    Keyword, Literal, Operator, Punctuation, Span, Token, TokenKind, TypeIdentifier,
+};
 
 pub struct Lexer<'input> {
     input: &'input str,
@@ -64,7 +66,12 @@ impl<'input> Lexer<'input> {
                 self.bump();
                 if self.peek() == Some('=') {
                     self.bump();
-                    Some(TokenKind::Operator(Operator::Equals))
+                    if self.peek() == Some('=') {
+                        self.bump();
+                        Some(TokenKind::Operator(Operator::StrictEquals))
+                    } else {
+                        Some(TokenKind::Operator(Operator::Equals))
+                    }
                 } else {
                     Some(TokenKind::Operator(Operator::Assign))
                 }
@@ -73,7 +80,12 @@ impl<'input> Lexer<'input> {
                 self.bump();
                 if self.peek() == Some('=') {
                     self.bump();
-                    Some(TokenKind::Operator(Operator::Different))
+                    if self.peek() == Some('=') {
+                        self.bump();
+                        Some(TokenKind::Operator(Operator::StrictDifferent))
+                    } else {
+                        Some(TokenKind::Operator(Operator::Different))
+                    }
                 } else {
                     Some(TokenKind::Operator(Operator::Not))
                 }
@@ -83,6 +95,9 @@ impl<'input> Lexer<'input> {
                 if self.peek() == Some('=') {
                     self.bump();
                     Some(TokenKind::Operator(Operator::GreaterEqual))
+                } else if self.peek() == Some('>') {
+                    self.bump();
+                    Some(TokenKind::Operator(Operator::ShiftRight))
                 } else {
                     Some(TokenKind::Operator(Operator::GreaterThan))
                 }
@@ -92,6 +107,9 @@ impl<'input> Lexer<'input> {
                 if self.peek() == Some('=') {
                     self.bump();
                     Some(TokenKind::Operator(Operator::LesserEqual))
+                } else if self.peek() == Some('<') {
+                    self.bump();
+                    Some(TokenKind::Operator(Operator::ShiftLeft))
                 } else {
                     Some(TokenKind::Operator(Operator::LesserThan))
                 }
@@ -101,6 +119,9 @@ impl<'input> Lexer<'input> {
                 if self.peek() == Some('+') {
                     self.bump();
                     Some(TokenKind::Operator(Operator::Increment))
+                } else if self.peek() == Some('=') {
+                    self.bump();
+                    Some(TokenKind::Operator(Operator::PlusAssign))
                 } else {
                     Some(TokenKind::Operator(Operator::Plus))
                 }
@@ -110,13 +131,33 @@ impl<'input> Lexer<'input> {
                 if self.peek() == Some('-') {
                     self.bump();
                     Some(TokenKind::Operator(Operator::Decrement))
+                } else if self.peek() == Some('>') {
+                    self.bump();
+                    Some(TokenKind::Punctuation(Punctuation::Arrow))
+                } else if self.peek() == Some('=') {
+                    self.bump();
+                    Some(TokenKind::Operator(Operator::MinusAssign))
                 } else {
                     Some(TokenKind::Operator(Operator::Minus))
                 }
             }
             '*' => {
                 self.bump();
-                Some(TokenKind::Operator(Operator::Multiply))
+                if self.peek() == Some('=') {
+                    self.bump();
+                    Some(TokenKind::Operator(Operator::MultiplyAssign))
+                } else {
+                    Some(TokenKind::Operator(Operator::Multiply))
+                }
+            }
+            '%' => {
+                self.bump();
+                if self.peek() == Some('=') {
+                    self.bump();
+                    Some(TokenKind::Operator(Operator::ModuloAssign))
+                } else {
+                    Some(TokenKind::Operator(Operator::Modulo))
+                }
             }
             '/' => {
                 self.bump();
@@ -124,6 +165,9 @@ impl<'input> Lexer<'input> {
                     self.bump();
                     self.skip_while(|c| c != '\n');
                     None
+                } else if self.peek() == Some('=') {
+                    self.bump();
+                    Some(TokenKind::Operator(Operator::DivideAssign))
                 } else {
                     Some(TokenKind::Operator(Operator::Divide))
                 }
@@ -134,7 +178,7 @@ impl<'input> Lexer<'input> {
                     self.bump();
                     Some(TokenKind::Operator(Operator::And))
                 } else {
-                    Some(TokenKind::Unknown('&'))
+                    Some(TokenKind::Operator(Operator::BitAnd))
                 }
             }
             '|' => {
@@ -143,9 +187,17 @@ impl<'input> Lexer<'input> {
                     self.bump();
                     Some(TokenKind::Operator(Operator::Or))
                 } else {
-                    Some(TokenKind::Unknown('|'))
+                    Some(TokenKind::Operator(Operator::BitOr))
                 }
             }
+            '^' => {
+                self.bump();
+                Some(TokenKind::Operator(Operator::BitXor))
+            }
+            '~' => {
+                self.bump();
+                Some(TokenKind::Operator(Operator::BitNot))
+            }
             '(' => {
                 self.bump();
                 Some(TokenKind::Punctuation(Punctuation::OpenParen))
@@ -174,26 +226,46 @@ impl<'input> Lexer<'input> {
                 self.bump();
                 Some(TokenKind::Punctuation(Punctuation::Colon))
             }
+            '@' => {
+                self.bump();
+                Some(TokenKind::Punctuation(Punctuation::At))
+            }
+            '.' => {
+                self.bump();
+                Some(TokenKind::Punctuation(Punctuation::Dot))
+            }
+            '?' => {
+                self.bump();
+                Some(TokenKind::Punctuation(Punctuation::Question))
+            }
             '\'' => {
                 self.bump(); // consume opening quote
                 let ch = self.bump()?; // get the character
+                // Without a closing quote this is a label such as 'name
+                if (ch.is_alphabetic() || ch == '_') && self.peek() != Some('\'') {
+                    let start = self.position - ch.len_utf8();
+                    self.skip_while(|c| c.is_alphanumeric() || c == '_');
+                    let label = self.input[start..self.position].to_string();
+                    let span = Span::new(start_line, start_col, self.line, self.column);
+                    return Some(Token::new(TokenKind::Label(label), span));
+                }
                 if self.bump()? != '\'' {
                     // expect closing quote
-                    return Some(Token::new(TokenKind::Unknown(ch), start_line, start_col));
+                    let span = Span::new(start_line, start_col, self.line, self.column);
+                    return Some(Token::new(TokenKind::Unknown(ch), span));
                 }
                 Some(TokenKind::Literal(Literal::Character(ch)))
             }
-            c if c.is_ascii_digit() => {
-                let num = self.lex_numeric(c);
-                num
-            }
-            c if c.is_alphabetic() => Some(self.lex_identifier_or_keyword()),
+            '"' => self.lex_string(),
+            c if c.is_ascii_digit() => self.lex_numeric(c),
+            c if c.is_alphabetic() || c == '_' => Some(self.lex_identifier_or_keyword()),
             c => {
                 self.bump();
                 Some(TokenKind::Unknown(c))
             }
         };
-        Some(Token::new(kind?, start_line, start_col))
+        let span = Span::new(start_line, start_col, self.line, self.column);
+        Some(Token::new(kind?, span))
     }
 
     fn lex_numeric(&mut self, first: char) -> Option<TokenKind> {
@@ -227,18 +299,49 @@ impl<'input> Lexer<'input> {
         } else {
             (10, |c: char| c.is_ascii_digit() || c == '.')
         };
-        self.skip_while(|c| f(c));
+        self.skip_while(f);
+        // In `1.cast_float()` the dot starts a method call, not the fraction
+        if self.input[..self.position].ends_with('.')
+            && self.peek().is_some_and(|c| c.is_alphabetic() || c == '_')
+        {
+            self.position -= 1;
+            self.column -= 1;
+        }
         let num_str = &self.input[start..self.position];
         if num_str.contains('.') {
             let value = ("0".to_string() + num_str).parse::<f32>().ok()?;
-            return Some(TokenKind::Literal(Literal::Float(value)));
+            // The spec spells floats with an `f` suffix: 3.14f
+            if self.peek() == Some('f') {
+                self.bump();
+            }
+            Some(TokenKind::Literal(Literal::Float(value)))
         } else {
             let value = u32::from_str_radix(num_str, base).unwrap_or_else(|e| {
                 eprintln!("Error: {}\nfor string \"{}\"", e, num_str);
                 // TODO: better errors
                 panic!();
             });
-            return Some(TokenKind::Literal(Literal::Integer(value)));
+            Some(TokenKind::Literal(Literal::Integer(value)))
+        }
+    }
+
+    /// Lex a string literal up to its closing quote. `\"`, `\\`, `\n`, `\t`
+    /// and `\0` are the escapes it understands.
+    fn lex_string(&mut self) -> Option<TokenKind> {
+        self.bump(); // consume opening quote
+        let mut value = String::new();
+        loop {
+            match self.bump()? {
+                '"' => return Some(TokenKind::Literal(Literal::String(value))),
+                '\\' => match self.bump()? {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    '0' => value.push('\0'),
+                    escaped @ ('"' | '\\') => value.push(escaped),
+                    other => return Some(TokenKind::Unknown(other)),
+                },
+                c => value.push(c),
+            }
         }
     }
 
@@ -248,17 +351,23 @@ impl<'input> Lexer<'input> {
         let name = &self.input[start..self.position];
         match name {
             "let" => TokenKind::Keyword(Keyword::Let),
-            "function" => TokenKind::Keyword(Keyword::Function),
+            "function" | "func" => TokenKind::Keyword(Keyword::Function),
             "if" => TokenKind::Keyword(Keyword::If),
             "else" => TokenKind::Keyword(Keyword::Else),
             "for" => TokenKind::Keyword(Keyword::For),
             "return" => TokenKind::Keyword(Keyword::Return),
-            "int" => TokenKind::TypeIdentifier(TypeIdentifier::Number),
-            "bool" => TokenKind::TypeIdentifier(TypeIdentifier::Boolean),
-            "char" => TokenKind::TypeIdentifier(TypeIdentifier::Char),
+            "type" => TokenKind::Keyword(Keyword::Type),
+            "match" => TokenKind::Keyword(Keyword::Match),
+            "panic" => TokenKind::Keyword(Keyword::Panic),
+            "deref" => TokenKind::Operator(Operator::Deref),
+            "addressof" => TokenKind::Operator(Operator::AddressOf),
             "true" => TokenKind::Literal(Literal::Boolean(true)),
             "false" => TokenKind::Literal(Literal::Boolean(false)),
-            _ => TokenKind::Identifier(name.to_string()),
+            "null" => TokenKind::Literal(Literal::Null),
+            _ => match TypeIdentifier::primitive(name) {
+                Some(primitive) => TokenKind::TypeIdentifier(primitive),
+                None => TokenKind::Identifier(name.to_string()),
+            },
         }
     }
 }
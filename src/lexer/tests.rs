@@ -1,11 +1,13 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use crate::{
         lexer::Lexer,
-        token::{Keyword, Literal, TokenKind},
+        token::{Keyword, Literal, Operator, Punctuation, TokenKind},
     };
 
     #[test]
+    #[allow(clippy::zero_prefixed_literal)]
     fn test_literal_integer() {
         let test_string = "1234 01234 0x12AB 0b1100";
         let expected = [1234, 01234, 0x12AB, 0b1100];
@@ -24,6 +26,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_literal_float() {
         let test_string = "3.14 0.14 12.4 1.0";
         let expected = [3.14, 0.14, 12.4, 1.0];
@@ -33,6 +36,19 @@ mod tests {
             .for_each(|(t, e)| assert_eq!(t.kind, TokenKind::Literal(Literal::Float(e))))
     }
 
+    #[test]
+    fn test_literal_float_suffix() {
+        let kinds: Vec<TokenKind> = Lexer::new("2.5f 1.0f x").map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::Literal(Literal::Float(2.5)),
+                TokenKind::Literal(Literal::Float(1.0)),
+                TokenKind::Identifier("x".into())
+            ]
+        );
+    }
+
     #[test]
     fn test_literal_bool() {
         let test_string = "true false";
@@ -43,6 +59,18 @@ mod tests {
             .for_each(|(t, e)| assert_eq!(t.kind, TokenKind::Literal(Literal::Boolean(e))))
     }
 
+    #[test]
+    fn test_literal_null() {
+        let kinds: Vec<TokenKind> = Lexer::new("null nullable").map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::Literal(Literal::Null),
+                TokenKind::Identifier("nullable".into())
+            ]
+        );
+    }
+
     #[test]
     fn test_literal_character() {
         let test_string = "'c' 'e' 'r' 'Ñ'";
@@ -53,6 +81,51 @@ mod tests {
             .for_each(|(t, e)| assert_eq!(t.kind, TokenKind::Literal(Literal::Character(e))))
     }
 
+    #[test]
+    fn test_literal_string() {
+        let test_string = r#""hello" "" "a \"quoted\" line\n" "tab\there""#;
+        let expected = ["hello", "", "a \"quoted\" line\n", "tab\there"];
+        let kinds: Vec<TokenKind> = Lexer::new(test_string).map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            expected
+                .iter()
+                .map(|e| TokenKind::Literal(Literal::String(e.to_string())))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_match_punctuation() {
+        let kinds: Vec<TokenKind> = Lexer::new("match r? x.y").map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Keyword(Keyword::Match),
+                TokenKind::Identifier("r".into()),
+                TokenKind::Punctuation(Punctuation::Question),
+                TokenKind::Identifier("x".into()),
+                TokenKind::Punctuation(Punctuation::Dot),
+                TokenKind::Identifier("y".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_label() {
+        let test_string = "'name 'a 'a' '_tag";
+        let kinds: Vec<TokenKind> = Lexer::new(test_string).map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Label("name".into()),
+                TokenKind::Label("a".into()),
+                TokenKind::Literal(Literal::Character('a')),
+                TokenKind::Label("_tag".into()),
+            ]
+        );
+    }
+
     #[test]
     fn test_let_keyword() {
         let test_string = "let";
@@ -94,4 +167,49 @@ mod tests {
         let lexer = Lexer::new(test_string);
         lexer.for_each(|t| assert_eq!(t.kind, TokenKind::Keyword(Keyword::Function)))
     }
+
+    #[test]
+    fn test_func_keyword() {
+        let test_string = "func";
+        let lexer = Lexer::new(test_string);
+        lexer.for_each(|t| assert_eq!(t.kind, TokenKind::Keyword(Keyword::Function)))
+    }
+
+    #[test]
+    fn test_arrow_punctuation() {
+        let test_string = "->";
+        let lexer = Lexer::new(test_string);
+        lexer.for_each(|t| assert_eq!(t.kind, TokenKind::Punctuation(Punctuation::Arrow)))
+    }
+
+    #[test]
+    fn test_operators() {
+        let test_string = "% & | ^ ~ << >> === !== += -= *= /= %= == != <= >=";
+        let expected = [
+            Operator::Modulo,
+            Operator::BitAnd,
+            Operator::BitOr,
+            Operator::BitXor,
+            Operator::BitNot,
+            Operator::ShiftLeft,
+            Operator::ShiftRight,
+            Operator::StrictEquals,
+            Operator::StrictDifferent,
+            Operator::PlusAssign,
+            Operator::MinusAssign,
+            Operator::MultiplyAssign,
+            Operator::DivideAssign,
+            Operator::ModuloAssign,
+            Operator::Equals,
+            Operator::Different,
+            Operator::LesserEqual,
+            Operator::GreaterEqual,
+        ];
+        let kinds: Vec<TokenKind> = Lexer::new(test_string).map(|t| t.kind).collect();
+        assert_eq!(kinds.len(), expected.len());
+        kinds
+            .into_iter()
+            .zip(expected)
+            .for_each(|(t, e)| assert_eq!(t, TokenKind::Operator(e)))
+    }
 }
@@ -1,7 +1,14 @@
 mod cli;
+mod diagnostic;
 mod driver;
+mod escape;
+mod hints;
+mod hir;
 mod lexer;
+mod monomorphize;
 mod parser;
+#[cfg(test)]
+mod test_support;
 mod token;
 mod type_checker;
 
@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+pub mod monomorphize;
+mod tests;
+
+pub use monomorphize::monomorphize;
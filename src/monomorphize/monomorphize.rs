@@ -0,0 +1,461 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{
+    diagnostic::Diagnostic,
+    hir::{
+        Hir,
+        hir::{
+            Binding, BindingId, Expression, ExpressionKind, Function, Parameter, Pattern,
+            PatternKind, Resolution, Statement, StatementKind,
+        },
+    },
+    token::{Span, TypeIdentifier},
+};
+
+/// Specialize every top-level function with a contract-typed parameter for
+/// the concrete types it is called with, as `calculate_area_Square` for
+/// `calculate_area(shape Shape)` called with a `Square`.
+///
+/// A call whose contract-typed arguments all have a concrete type is
+/// rewritten to the specialization for those types, made once however many
+/// calls share it. The specialization is a copy of the function whose
+/// contract-typed parameters have the concrete types, so method calls on
+/// them name the implementation that runs. A generic function is dropped
+/// unless something outside of the generic functions that are dropped still
+/// refers to it.
+///
+/// Types that are not declared in the program are contracts: the type
+/// checker rejects any other unknown name.
+pub fn monomorphize(hir: &Hir) -> Result<Hir, Vec<Diagnostic>> {
+    let types = hir
+        .statements
+        .iter()
+        .filter_map(|statement| match &statement.kind {
+            StatementKind::TypeDeclaration { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    let functions: Vec<&Function> = hir
+        .statements
+        .iter()
+        .filter_map(|statement| match &statement.kind {
+            StatementKind::Function(function) => Some(function),
+            _ => None,
+        })
+        .collect();
+    let mut pass = Monomorphizer {
+        bindings: hir.bindings.clone(),
+        types,
+        functions: functions
+            .iter()
+            .map(|function| function.name.clone())
+            .collect(),
+        generic: HashMap::new(),
+        instances: HashMap::new(),
+        names: HashMap::new(),
+        worklist: VecDeque::new(),
+        specializations: HashMap::new(),
+        references: Vec::new(),
+        owner: None,
+        copies: None,
+        diagnostics: Vec::new(),
+    };
+    for function in functions {
+        if pass.contract_parameters(function).next().is_some() {
+            pass.generic.insert(function.name.clone(), function.clone());
+        }
+    }
+
+    let mut statements = hir.statements.clone();
+    for statement in &mut statements {
+        pass.owner = match &statement.kind {
+            StatementKind::Function(function) if pass.generic.contains_key(&function.name) => {
+                Some(function.name.clone())
+            }
+            _ => None,
+        };
+        pass.rewrite_statement(statement);
+    }
+    pass.owner = None;
+    while let Some(instance) = pass.worklist.pop_front() {
+        let specialization = pass.specialize(instance);
+        pass.specializations
+            .entry(specialization.0)
+            .or_default()
+            .push(specialization.1);
+    }
+    if !pass.diagnostics.is_empty() {
+        return Err(pass.diagnostics);
+    }
+    let kept = pass.kept();
+
+    // Each specialization follows its generic function, in the order it was made
+    let mut monomorphized = Vec::new();
+    for statement in statements {
+        let StatementKind::Function(function) = &statement.kind else {
+            monomorphized.push(statement);
+            continue;
+        };
+        let span = statement.span;
+        let specializations = pass
+            .specializations
+            .remove(&function.name)
+            .unwrap_or_default();
+        if !pass.generic.contains_key(&function.name) || kept.contains(&function.name) {
+            monomorphized.push(statement);
+        }
+        monomorphized.extend(specializations.into_iter().map(|function| Statement {
+            kind: StatementKind::Function(function),
+            span,
+        }));
+    }
+    Ok(Hir {
+        bindings: pass.bindings,
+        statements: monomorphized,
+    })
+}
+
+/// A specialization to make: the generic function, the concrete type of
+/// each of its contract-typed parameters in order, and the mangled name.
+struct Instance {
+    function: String,
+    types: Vec<TypeIdentifier>,
+    name: String,
+}
+
+struct Monomorphizer {
+    // Every binding of the program, those of the specializations appended
+    bindings: Vec<Binding>,
+    // The named types the program declares
+    types: HashSet<String>,
+    // The names of the top-level functions
+    functions: HashSet<String>,
+    // The top-level functions with a contract-typed parameter, as written
+    generic: HashMap<String, Function>,
+    // The name of the specialization for each generic function and the
+    // concrete names of its contract-typed arguments
+    instances: HashMap<(String, Vec<String>), String>,
+    // The key each mangled name was given to, to report two keys that
+    // mangle the same
+    names: HashMap<String, (String, Vec<String>)>,
+    worklist: VecDeque<Instance>,
+    // The specializations of each generic function, in the order made
+    specializations: HashMap<String, Vec<Function>>,
+    // Generic functions still named by a call or used as a value, and the
+    // generic function naming them, if any
+    references: Vec<(Option<String>, String)>,
+    // The generic function being rewritten, as written
+    owner: Option<String>,
+    // While copying a generic function, the copy of each binding it declares
+    copies: Option<HashMap<BindingId, BindingId>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Monomorphizer {
+    /// The positions of the parameters of `function` typed by a contract.
+    fn contract_parameters<'a>(&'a self, function: &'a Function) -> impl Iterator<Item = usize> {
+        function
+            .parameters
+            .iter()
+            .enumerate()
+            .filter(|(_, parameter)| {
+                self.is_contract(&self.bindings[parameter.binding.0].binding_type)
+            })
+            .map(|(i, _)| i)
+    }
+
+    fn is_contract(&self, value_type: &TypeIdentifier) -> bool {
+        matches!(value_type, TypeIdentifier::Named(name) if !self.types.contains(name))
+    }
+
+    /// The generic functions to keep: those named outside of generic
+    /// functions, and those named by a generic function that is kept.
+    fn kept(&self) -> HashSet<String> {
+        let mut kept = HashSet::new();
+        loop {
+            let before = kept.len();
+            for (owner, name) in &self.references {
+                if owner.as_ref().is_none_or(|owner| kept.contains(owner)) {
+                    kept.insert(name.clone());
+                }
+            }
+            if kept.len() == before {
+                return kept;
+            }
+        }
+    }
+
+    /// Copy the generic function of `instance` with its contract-typed
+    /// parameters given their concrete types, and rewrite the calls in it.
+    fn specialize(&mut self, instance: Instance) -> (String, Function) {
+        let mut function = self.generic[&instance.function].clone();
+        let contract_parameters: Vec<usize> = self.contract_parameters(&function).collect();
+        let outer_copies = self.copies.replace(HashMap::new());
+        for (i, parameter) in function.parameters.iter_mut().enumerate() {
+            let concrete = contract_parameters
+                .iter()
+                .position(|&position| position == i)
+                .map(|n| instance.types[n].clone());
+            self.declare(&mut parameter.binding, concrete);
+        }
+        self.rewrite_statements(&mut function.body);
+        self.copies = outer_copies;
+        function.name = instance.name;
+        (instance.function, function)
+    }
+
+    /// The specialization a call to `function` with `args` goes to, made
+    /// the first time it is needed. None when the function is not generic,
+    /// or an argument for a contract-typed parameter has no concrete type.
+    fn instance(&mut self, function: &str, args: &[Expression], span: Span) -> Option<String> {
+        let generic = self.generic.get(function)?;
+        let positions: Vec<usize> = self.contract_parameters(generic).collect();
+        let mut types = Vec::new();
+        for position in positions {
+            let arg_type = &args.get(position)?.expr_type;
+            if self.is_contract(arg_type) {
+                return None;
+            }
+            types.push(arg_type.clone());
+        }
+        let type_names: Vec<String> = types.iter().map(mangled_type).collect();
+        let key = (function.to_string(), type_names);
+        if let Some(name) = self.instances.get(&key) {
+            return Some(name.clone());
+        }
+        let name = format!("{}_{}", function, key.1.join("_"));
+        if let Some(other) = self.names.get(&name) {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "E0600",
+                    format!(
+                        "The specialization of '{}' for {} would be named '{}', as is the one of '{}' for {}",
+                        function,
+                        key.1.join(", "),
+                        name,
+                        other.0,
+                        other.1.join(", ")
+                    ),
+                    span,
+                )
+                .with_note("rename one of the functions or types"),
+            );
+            return None;
+        }
+        if self.functions.contains(&name) {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "E0600",
+                    format!(
+                        "The specialization of '{}' for {} would be named '{}', as is a function of the program",
+                        function,
+                        key.1.join(", "),
+                        name
+                    ),
+                    span,
+                )
+                .with_note(format!("rename function '{}'", name)),
+            );
+            return None;
+        }
+        self.instances.insert(key.clone(), name.clone());
+        self.names.insert(name.clone(), key);
+        self.worklist.push_back(Instance {
+            function: function.to_string(),
+            types,
+            name: name.clone(),
+        });
+        Some(name)
+    }
+
+    /// Give a binding declared in a function being copied a copy of its own,
+    /// of type `concrete` when given.
+    fn declare(&mut self, id: &mut BindingId, concrete: Option<TypeIdentifier>) {
+        let Some(copies) = &mut self.copies else {
+            return;
+        };
+        let mut binding = self.bindings[id.0].clone();
+        if let Some(concrete) = concrete {
+            binding.binding_type = concrete;
+        }
+        let copy = BindingId(self.bindings.len());
+        self.bindings.push(binding);
+        copies.insert(*id, copy);
+        *id = copy;
+    }
+
+    /// Point a use of a binding at its copy, in a function being copied.
+    fn rename(&self, id: &mut BindingId) {
+        if let Some(copy) = self.copies.as_ref().and_then(|copies| copies.get(id)) {
+            *id = *copy;
+        }
+    }
+
+    fn rewrite_statements(&mut self, statements: &mut [Statement]) {
+        for statement in statements {
+            self.rewrite_statement(statement);
+        }
+    }
+
+    fn rewrite_statement(&mut self, statement: &mut Statement) {
+        match &mut statement.kind {
+            StatementKind::Let { pattern, value } => {
+                if let Some(value) = value {
+                    self.rewrite_expr(value);
+                }
+                self.declare_pattern(pattern);
+            }
+            StatementKind::Assignment { target, value }
+            | StatementKind::CompoundAssignment { target, value, .. } => {
+                self.rewrite_expr(value);
+                self.rename(target);
+            }
+            StatementKind::Store { target, value } => {
+                self.rewrite_expr(target);
+                self.rewrite_expr(value);
+            }
+            StatementKind::IncrementDecrement { target, .. } => self.rename(target),
+            StatementKind::Expression(expr) | StatementKind::Return(Some(expr)) => {
+                self.rewrite_expr(expr)
+            }
+            StatementKind::Return(None) => {}
+            StatementKind::Function(function) => self.rewrite_function(function),
+            StatementKind::TypeDeclaration { contracts, .. } => {
+                for method in contracts
+                    .iter_mut()
+                    .flat_map(|contract| &mut contract.methods)
+                {
+                    self.rewrite_function(method);
+                }
+            }
+            StatementKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.rewrite_expr(condition);
+                self.rewrite_statements(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.rewrite_statements(else_branch);
+                }
+            }
+            StatementKind::For { condition, body } => {
+                self.rewrite_expr(condition);
+                self.rewrite_statements(body);
+            }
+            StatementKind::Hinted { statement, .. } => self.rewrite_statement(statement),
+            StatementKind::Match { scrutinee, arms } => {
+                self.rewrite_expr(scrutinee);
+                for arm in arms {
+                    self.declare_pattern(&mut arm.pattern);
+                    self.rewrite_statements(&mut arm.body);
+                }
+            }
+        }
+    }
+
+    fn rewrite_function(&mut self, function: &mut Function) {
+        self.declare_parameters(&mut function.parameters);
+        self.rewrite_statements(&mut function.body);
+    }
+
+    fn declare_parameters(&mut self, parameters: &mut [Parameter]) {
+        for parameter in parameters {
+            self.declare(&mut parameter.binding, None);
+        }
+    }
+
+    fn declare_pattern(&mut self, pattern: &mut Pattern) {
+        match &mut pattern.kind {
+            PatternKind::Wildcard | PatternKind::Literal(_) => {}
+            PatternKind::Binding(id) => self.declare(id, None),
+            PatternKind::Tuple(elements) => {
+                for element in elements {
+                    self.declare_pattern(element);
+                }
+            }
+            PatternKind::Labeled(fields) => {
+                for (_, field) in fields {
+                    self.declare_pattern(field);
+                }
+            }
+            PatternKind::Variant { payload, .. } => {
+                if let Some(payload) = payload {
+                    self.declare_pattern(payload);
+                }
+            }
+        }
+    }
+
+    fn rewrite_expr(&mut self, expr: &mut Expression) {
+        match &mut expr.kind {
+            ExpressionKind::Call { callee, args } => {
+                args.iter_mut().for_each(|arg| self.rewrite_expr(arg));
+                if let ExpressionKind::Name(Resolution::Function(name)) = &callee.kind
+                    && let Some(specialization) = self.instance(name, args, expr.span)
+                {
+                    callee.kind = ExpressionKind::Name(Resolution::Function(specialization));
+                    callee.expr_type = TypeIdentifier::Function {
+                        parameters: args.iter().map(|arg| arg.expr_type.clone()).collect(),
+                        return_type: Box::new(expr.expr_type.clone()),
+                    };
+                }
+                self.rewrite_expr(callee);
+            }
+            ExpressionKind::Name(Resolution::Function(name)) => {
+                if self.generic.contains_key(name) {
+                    self.references.push((self.owner.clone(), name.clone()));
+                }
+            }
+            ExpressionKind::Name(Resolution::Local(id)) | ExpressionKind::Move(id) => {
+                let original = *id;
+                self.rename(id);
+                // A contract-typed parameter of a specialization has its concrete type
+                if *id != original && self.is_contract(&expr.expr_type) {
+                    expr.expr_type = self.bindings[id.0].binding_type.clone();
+                }
+            }
+            ExpressionKind::Lambda(lambda) => {
+                for captured in &mut lambda.captures {
+                    self.rename(captured);
+                }
+                self.declare_parameters(&mut lambda.parameters);
+                self.rewrite_statements(&mut lambda.body);
+            }
+            ExpressionKind::Binary { left, right, .. } => {
+                self.rewrite_expr(left);
+                self.rewrite_expr(right);
+            }
+            ExpressionKind::Unary { expression, .. }
+            | ExpressionKind::Hinted { expression, .. }
+            | ExpressionKind::Cast { expression, .. }
+            | ExpressionKind::Field {
+                receiver: expression,
+                ..
+            }
+            | ExpressionKind::Propagate(expression) => self.rewrite_expr(expression),
+            ExpressionKind::MethodCall { receiver, args, .. } => {
+                self.rewrite_expr(receiver);
+                args.iter_mut().for_each(|arg| self.rewrite_expr(arg));
+            }
+            ExpressionKind::Tuple(elements) => elements
+                .iter_mut()
+                .for_each(|element| self.rewrite_expr(element)),
+            ExpressionKind::Struct(fields) => fields
+                .iter_mut()
+                .for_each(|(_, value)| self.rewrite_expr(value)),
+            ExpressionKind::Variant { payload, .. } | ExpressionKind::Panic(payload) => {
+                if let Some(payload) = payload {
+                    self.rewrite_expr(payload);
+                }
+            }
+            ExpressionKind::Literal(_) => {}
+        }
+    }
+}
+
+/// The part of a mangled name a concrete type adds. Only named types sign
+/// contracts, so it is the name of the type.
+fn mangled_type(concrete: &TypeIdentifier) -> String {
+    concrete.to_string()
+}
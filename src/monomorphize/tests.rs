@@ -0,0 +1,261 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::{
+        diagnostic::Diagnostic,
+        hir::{
+            Hir,
+            hir::{ExpressionKind, Function, Resolution, StatementKind},
+        },
+        monomorphize::monomorphize,
+        test_support::lower,
+        token::TypeIdentifier,
+    };
+
+    const TYPES: &str = "
+        type Square = ('side int ; Error {
+            function get_error_id(self) int { return 1; }
+            function get_error_message(self) string { return \"square\"; }
+        })
+        type Circle = ('radius int ; Error {
+            function get_error_id(self) int { return 2; }
+            function get_error_message(self) string { return \"circle\"; }
+        })
+        function calculate_area(shape Error) int {
+            let id int = shape.get_error_id();
+            return id * 2;
+        }
+    ";
+
+    fn monomorphized(src: &str) -> Hir {
+        monomorphize(&lower(&format!("{}{}", TYPES, src))).expect("should monomorphize")
+    }
+
+    fn errors(src: &str) -> Vec<Diagnostic> {
+        monomorphize(&lower(&format!("{}{}", TYPES, src))).expect_err("should fail")
+    }
+
+    fn functions(hir: &Hir) -> Vec<&Function> {
+        hir.statements
+            .iter()
+            .filter_map(|statement| match &statement.kind {
+                StatementKind::Function(function) => Some(function),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn function_names(hir: &Hir) -> Vec<&str> {
+        functions(hir)
+            .into_iter()
+            .map(|function| function.name.as_str())
+            .collect()
+    }
+
+    /// The names of the functions called by `function`, in order.
+    fn callees(function: &Function) -> Vec<String> {
+        function
+            .body
+            .iter()
+            .filter_map(|statement| match &statement.kind {
+                StatementKind::Let {
+                    value: Some(value), ..
+                }
+                | StatementKind::Expression(value) => Some(value),
+                _ => None,
+            })
+            .filter_map(|value| match &value.kind {
+                ExpressionKind::Call { callee, .. } => match &callee.kind {
+                    ExpressionKind::Name(Resolution::Function(name)) => Some(name.clone()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn function<'a>(hir: &'a Hir, name: &str) -> &'a Function {
+        functions(hir)
+            .into_iter()
+            .find(|function| function.name == name)
+            .unwrap_or_else(|| panic!("no function '{}'", name))
+    }
+
+    #[test]
+    fn test_calls_with_the_same_type_share_a_specialization() {
+        let hir = monomorphized(
+            "function main() {
+                 let a Square = { side = 2 };
+                 let b Square = { side = 3 };
+                 let x int = calculate_area(a);
+                 let y int = calculate_area(b);
+             }",
+        );
+        // The generic original is dropped once no call refers to it
+        assert_eq!(function_names(&hir), ["calculate_area_Square", "main"]);
+        assert_eq!(
+            callees(function(&hir, "main")),
+            ["calculate_area_Square", "calculate_area_Square"]
+        );
+    }
+
+    #[test]
+    fn test_each_type_gets_its_own_specialization() {
+        let hir = monomorphized(
+            "function main() {
+                 let a Circle = { radius = 2 };
+                 let b Square = { side = 3 };
+                 let x int = calculate_area(a);
+                 let y int = calculate_area(b);
+                 let z int = calculate_area(a);
+             }",
+        );
+        assert_eq!(
+            function_names(&hir),
+            ["calculate_area_Circle", "calculate_area_Square", "main"]
+        );
+        assert_eq!(
+            callees(function(&hir, "main")),
+            [
+                "calculate_area_Circle",
+                "calculate_area_Square",
+                "calculate_area_Circle"
+            ]
+        );
+        // The parameter and its uses have the concrete type
+        let specialization = function(&hir, "calculate_area_Square");
+        let parameter = specialization.parameters[0].binding;
+        assert_eq!(
+            hir.bindings[parameter.0].binding_type,
+            TypeIdentifier::Named("Square".into())
+        );
+        let StatementKind::Let {
+            value: Some(value), ..
+        } = &specialization.body[0].kind
+        else {
+            panic!("expected a let");
+        };
+        let ExpressionKind::MethodCall { receiver, .. } = &value.kind else {
+            panic!("expected a method call");
+        };
+        assert_eq!(receiver.expr_type, TypeIdentifier::Named("Square".into()));
+        assert!(matches!(
+            receiver.kind,
+            ExpressionKind::Name(Resolution::Local(id)) if id == parameter
+        ));
+    }
+
+    #[test]
+    fn test_mangled_names_are_stable() {
+        let src = "function combine(a Error, n int, b Error) int {
+                       return calculate_area(a) + calculate_area(b) + n;
+                   }
+                   function main() {
+                       let c Circle = { radius = 2 };
+                       let s Square = { side = 3 };
+                       let x int = combine(s, 1, c);
+                   }";
+        let first = monomorphized(src);
+        // Specializations made from other specializations come through the
+        // worklist, after the ones they are made from
+        assert_eq!(
+            function_names(&first),
+            [
+                "calculate_area_Square",
+                "calculate_area_Circle",
+                "combine_Square_Circle",
+                "main"
+            ]
+        );
+        for _ in 0..3 {
+            assert_eq!(function_names(&monomorphized(src)), function_names(&first));
+        }
+    }
+
+    #[test]
+    fn test_generic_function_kept_while_referenced() {
+        let hir = monomorphized(
+            "function describe(e Error) int {
+                 return calculate_area(e);
+             }
+             function main() {
+                 let s Square = { side = 3 };
+                 let y int = describe(s);
+             }",
+        );
+        // 'calculate_area' is only called by the generic 'describe', which is dropped
+        assert_eq!(
+            function_names(&hir),
+            ["calculate_area_Square", "describe_Square", "main"]
+        );
+        let StatementKind::Return(Some(value)) = &function(&hir, "describe_Square").body[0].kind
+        else {
+            panic!("expected a return");
+        };
+        let ExpressionKind::Call { callee, .. } = &value.kind else {
+            panic!("expected a call");
+        };
+        assert!(matches!(
+            &callee.kind,
+            ExpressionKind::Name(Resolution::Function(name)) if name == "calculate_area_Square"
+        ));
+        // A value typed by the contract has no concrete type to specialize for
+        let hir = monomorphized(
+            "function main() {
+                 let s Square = { side = 3 };
+                 let e Error = s;
+                 let x int = calculate_area(e);
+                 let y int = calculate_area(s);
+             }",
+        );
+        assert_eq!(
+            function_names(&hir),
+            ["calculate_area", "calculate_area_Square", "main"]
+        );
+        assert_eq!(
+            callees(function(&hir, "main")),
+            ["calculate_area", "calculate_area_Square"]
+        );
+    }
+
+    #[test]
+    fn test_mangled_name_taken_by_a_function() {
+        let diagnostics = errors(
+            "function calculate_area_Square(n int) int {
+                 return n;
+             }
+             function main() {
+                 let s Square = { side = 3 };
+                 let x int = calculate_area(s);
+             }",
+        );
+        assert_eq!(diagnostics[0].code, "E0600");
+        assert_eq!(
+            diagnostics[0].message,
+            "The specialization of 'calculate_area' for Square would be named 'calculate_area_Square', as is a function of the program"
+        );
+        assert_eq!(
+            diagnostics[0].notes,
+            ["rename function 'calculate_area_Square'"]
+        );
+        // Two specializations mangled the same
+        let diagnostics = errors(
+            "function sum(a Error, b Error) int {
+                 return calculate_area(a) + calculate_area(b);
+             }
+             function sum_Square(a Error) int {
+                 return calculate_area(a);
+             }
+             function main() {
+                 let s Square = { side = 3 };
+                 let c Circle = { radius = 2 };
+                 let x int = sum(s, c);
+                 let y int = sum_Square(c);
+             }",
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "The specialization of 'sum_Square' for Circle would be named 'sum_Square_Circle', as is the one of 'sum' for Square, Circle"
+        );
+    }
+}
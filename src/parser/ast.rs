@@ -7,8 +7,8 @@ pub struct Ast {
 
 impl Ast {
     pub fn new() -> Self {
-        return Self {
+        Self {
             statements: Vec::new(),
-        };
+        }
     }
-}
\ No newline at end of file
+}
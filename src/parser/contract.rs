@@ -0,0 +1,11 @@
+use crate::parser::function::Function;
+use crate::token::Span;
+
+/// The methods a named type defines to sign a contract:
+/// <contract> { function <method>(self, ...) ... }
+#[derive(Debug, Clone)]
+pub struct ContractImplementation {
+    pub contract: String,
+    pub methods: Vec<Function>,
+    pub span: Span,
+}
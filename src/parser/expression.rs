@@ -1,8 +1,22 @@
-use crate::token::Operator;
+use crate::parser::function::Lambda;
+use crate::parser::hint::Hint;
 use crate::token::literal::Literal;
+use crate::token::{Operator, Span, TypeIdentifier};
 
 #[derive(Debug, Clone)]
-pub enum Expression {
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ExpressionKind {
     Binary {
         left: Box<Expression>,
         operator: Operator,
@@ -20,4 +34,45 @@ pub enum Expression {
         callee: Box<Expression>,
         args: Vec<Expression>,
     },
+    Hinted {
+        hints: Vec<Hint>,
+        expression: Box<Expression>,
+    },
+    Lambda(Box<Lambda>),
+    // (<expression>, <expression>, ...)
+    Tuple(Vec<Expression>),
+    // { <label> = <expression>, ... }
+    Struct(Vec<(String, Expression)>),
+    // cast_<type>(<expression>) or <expression>.cast_<type>()
+    Cast {
+        expression: Box<Expression>,
+        target: TypeIdentifier,
+    },
+    // '<variant> [<expression>], a value of the sum type it is used as
+    Variant {
+        label: String,
+        payload: Option<Box<Expression>>,
+        // The type named before a struct literal payload: '<variant> <type> { ... }
+        payload_type: Option<String>,
+    },
+    // <receiver>.<field>
+    Field {
+        receiver: Box<Expression>,
+        field: String,
+    },
+    // <receiver>.<method>(<arguments>)
+    MethodCall {
+        receiver: Box<Expression>,
+        method: String,
+        args: Vec<Expression>,
+    },
+    // <expression>?, the 'Ok payload or an early return of the 'Err
+    Propagate(Box<Expression>),
+    // panic [<message>], stops the program and never has a value
+    Panic(Option<Box<Expression>>),
+}
+
+/// The type a safe cast function such as `cast_int` converts to.
+pub fn cast_target(name: &str) -> Option<TypeIdentifier> {
+    TypeIdentifier::primitive(name.strip_prefix("cast_")?)
 }
@@ -1,10 +1,13 @@
+use crate::parser::hint::Hint;
 use crate::parser::statement::Statement;
-use crate::token::TypeIdentifier;
+use crate::token::{Span, TypeIdentifier};
 
 #[derive(Debug, Clone)]
 pub struct Function {
+    pub hints: Vec<Hint>,
     pub signature: FunctionSignature,
     pub body: FunctionBody,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -17,10 +20,21 @@ pub struct FunctionSignature {
 #[derive(Debug, Clone)]
 pub struct FunctionParameter {
     pub parameter_name: String,
-    pub parameter_type: TypeIdentifier,
+    // Only parameters of anonymous functions may leave their type to inference
+    pub parameter_type: Option<TypeIdentifier>,
+    // Inline requirement hints and promise hints bound to this parameter
+    pub hints: Vec<Hint>,
+    pub span: Span,
+}
+
+// An anonymous function: function(<parameters>) <return_type> { <statements> }
+#[derive(Debug, Clone)]
+pub struct Lambda {
+    pub parameters: Vec<FunctionParameter>,
+    pub return_type: Option<TypeIdentifier>,
+    pub body: Vec<Statement>,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum FunctionBody {
     Statements(Vec<Statement>),
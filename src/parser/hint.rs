@@ -0,0 +1,20 @@
+use crate::parser::expression::Expression;
+use crate::token::Span;
+
+#[derive(Debug, Clone)]
+pub struct Hint {
+    pub name: String,
+    pub arguments: Vec<Expression>,
+    pub placement: HintPlacement,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HintPlacement {
+    // Written right before the element it applies to. On a parameter this is a
+    // requirement the caller must satisfy.
+    Inline,
+    // Written before a function as `@hint(parameter_name)`. The function
+    // promises to uphold the hint for that parameter.
+    Promise,
+}
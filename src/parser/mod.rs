@@ -1,13 +1,22 @@
 pub mod ast;
+pub mod contract;
 pub mod expression;
 pub mod function;
+pub mod hint;
+#[allow(clippy::module_inception)]
 pub mod parser;
+pub mod pattern;
+pub mod precedence;
 pub mod statement;
+mod tests;
 pub mod variable_declaration;
 
 pub use ast::Ast;
-pub use expression::Expression;
-pub use function::{Function, FunctionBody, FunctionParameter, FunctionSignature};
+pub use contract::ContractImplementation;
+pub use expression::{Expression, ExpressionKind, cast_target};
+pub use function::{Function, FunctionBody, FunctionParameter, FunctionSignature, Lambda};
+pub use hint::{Hint, HintPlacement};
 pub use parser::Parser;
-pub use statement::Statement;
+pub use pattern::{Pattern, PatternKind};
+pub use statement::{MatchArm, Statement, StatementKind};
 pub use variable_declaration::VariableDeclaration;
@@ -1,9 +1,15 @@
 use std::fmt;
 use std::iter::Peekable;
 
-use crate::parser::function::{FunctionBody, FunctionParameter, FunctionSignature};
-use crate::parser::{Ast, Expression, Function, Statement, VariableDeclaration};
-use crate::token::{Keyword, Literal, Operator, Punctuation, Token, TokenKind};
+use crate::parser::function::{FunctionBody, FunctionParameter, FunctionSignature, Lambda};
+use crate::parser::precedence::{self, Associativity};
+use crate::parser::{
+    Ast, ContractImplementation, Expression, ExpressionKind, Function, Hint, HintPlacement,
+    MatchArm, Pattern, PatternKind, Statement, StatementKind, VariableDeclaration, cast_target,
+};
+use crate::token::{
+    Keyword, Literal, Operator, Ownership, Punctuation, Span, Token, TokenKind, TypeIdentifier,
+};
 
 #[derive(Debug)]
 pub struct ParseError {
@@ -33,6 +39,11 @@ where
     tokens: Peekable<I>,
     filename: String,
     source_lines: Vec<String>,
+    // Span of the last consumed token, where the node being parsed ends
+    previous: Span,
+    // Set while parsing the single statement of a match arm, which a '|'
+    // outside any brackets ends
+    in_match_arm: bool,
 }
 
 impl<I> Parser<I>
@@ -44,6 +55,8 @@ where
             tokens: tokens.peekable(),
             filename,
             source_lines: source.lines().map(|s| s.to_string()).collect(),
+            previous: Span::default(),
+            in_match_arm: false,
         }
     }
 
@@ -67,7 +80,43 @@ where
     }
 
     fn next(&mut self) -> Option<Token> {
-        self.tokens.next()
+        let token = self.tokens.next();
+        if let Some(token) = &token {
+            self.previous = token.span;
+        }
+        token
+    }
+
+    /// Span of the next token, where the node about to be parsed starts.
+    fn current_span(&mut self) -> Span {
+        let previous = self.previous;
+        self.peek().map_or(previous, |t| t.span)
+    }
+
+    /// Run `parse` on something in brackets, where a '|' is an operator again
+    /// even inside a match arm.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> ParseResult<T>) -> ParseResult<T> {
+        let in_match_arm = std::mem::replace(&mut self.in_match_arm, false);
+        let result = parse(self);
+        self.in_match_arm = in_match_arm;
+        result
+    }
+
+    /// Span from `start` up to the last consumed token.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous)
+    }
+
+    fn binary(left: Expression, operator: Operator, right: Expression) -> Expression {
+        let span = left.span.to(right.span);
+        Expression::new(
+            ExpressionKind::Binary {
+                left: Box::new(left),
+                operator,
+                right: Box::new(right),
+            },
+            span,
+        )
     }
 
     /// Consume and return the next token, or error if none.
@@ -88,7 +137,7 @@ where
         if pred(&token) {
             Ok(token)
         } else {
-            Err(self.error(msg, token.line, token.column))
+            Err(self.error(msg, token.span.line, token.span.column))
         }
     }
 
@@ -97,10 +146,10 @@ where
     where
         F: FnOnce(&Token) -> bool,
     {
-        if let Some(token) = self.peek() {
-            if pred(token) {
-                return self.next();
-            }
+        if let Some(token) = self.peek()
+            && pred(token)
+        {
+            return self.next();
         }
         None
     }
@@ -115,7 +164,10 @@ where
     }
 
     fn parse_statement(&mut self) -> ParseResult<Statement> {
-        let stmt = if let Some(token) = self.peek() {
+        let starts_lambda = self.is_lambda();
+        let in_match_arm = self.in_match_arm;
+        let start = self.current_span();
+        let kind = if let Some(token) = self.peek() {
             match &token.kind {
                 TokenKind::Keyword(Keyword::If) => {
                     self.next(); // consume 'if'
@@ -134,20 +186,41 @@ where
                     } else {
                         None
                     };
-                    Statement::If {
+                    StatementKind::If {
                         condition,
                         then_branch,
                         else_branch,
                     }
                 }
+                TokenKind::Keyword(Keyword::Function) if starts_lambda => {
+                    let expr = self.parse_expression()?;
+                    StatementKind::Expression(expr)
+                }
                 TokenKind::Keyword(Keyword::Function) => {
-                    let user_function = self.parse_function_declaration()?;
-                    Statement::FunctionDeclaration(user_function)
+                    let user_function = self.parse_function_declaration(Vec::new())?;
+                    StatementKind::FunctionDeclaration(user_function)
+                }
+                TokenKind::Punctuation(Punctuation::At) => {
+                    let hints = self.parse_hints()?;
+                    if let Some(token) = self.peek()
+                        && matches!(token.kind, TokenKind::Keyword(Keyword::Function))
+                    {
+                        let user_function = self.parse_function_declaration(hints)?;
+                        StatementKind::FunctionDeclaration(user_function)
+                    } else {
+                        let statement = self.parse_statement()?;
+                        StatementKind::Hinted {
+                            hints,
+                            statement: Box::new(statement),
+                        }
+                    }
                 }
                 TokenKind::Keyword(Keyword::Let) => {
                     let stmt = self.parse_var_decl()?;
-                    Statement::VariableDeclaration(stmt)
+                    StatementKind::VariableDeclaration(stmt)
                 }
+                TokenKind::Keyword(Keyword::Type) => self.parse_type_declaration()?,
+                TokenKind::Keyword(Keyword::Match) => self.parse_match()?,
                 TokenKind::Keyword(Keyword::Return) => {
                     self.next(); // consume 'return'
                     // Optionally parse an expression after return
@@ -156,106 +229,156 @@ where
                         match token.kind {
                             TokenKind::Punctuation(Punctuation::Semicolon)
                             | TokenKind::Punctuation(Punctuation::CloseCurly) => {
-                                Statement::Return(None)
+                                StatementKind::Return(None)
+                            }
+                            TokenKind::Operator(Operator::BitOr) if in_match_arm => {
+                                StatementKind::Return(None)
                             }
                             _ => {
                                 let expr = self.parse_expression()?;
-                                Statement::Return(Some(expr))
+                                StatementKind::Return(Some(expr))
                             }
                         }
                     } else {
-                        Statement::Return(None)
+                        StatementKind::Return(None)
                     }
                 }
                 TokenKind::Identifier(_) => {
-                    // Try to parse as assignment or increment/decrement
+                    // Try to parse as assignment, compound assignment or increment/decrement
                     if self.is_assignment()? {
                         let stmt = self.parse_assignment()?;
-                        Statement::Assignment {
+                        StatementKind::Assignment {
                             identifier: stmt.0,
                             expr: stmt.1,
                         }
+                    } else if self.is_compound_assignment()? {
+                        let (identifier, operator, expr) = self.parse_compound_assignment()?;
+                        StatementKind::CompoundAssignment {
+                            identifier,
+                            operator,
+                            expr,
+                        }
                     } else if self.is_increment_decrement()? {
-                        let (identifier, op) = self.parse_increment_decrement()?;
-                        // Represent as assignment: x++ => x = x + 1, x-- => x = x - 1
-                        let expr = Expression::Binary {
-                            left: Box::new(Expression::Identifier(identifier.clone())),
-                            operator: op,
-                            right: Box::new(Expression::Literal(Literal::Integer(1))),
-                        };
-                        Statement::Assignment { identifier, expr }
+                        let (identifier, operator) = self.parse_increment_decrement()?;
+                        StatementKind::IncrementDecrement {
+                            identifier,
+                            operator,
+                        }
                     } else {
                         let expr = self.parse_expression()?;
-                        Statement::Expression(expr)
+                        StatementKind::Expression(expr)
                     }
                 }
                 TokenKind::Keyword(Keyword::For) => {
-                    // For simplicity, treat for as an expression statement for now
                     self.next(); // consume 'for'
                     let condition = self.parse_expression()?;
-                    // Use shared parse_body to consume the block
-                    let _body = self.parse_body()?;
-                    // Represent while as a function call for now (to be implemented properly later)
-                    let while_expr = Expression::Call {
-                        callee: Box::new(Expression::Identifier("while".to_string())),
-                        args: vec![condition], // Incomplete representation
-                    };
-                    Statement::Expression(while_expr)
+                    let body = self.parse_body()?;
+                    StatementKind::For { condition, body }
                 }
-                TokenKind::Literal(_) => {
+                TokenKind::Literal(_) | TokenKind::Keyword(Keyword::Panic) => {
                     let expr = self.parse_expression()?;
-                    Statement::Expression(expr)
+                    StatementKind::Expression(expr)
                 }
-                TokenKind::Operator(Operator::Not) => {
+                TokenKind::Operator(operator)
+                    if precedence::prefix(precedence::as_prefix(*operator)).is_some() =>
+                {
                     let expr = self.parse_expression()?;
-                    Statement::Expression(expr)
+                    match self
+                        .consume_if(|t| matches!(t.kind, TokenKind::Operator(Operator::Assign)))
+                    {
+                        Some(_)
+                            if matches!(
+                                expr.kind,
+                                ExpressionKind::Unary {
+                                    operator: Operator::Deref,
+                                    ..
+                                }
+                            ) =>
+                        {
+                            StatementKind::Store {
+                                target: expr,
+                                expr: self.parse_expression()?,
+                            }
+                        }
+                        Some(assign) => {
+                            return Err(self.error(
+                                "parse_statement: only a variable or 'deref' of a pointer can be assigned to",
+                                assign.span.line,
+                                assign.span.column,
+                            ));
+                        }
+                        None => StatementKind::Expression(expr),
+                    }
                 }
                 TokenKind::TypeIdentifier(_)
+                | TokenKind::Label(_)
                 | TokenKind::Keyword(Keyword::Else)
                 | TokenKind::Operator(_)
                 | TokenKind::Punctuation(_)
                 | TokenKind::Unknown(_) => {
                     let t = token.clone();
-                    return Err(self.error("unsupported", t.line, t.column));
+                    return Err(self.error("unsupported", t.span.line, t.span.column));
                 }
             }
         } else {
             return Err(self.error("parse_statement: expected a token, found none", 0, 0));
         };
 
+        let span = self.span_from(start);
         // Optionally consume a semicolon if present
         self.consume_if(|t| matches!(t.kind, TokenKind::Punctuation(Punctuation::Semicolon)));
-        Ok(stmt)
+        Ok(Statement::new(kind, span))
     }
 
     /// Checks if the next tokens represent an assignment (identifier followed by '=')
     fn is_assignment(&mut self) -> ParseResult<bool> {
         let mut iter = self.tokens.clone();
-        if let Some(token) = iter.next() {
-            if let TokenKind::Identifier(_) = token.kind {
-                if let Some(next_token) = iter.next() {
-                    if let TokenKind::Operator(Operator::Assign) = next_token.kind {
-                        return Ok(true);
-                    }
-                }
-            }
+        if let Some(token) = iter.next()
+            && let TokenKind::Identifier(_) = token.kind
+            && let Some(next_token) = iter.next()
+            && let TokenKind::Operator(Operator::Assign) = next_token.kind
+        {
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Checks if the next tokens represent a compound assignment (identifier followed by '+=', '-=', ...)
+    fn is_compound_assignment(&mut self) -> ParseResult<bool> {
+        let mut iter = self.tokens.clone();
+        if let Some(token) = iter.next()
+            && let TokenKind::Identifier(_) = token.kind
+            && let Some(next_token) = iter.next()
+            && let TokenKind::Operator(op) = next_token.kind
+            && op.compound_operation().is_some()
+        {
+            return Ok(true);
         }
         Ok(false)
     }
 
+    /// Checks if the next tokens start an anonymous function ('function' followed by '(')
+    fn is_lambda(&self) -> bool {
+        let mut iter = self.tokens.clone();
+        matches!(
+            iter.next().map(|t| t.kind),
+            Some(TokenKind::Keyword(Keyword::Function))
+        ) && matches!(
+            iter.next().map(|t| t.kind),
+            Some(TokenKind::Punctuation(Punctuation::OpenParen))
+        )
+    }
+
     /// Checks if the next tokens represent an increment or decrement (identifier followed by ++ or --)
     fn is_increment_decrement(&mut self) -> ParseResult<bool> {
         let mut iter = self.tokens.clone();
-        if let Some(token) = iter.next() {
-            if let TokenKind::Identifier(_) = token.kind {
-                if let Some(next_token) = iter.next() {
-                    if let TokenKind::Operator(Operator::Increment)
-                    | TokenKind::Operator(Operator::Decrement) = next_token.kind
-                    {
-                        return Ok(true);
-                    }
-                }
-            }
+        if let Some(token) = iter.next()
+            && let TokenKind::Identifier(_) = token.kind
+            && let Some(next_token) = iter.next()
+            && let TokenKind::Operator(Operator::Increment)
+            | TokenKind::Operator(Operator::Decrement) = next_token.kind
+        {
+            return Ok(true);
         }
         Ok(false)
     }
@@ -285,6 +408,30 @@ where
         Ok((identifier, expr))
     }
 
+    /// Parses a compound assignment statement: identifier ('+=' | '-=' | ...) expression
+    fn parse_compound_assignment(&mut self) -> ParseResult<(String, Operator, Expression)> {
+        let ident_token = self.expect_token(
+            |t| matches!(t.kind, TokenKind::Identifier(_)),
+            "parse_compound_assignment: expected identifier",
+        )?;
+        let identifier = if let TokenKind::Identifier(id) = ident_token.kind {
+            id
+        } else {
+            unreachable!()
+        };
+
+        let op_token = self.expect_token(
+            |t| matches!(t.kind, TokenKind::Operator(op) if op.compound_operation().is_some()),
+            "parse_compound_assignment: expected a compound assignment operator",
+        )?;
+        let TokenKind::Operator(op) = op_token.kind else {
+            unreachable!()
+        };
+
+        let expr = self.parse_expression()?;
+        Ok((identifier, op, expr))
+    }
+
     /// Parses an increment or decrement statement: identifier ++ or identifier --
     fn parse_increment_decrement(&mut self) -> ParseResult<(String, Operator)> {
         let ident_token = self.expect_token(
@@ -299,13 +446,12 @@ where
 
         let op_token = self.expect_next("parse_increment_decrement: expected '++' or '--'")?;
         let op = match op_token.kind {
-            TokenKind::Operator(Operator::Increment) => Operator::Plus,
-            TokenKind::Operator(Operator::Decrement) => Operator::Minus,
+            TokenKind::Operator(op @ (Operator::Increment | Operator::Decrement)) => op,
             _ => {
                 return Err(self.error(
                     "parse_increment_decrement: expected '++' or '--'",
-                    op_token.line,
-                    op_token.column,
+                    op_token.span.line,
+                    op_token.span.column,
                 ));
             }
         };
@@ -314,250 +460,598 @@ where
     }
 
     fn parse_var_decl(&mut self) -> ParseResult<VariableDeclaration> {
+        let start = self.current_span();
         self.expect_token(
             |t| matches!(t.kind, TokenKind::Keyword(Keyword::Let)),
             "parse_var_decl: expected 'var' keyword",
         )?;
 
-        let ident_token = self.expect_token(
+        let pattern = self.parse_pattern()?;
+
+        let expr = match self.peek() {
+            Some(token) if matches!(token.kind, TokenKind::Operator(Operator::Assign)) => {
+                Some(self.parse_initializer()?)
+            }
+            // `let coords { x = 10 }` leaves out the '=' before a struct literal
+            Some(token) if matches!(token.kind, TokenKind::Punctuation(Punctuation::OpenCurly)) => {
+                let span = token.span;
+                return Err(self.error(
+                    "parse_var_decl: expected '=' before the initializer",
+                    span.line,
+                    span.column,
+                ));
+            }
+            _ => None,
+        };
+        Ok(VariableDeclaration::new(
+            pattern,
+            expr,
+            self.span_from(start),
+        ))
+    }
+
+    /// Parse a named type declaration: 'type' identifier '=' type.
+    fn parse_type_declaration(&mut self) -> ParseResult<StatementKind> {
+        self.expect_token(
+            |t| matches!(t.kind, TokenKind::Keyword(Keyword::Type)),
+            "parse_type_declaration: expected 'type' keyword",
+        )?;
+        let name_token = self.expect_token(
             |t| matches!(t.kind, TokenKind::Identifier(_)),
-            "parse_var_decl: expected identifier",
+            "parse_type_declaration: expected the name of the type",
         )?;
-        let ident = if let TokenKind::Identifier(ident) = ident_token.kind {
-            ident
-        } else {
+        let TokenKind::Identifier(name) = name_token.kind else {
             unreachable!()
         };
-
-        let type_token = self.expect_token(
-            |t| matches!(t.kind, TokenKind::TypeIdentifier(_)),
-            "parse_var_decl: expected type identifier",
+        self.expect_token(
+            |t| matches!(t.kind, TokenKind::Operator(Operator::Assign)),
+            "parse_type_declaration: expected '=' after the name of the type",
         )?;
-        let var_type = if let TokenKind::TypeIdentifier(t_ident) = type_token.kind {
-            t_ident
-        } else {
-            unreachable!()
+        let mut lookahead = self.tokens.clone();
+        let (definition, contracts) = match (lookahead.next(), lookahead.next()) {
+            (
+                Some(Token {
+                    kind: TokenKind::Label(_),
+                    ..
+                }),
+                _,
+            ) => (self.parse_sum_type()?, Vec::new()),
+            // Only the definition of a named labeled product can sign contracts
+            (
+                Some(Token {
+                    kind: TokenKind::Punctuation(Punctuation::OpenParen),
+                    ..
+                }),
+                Some(Token {
+                    kind: TokenKind::Label(_),
+                    ..
+                }),
+            ) => {
+                self.next(); // consume '('
+                let fields = self.parse_labeled_fields()?;
+                let contracts = if self
+                    .consume_if(|t| {
+                        matches!(t.kind, TokenKind::Punctuation(Punctuation::Semicolon))
+                    })
+                    .is_some()
+                {
+                    self.parse_contract_implementations(&name)?
+                } else {
+                    Vec::new()
+                };
+                self.expect_token(
+                    |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseParen)),
+                    "parse_type_declaration: expected ')' after a labeled product type",
+                )?;
+                (TypeIdentifier::Labeled(fields), contracts)
+            }
+            _ => (self.parse_type()?, Vec::new()),
         };
-
-        let expr = self.parse_initializer()?;
-        Ok(VariableDeclaration::new(ident, var_type, expr))
-    }
-
-    fn parse_expression(&mut self) -> ParseResult<Expression> {
-        self.parse_logical_or()
+        Ok(StatementKind::TypeDeclaration {
+            name,
+            definition,
+            contracts,
+        })
     }
 
-    fn parse_logical_or(&mut self) -> ParseResult<Expression> {
-        let mut expr = self.parse_logical_and()?;
-        while let Some(token) = self.peek() {
-            match &token.kind {
-                TokenKind::Operator(Operator::Or) => {
-                    self.next();
-                    let right = Box::new(self.parse_logical_and()?);
-                    expr = Expression::Binary {
-                        left: Box::new(expr),
-                        operator: Operator::Or,
-                        right,
-                    };
-                }
-                _ => break,
+    /// Parse the variants of a sum type: '<label> [<type>] + '<label> [<type>] ...
+    fn parse_sum_type(&mut self) -> ParseResult<TypeIdentifier> {
+        let mut variants = Vec::new();
+        loop {
+            let label_token = self.expect_token(
+                |t| matches!(t.kind, TokenKind::Label(_)),
+                "parse_sum_type: expected a variant label",
+            )?;
+            let TokenKind::Label(label) = label_token.kind else {
+                unreachable!()
+            };
+            // A unit variant has nothing after its label
+            let payload = match self.peek().map(|t| &t.kind) {
+                None
+                | Some(TokenKind::Operator(Operator::Plus))
+                | Some(TokenKind::Punctuation(Punctuation::Semicolon)) => None,
+                Some(_) => Some(self.parse_type()?),
+            };
+            variants.push((label, payload));
+            if self
+                .consume_if(|t| matches!(t.kind, TokenKind::Operator(Operator::Plus)))
+                .is_none()
+            {
+                break;
             }
         }
-        Ok(expr)
+        Ok(TypeIdentifier::Sum(variants))
     }
 
-    fn parse_logical_and(&mut self) -> ParseResult<Expression> {
-        let mut expr = self.parse_equality()?;
-        while let Some(token) = self.peek() {
-            match &token.kind {
-                TokenKind::Operator(Operator::And) => {
-                    self.next();
-                    let right = Box::new(self.parse_equality()?);
-                    expr = Expression::Binary {
-                        left: Box::new(expr),
-                        operator: Operator::And,
-                        right,
-                    };
+    /// Parse the contracts a type signs, after the ';' of its definition:
+    /// <contract> { <method> ... } ...
+    fn parse_contract_implementations(
+        &mut self,
+        owner: &str,
+    ) -> ParseResult<Vec<ContractImplementation>> {
+        let mut contracts = Vec::new();
+        while let Some(token) = self.peek()
+            && !matches!(token.kind, TokenKind::Punctuation(Punctuation::CloseParen))
+        {
+            let start = token.span;
+            let contract_token = self.expect_token(
+                |t| matches!(t.kind, TokenKind::Identifier(_)),
+                "parse_contract_implementations: expected the name of a contract",
+            )?;
+            let TokenKind::Identifier(contract) = contract_token.kind else {
+                unreachable!()
+            };
+            self.expect_token(
+                |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::OpenCurly)),
+                "parse_contract_implementations: expected '{' after the contract name",
+            )?;
+            let mut methods = Vec::new();
+            while self
+                .consume_if(|t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseCurly)))
+                .is_none()
+            {
+                let mut method = self.parse_function_declaration(Vec::new())?;
+                // 'self' is a value of the type that signs the contract
+                if let Some(receiver) = method.signature.parameters.first_mut()
+                    && receiver.parameter_name == "self"
+                {
+                    receiver.parameter_type = Some(TypeIdentifier::Named(owner.to_string()));
                 }
-                _ => break,
+                methods.push(method);
             }
+            contracts.push(ContractImplementation {
+                contract,
+                methods,
+                span: self.span_from(start),
+            });
         }
-        Ok(expr)
+        Ok(contracts)
     }
 
-    /// Parse equality and comparison expressions (==, !=, >, <, >=, <=)
-    fn parse_equality(&mut self) -> ParseResult<Expression> {
-        let mut expr = self.parse_comparison()?;
-
-        while let Some(token) = self.peek() {
-            match &token.kind {
-                TokenKind::Operator(op @ (Operator::Equals | Operator::Different)) => {
-                    let op = *op;
-                    self.next();
-                    let right = Box::new(self.parse_comparison()?);
-                    expr = Expression::Binary {
-                        left: Box::new(expr),
-                        operator: op,
-                        right,
-                    };
+    /// Parse a match statement: match <expression> { | <pattern> -> <arm> ... }.
+    /// An arm is a block, or a single statement that ends at the next '|'.
+    fn parse_match(&mut self) -> ParseResult<StatementKind> {
+        self.next(); // consume 'match'
+        let scrutinee = self.parse_expression()?;
+        self.expect_token(
+            |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::OpenCurly)),
+            "parse_match: expected '{' after the matched value",
+        )?;
+        let mut arms = Vec::new();
+        while self
+            .consume_if(|t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseCurly)))
+            .is_none()
+        {
+            self.expect_token(
+                |t| matches!(t.kind, TokenKind::Operator(Operator::BitOr)),
+                "parse_match: expected '|' before a match arm",
+            )?;
+            let pattern = self.parse_pattern()?;
+            self.expect_token(
+                |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::Arrow)),
+                "parse_match: expected '->' after the pattern",
+            )?;
+            let body = match self.peek() {
+                Some(token)
+                    if matches!(token.kind, TokenKind::Punctuation(Punctuation::OpenCurly)) =>
+                {
+                    self.parse_body()?
                 }
-                _ => break,
-            }
+                _ => {
+                    let in_match_arm = std::mem::replace(&mut self.in_match_arm, true);
+                    let statement = self.parse_statement();
+                    self.in_match_arm = in_match_arm;
+                    vec![statement?]
+                }
+            };
+            arms.push(MatchArm { pattern, body });
         }
-        Ok(expr)
+        Ok(StatementKind::Match { scrutinee, arms })
     }
 
-    fn parse_comparison(&mut self) -> ParseResult<Expression> {
-        let mut expr = self.parse_additive()?;
-
-        while let Some(token) = self.peek() {
-            match &token.kind {
-                TokenKind::Operator(
-                    op @ (Operator::GreaterThan
-                    | Operator::LesserThan
-                    | Operator::GreaterEqual
-                    | Operator::LesserEqual),
-                ) => {
-                    let op = *op;
-                    self.next();
-                    let right = Box::new(self.parse_additive()?);
-                    expr = Expression::Binary {
-                        left: Box::new(expr),
-                        operator: op,
-                        right,
-                    };
+    /// Parse a pattern: '_', a name with an optional type, a literal, a tuple
+    /// pattern '(' pattern, ... ')' or a labeled pattern '{' label '=' pattern, ... '}'.
+    fn parse_pattern(&mut self) -> ParseResult<Pattern> {
+        let token = self.expect_next("parse_pattern: expected a pattern, found none")?;
+        let kind = match token.kind {
+            TokenKind::Identifier(name) if name == "_" => PatternKind::Wildcard,
+            TokenKind::Identifier(name) => PatternKind::Binding {
+                name,
+                binding_type: self.parse_optional_type()?,
+            },
+            TokenKind::Literal(literal) => PatternKind::Literal(literal),
+            TokenKind::Label(label) => {
+                let payload = match self.peek().map(|t| &t.kind) {
+                    Some(
+                        TokenKind::Literal(_)
+                        | TokenKind::Identifier(_)
+                        | TokenKind::Label(_)
+                        | TokenKind::Punctuation(Punctuation::OpenParen | Punctuation::OpenCurly),
+                    ) => Some(Box::new(self.parse_pattern()?)),
+                    _ => None,
+                };
+                PatternKind::Variant { label, payload }
+            }
+            TokenKind::Punctuation(Punctuation::OpenParen)
+                if self
+                    .consume_if(|t| {
+                        matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseParen))
+                    })
+                    .is_some() =>
+            {
+                PatternKind::Literal(Literal::Unit)
+            }
+            TokenKind::Punctuation(Punctuation::OpenParen) => {
+                let mut elements = vec![self.parse_pattern()?];
+                while self
+                    .consume_if(|t| matches!(t.kind, TokenKind::Punctuation(Punctuation::Comma)))
+                    .is_some()
+                {
+                    elements.push(self.parse_pattern()?);
+                }
+                self.expect_token(
+                    |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseParen)),
+                    "parse_pattern: expected ',' or ')' in a tuple pattern",
+                )?;
+                // A parenthesized pattern without a comma is just that pattern
+                if elements.len() == 1 {
+                    elements.pop().unwrap().kind
+                } else {
+                    PatternKind::Tuple(elements)
                 }
-                _ => break,
             }
-        }
-        Ok(expr)
-    }
-
-    fn parse_additive(&mut self) -> ParseResult<Expression> {
-        let mut expr = self.parse_term()?;
-
-        while let Some(token) = self.peek() {
-            match &token.kind {
-                TokenKind::Operator(op @ (Operator::Plus | Operator::Minus)) => {
-                    let op = *op;
-                    self.next();
-                    let right = Box::new(self.parse_term()?);
-                    expr = Expression::Binary {
-                        left: Box::new(expr),
-                        operator: op,
-                        right,
+            TokenKind::Punctuation(Punctuation::OpenCurly) => {
+                let mut fields = Vec::new();
+                loop {
+                    let label_token = self.expect_token(
+                        |t| matches!(t.kind, TokenKind::Identifier(_)),
+                        "parse_pattern: expected a field label",
+                    )?;
+                    let TokenKind::Identifier(label) = label_token.kind else {
+                        unreachable!()
                     };
+                    self.expect_token(
+                        |t| matches!(t.kind, TokenKind::Operator(Operator::Assign)),
+                        "parse_pattern: expected '=' after a field label",
+                    )?;
+                    fields.push((label, self.parse_pattern()?));
+                    if self
+                        .consume_if(|t| {
+                            matches!(t.kind, TokenKind::Punctuation(Punctuation::Comma))
+                        })
+                        .is_none()
+                    {
+                        break;
+                    }
                 }
-                _ => break,
+                self.expect_token(
+                    |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseCurly)),
+                    "parse_pattern: expected ',' or '}' in a labeled pattern",
+                )?;
+                PatternKind::Labeled(fields)
             }
-        }
-        Ok(expr)
+            _ => {
+                return Err(self.error(
+                    &format!("parse_pattern: expected a pattern, found {:?}", token.kind),
+                    token.span.line,
+                    token.span.column,
+                ));
+            }
+        };
+        Ok(Pattern::new(kind, self.span_from(token.span)))
     }
 
-    fn parse_term(&mut self) -> ParseResult<Expression> {
-        let mut expr = self.parse_unary()?;
+    fn parse_expression(&mut self) -> ParseResult<Expression> {
+        // Assignments are statements, so an expression stops right above them
+        self.parse_binary(precedence::ASSIGNMENT.level - 1)
+    }
 
-        while let Some(token) = self.peek() {
-            match &token.kind {
-                TokenKind::Operator(op @ (Operator::Multiply | Operator::Divide)) => {
-                    let op = *op;
-                    self.next();
-                    let right = Box::new(self.parse_unary()?);
-                    expr = Expression::Binary {
-                        left: Box::new(expr),
-                        operator: op,
-                        right,
-                    };
-                }
-                _ => break,
+    /// Precedence climbing over the infix operator table: parses operands
+    /// joined by operators whose level is at most `max_level`.
+    fn parse_binary(&mut self, max_level: u8) -> ParseResult<Expression> {
+        let mut expr = self.parse_unary()?;
+        while let Some(Token {
+            kind: TokenKind::Operator(operator),
+            ..
+        }) = self.peek()
+        {
+            let operator = *operator;
+            let Some(precedence) = precedence::infix(operator) else {
+                break;
+            };
+            // The next arm of the match starts here
+            if operator == Operator::BitOr && self.in_match_arm {
+                break;
             }
+            if precedence.level > max_level {
+                break;
+            }
+            self.next();
+            // A left-associative operator does not take an equal level on its right
+            let right_max = match precedence.associativity {
+                Associativity::Left => precedence.level - 1,
+                Associativity::Right => precedence.level,
+            };
+            let right = self.parse_binary(right_max)?;
+            expr = Self::binary(expr, operator, right);
         }
         Ok(expr)
     }
 
+    /// Parse the prefix operators of an operand, which all bind tighter than
+    /// any infix operator.
     fn parse_unary(&mut self) -> ParseResult<Expression> {
-        // FIXME:   this function should peek and pattern match to find what operator
-        //          in particular is triggering the unary expression parsing and
-        //          then return the correct unary expression from that.
-        // Currently only supports '!' (BOOLEAN NOT) unary operator.
-        if let Some(_t) = self.peek() {
-            // If there's a '!' operator, consume it and parse unary recursively
-            if let Some(_op_token) =
-                self.consume_if(|t| matches!(t.kind, TokenKind::Operator(Operator::Not)))
-            {
-                let expr = self.parse_unary()?;
-                Ok(Expression::Unary {
-                    operator: Operator::Not,
+        let start = self.current_span();
+        if let Some(token) = self.peek()
+            && matches!(token.kind, TokenKind::Punctuation(Punctuation::At))
+        {
+            let hints = self.parse_hints()?;
+            let expr = self.parse_unary()?;
+            let span = start.to(expr.span);
+            return Ok(Expression::new(
+                ExpressionKind::Hinted {
+                    hints,
                     expression: Box::new(expr),
-                })
-            } else {
-                self.parse_atom()
-            }
-        } else {
-            self.parse_atom()
+                },
+                span,
+            ));
         }
+        if let Some(Token {
+            kind: TokenKind::Operator(operator),
+            ..
+        }) = self.peek()
+            && precedence::prefix(precedence::as_prefix(*operator)).is_some()
+        {
+            let operator = precedence::as_prefix(*operator);
+            self.next();
+            let expr = self.parse_unary()?;
+            let span = start.to(expr.span);
+            return Ok(Expression::new(
+                ExpressionKind::Unary {
+                    operator,
+                    expression: Box::new(expr),
+                },
+                span,
+            ));
+        }
+        self.parse_atom()
     }
 
     fn parse_atom(&mut self) -> ParseResult<Expression> {
         let token = self.expect_next("parse_atom: expected a token, found none")?;
-        let mut expr = match token.kind {
+        let kind = match token.kind {
             TokenKind::Literal(Literal::Integer(integer_literal)) => {
-                Expression::Literal(Literal::Integer(integer_literal))
+                ExpressionKind::Literal(Literal::Integer(integer_literal))
+            }
+            TokenKind::Literal(Literal::Float(float_literal)) => {
+                ExpressionKind::Literal(Literal::Float(float_literal))
             }
             TokenKind::Literal(Literal::Boolean(boolean_literal)) => {
-                Expression::Literal(Literal::Boolean(boolean_literal))
+                ExpressionKind::Literal(Literal::Boolean(boolean_literal))
             }
             TokenKind::Literal(Literal::Character(char_literal)) => {
-                Expression::Literal(Literal::Character(char_literal))
+                ExpressionKind::Literal(Literal::Character(char_literal))
+            }
+            TokenKind::Literal(Literal::String(string_literal)) => {
+                ExpressionKind::Literal(Literal::String(string_literal))
+            }
+            TokenKind::Literal(Literal::Null) => ExpressionKind::Literal(Literal::Null),
+            TokenKind::Identifier(id) => ExpressionKind::Identifier(id),
+            TokenKind::Label(label) => {
+                // A struct literal payload may name its type first
+                let mut lookahead = self.tokens.clone();
+                let payload_type = match (lookahead.next(), lookahead.next()) {
+                    (
+                        Some(Token {
+                            kind: TokenKind::Identifier(name),
+                            ..
+                        }),
+                        Some(Token {
+                            kind: TokenKind::Punctuation(Punctuation::OpenCurly),
+                            ..
+                        }),
+                    ) => {
+                        self.next(); // consume the type name
+                        Some(name)
+                    }
+                    _ => None,
+                };
+                // The payload is a single operand, 'Ok (a / b) needs its parentheses
+                let payload = match self.peek().map(|t| &t.kind) {
+                    Some(
+                        TokenKind::Literal(_)
+                        | TokenKind::Identifier(_)
+                        | TokenKind::Label(_)
+                        | TokenKind::Keyword(Keyword::Function)
+                        | TokenKind::Punctuation(Punctuation::OpenParen | Punctuation::OpenCurly),
+                    ) => Some(Box::new(self.parse_unary()?)),
+                    _ => None,
+                };
+                return Ok(Expression::new(
+                    ExpressionKind::Variant {
+                        label,
+                        payload,
+                        payload_type,
+                    },
+                    self.span_from(token.span),
+                ));
+            }
+            TokenKind::Keyword(Keyword::Function) => self.parse_lambda()?,
+            TokenKind::Keyword(Keyword::Panic) => {
+                // A bare panic ends where its statement does
+                let message = match self.peek().map(|t| &t.kind) {
+                    Some(
+                        TokenKind::Literal(_)
+                        | TokenKind::Identifier(_)
+                        | TokenKind::Punctuation(Punctuation::OpenParen),
+                    ) => Some(Box::new(self.parse_expression()?)),
+                    _ => None,
+                };
+                return Ok(Expression::new(
+                    ExpressionKind::Panic(message),
+                    self.span_from(token.span),
+                ));
+            }
+            TokenKind::Punctuation(Punctuation::OpenParen)
+                if self
+                    .consume_if(|t| {
+                        matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseParen))
+                    })
+                    .is_some() =>
+            {
+                ExpressionKind::Literal(Literal::Unit)
             }
-            TokenKind::Identifier(id) => Expression::Identifier(id),
             TokenKind::Punctuation(Punctuation::OpenParen) => {
-                let inner_expr = self.parse_expression()?;
+                let inner_expr = self.nested(Self::parse_expression)?;
+                // A comma after the first element makes this a tuple literal
+                let kind = if self
+                    .consume_if(|t| matches!(t.kind, TokenKind::Punctuation(Punctuation::Comma)))
+                    .is_some()
+                {
+                    let mut elements = vec![inner_expr];
+                    elements.extend(self.parse_arguments()?);
+                    ExpressionKind::Tuple(elements)
+                } else {
+                    ExpressionKind::Grouping(Box::new(inner_expr))
+                };
                 let _close = self.expect_token(
                     |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseParen)),
                     "parse_atom: expected ')'",
                 )?;
-                Expression::Grouping(Box::new(inner_expr))
+                kind
+            }
+            TokenKind::Punctuation(Punctuation::OpenCurly) => {
+                self.nested(Self::parse_struct_literal)?
             }
             _ => {
                 return Err(self.error(
                     &format!("parse_atom: expected an atom, found {:?}", token.kind),
-                    token.line,
-                    token.column,
+                    token.span.line,
+                    token.span.column,
                 ));
             }
         };
+        let mut expr = Expression::new(kind, self.span_from(token.span));
 
-        // Parse function call if '(' follows
+        // Parse function calls and casts that follow
         while let Some(token) = self.peek() {
             if matches!(token.kind, TokenKind::Punctuation(Punctuation::OpenParen)) {
+                let open = token.span;
                 self.next(); // consume '('
-                let mut args = Vec::new();
-                if let Some(token) = self.peek() {
-                    if !matches!(token.kind, TokenKind::Punctuation(Punctuation::CloseParen)) {
-                        loop {
-                            args.push(self.parse_expression()?);
-                            if let Some(token) = self.peek() {
-                                if matches!(token.kind, TokenKind::Punctuation(Punctuation::Comma))
-                                {
-                                    self.next(); // consume ','
-                                } else {
-                                    break;
-                                }
-                            } else {
-                                break;
-                            }
+                let mut args = self.parse_arguments()?;
+                self.expect_token(
+                    |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseParen)),
+                    "parse_atom: expected ')' after function call arguments",
+                )?;
+                let span = self.span_from(expr.span);
+                let cast = match &expr.kind {
+                    ExpressionKind::Identifier(name) => cast_target(name),
+                    _ => None,
+                };
+                let kind = match cast {
+                    Some(target) => {
+                        if args.len() != 1 {
+                            return Err(self.error(
+                                "parse_atom: a cast takes exactly one value",
+                                open.line,
+                                open.column,
+                            ));
+                        }
+                        ExpressionKind::Cast {
+                            expression: Box::new(args.remove(0)),
+                            target,
                         }
                     }
+                    None => ExpressionKind::Call {
+                        callee: Box::new(expr),
+                        args,
+                    },
+                };
+                expr = Expression::new(kind, span);
+            } else if matches!(token.kind, TokenKind::Punctuation(Punctuation::Dot)) {
+                self.next(); // consume '.'
+                let method_token = self.expect_token(
+                    |t| matches!(t.kind, TokenKind::Identifier(_)),
+                    "parse_atom: expected a field or method name after '.'",
+                )?;
+                let TokenKind::Identifier(method) = method_token.kind else {
+                    unreachable!()
+                };
+                // Without arguments it reads a field
+                if self
+                    .consume_if(|t| {
+                        matches!(t.kind, TokenKind::Punctuation(Punctuation::OpenParen))
+                    })
+                    .is_none()
+                {
+                    let span = self.span_from(expr.span);
+                    expr = Expression::new(
+                        ExpressionKind::Field {
+                            receiver: Box::new(expr),
+                            field: method,
+                        },
+                        span,
+                    );
+                    continue;
                 }
+                let args = self.parse_arguments()?;
                 self.expect_token(
                     |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseParen)),
-                    "parse_atom: expected ')' after function call arguments",
+                    "parse_atom: expected ')' after method call arguments",
                 )?;
-                expr = Expression::Call {
-                    callee: Box::new(expr),
-                    args,
+                let span = self.span_from(expr.span);
+                let kind = match cast_target(&method) {
+                    Some(_) if !args.is_empty() => {
+                        return Err(self.error(
+                            "parse_atom: a cast after '.' takes no arguments",
+                            method_token.span.line,
+                            method_token.span.column,
+                        ));
+                    }
+                    Some(target) => ExpressionKind::Cast {
+                        expression: Box::new(expr),
+                        target,
+                    },
+                    None => ExpressionKind::MethodCall {
+                        receiver: Box::new(expr),
+                        method,
+                        args,
+                    },
                 };
+                expr = Expression::new(kind, span);
+            } else if matches!(token.kind, TokenKind::Punctuation(Punctuation::Question)) {
+                self.next(); // consume '?'
+                let span = self.span_from(expr.span);
+                expr = Expression::new(ExpressionKind::Propagate(Box::new(expr)), span);
+            } else if let TokenKind::Operator(op @ (Operator::Increment | Operator::Decrement)) =
+                token.kind
+            {
+                let span = token.span;
+                return Err(self.error(
+                    &format!("'{}' is a statement, not an expression", op),
+                    span.line,
+                    span.column,
+                ));
             } else {
                 break;
             }
@@ -566,7 +1060,99 @@ where
         Ok(expr)
     }
 
-    fn parse_function_declaration(&mut self) -> ParseResult<Function> {
+    /// Parse a struct literal after its '{' has been consumed: label '=' expression, ... '}'.
+    fn parse_struct_literal(&mut self) -> ParseResult<ExpressionKind> {
+        let mut fields = Vec::new();
+        while self
+            .consume_if(|t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseCurly)))
+            .is_none()
+        {
+            let label_token = self.expect_token(
+                |t| matches!(t.kind, TokenKind::Identifier(_)),
+                "parse_struct_literal: expected a field label",
+            )?;
+            let TokenKind::Identifier(label) = label_token.kind else {
+                unreachable!()
+            };
+            let value = self.parse_initializer()?;
+            fields.push((label, value));
+            if self
+                .consume_if(|t| matches!(t.kind, TokenKind::Punctuation(Punctuation::Comma)))
+                .is_none()
+            {
+                self.expect_token(
+                    |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseCurly)),
+                    "parse_struct_literal: expected ',' or '}'",
+                )?;
+                break;
+            }
+        }
+        Ok(ExpressionKind::Struct(fields))
+    }
+
+    /// Parse a comma separated list of expressions up to (not including) ')'.
+    fn parse_arguments(&mut self) -> ParseResult<Vec<Expression>> {
+        self.nested(Self::parse_argument_list)
+    }
+
+    fn parse_argument_list(&mut self) -> ParseResult<Vec<Expression>> {
+        let mut args = Vec::new();
+        if let Some(token) = self.peek()
+            && !matches!(token.kind, TokenKind::Punctuation(Punctuation::CloseParen))
+        {
+            loop {
+                args.push(self.parse_expression()?);
+                if self
+                    .consume_if(|t| matches!(t.kind, TokenKind::Punctuation(Punctuation::Comma)))
+                    .is_none()
+                {
+                    break;
+                }
+            }
+        }
+        Ok(args)
+    }
+
+    /// Parse a run of hints: '@' name ['(' arguments ')'] ...
+    fn parse_hints(&mut self) -> ParseResult<Vec<Hint>> {
+        let mut hints = Vec::new();
+        while let Some(at) =
+            self.consume_if(|t| matches!(t.kind, TokenKind::Punctuation(Punctuation::At)))
+        {
+            let name_token = self.expect_token(
+                |t| matches!(t.kind, TokenKind::Identifier(_)),
+                "parse_hints: expected hint name after '@'",
+            )?;
+            let name = if let TokenKind::Identifier(n) = name_token.kind {
+                n
+            } else {
+                unreachable!()
+            };
+            let arguments = if self
+                .consume_if(|t| matches!(t.kind, TokenKind::Punctuation(Punctuation::OpenParen)))
+                .is_some()
+            {
+                let arguments = self.parse_arguments()?;
+                self.expect_token(
+                    |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseParen)),
+                    "parse_hints: expected ')' after hint arguments",
+                )?;
+                arguments
+            } else {
+                Vec::new()
+            };
+            hints.push(Hint {
+                name,
+                arguments,
+                placement: HintPlacement::Inline,
+                span: self.span_from(at.span),
+            });
+        }
+        Ok(hints)
+    }
+
+    fn parse_function_declaration(&mut self, hints: Vec<Hint>) -> ParseResult<Function> {
+        let start = self.current_span();
         self.expect_token(
             |t| matches!(t.kind, TokenKind::Keyword(Keyword::Function)),
             "parse_function_declaration: expected 'function' keyword",
@@ -583,10 +1169,49 @@ where
             unreachable!()
         };
 
-        // Parameters
+        let args = self.parse_parameters(true)?;
+        let return_type = self.parse_optional_type()?;
+
+        // Function body (use shared parse_body); without one this is a forward declaration
+        let body = match self.peek() {
+            Some(token) if matches!(token.kind, TokenKind::Punctuation(Punctuation::OpenCurly)) => {
+                FunctionBody::Statements(self.parse_body()?)
+            }
+            _ => FunctionBody::Empty,
+        };
+
+        // Hints written before the function as `@hint(param)` are bound to that
+        // parameter as promises by the hint pass
+        Ok(Function {
+            hints,
+            signature: FunctionSignature {
+                name,
+                parameters: args,
+                return_type,
+            },
+            body,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parse an anonymous function after its 'function' keyword has been consumed.
+    fn parse_lambda(&mut self) -> ParseResult<ExpressionKind> {
+        let parameters = self.parse_parameters(false)?;
+        let return_type = self.parse_optional_type()?;
+        let body = self.parse_body()?;
+        Ok(ExpressionKind::Lambda(Box::new(Lambda {
+            parameters,
+            return_type,
+            body,
+        })))
+    }
+
+    /// Parse a parameter list: '(' [hints] name type, ... ')'. Unless
+    /// `require_types` is set, a parameter may leave its type to inference.
+    fn parse_parameters(&mut self, require_types: bool) -> ParseResult<Vec<FunctionParameter>> {
         self.expect_token(
             |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::OpenParen)),
-            "parse_function_declaration: expected '('",
+            "parse_parameters: expected '('",
         )?;
         let mut args = Vec::new();
         while let Some(token) = self.peek() {
@@ -595,10 +1220,12 @@ where
                     self.next();
                     break;
                 }
-                TokenKind::Identifier(_) => {
+                TokenKind::Identifier(_) | TokenKind::Punctuation(Punctuation::At) => {
+                    let start = token.span;
+                    let parameter_hints = self.parse_hints()?;
                     let param_name_token = self.expect_token(
                         |t| matches!(t.kind, TokenKind::Identifier(_)),
-                        "parse_function_declaration: expected parameter name",
+                        "parse_parameters: expected parameter name",
                     )?;
                     let argument_name = if let TokenKind::Identifier(n) = param_name_token.kind {
                         n
@@ -606,20 +1233,19 @@ where
                         unreachable!()
                     };
 
-                    let param_type_token = self.expect_token(
-                        |t| matches!(t.kind, TokenKind::TypeIdentifier(_)),
-                        "parse_function_declaration: expected parameter type",
-                    )?;
-                    let argument_type = if let TokenKind::TypeIdentifier(t) = param_type_token.kind
-                    {
-                        t
+                    // A method's 'self' has the type that signs the contract
+                    let is_receiver = argument_name == "self" && args.is_empty();
+                    let argument_type = if require_types && !is_receiver {
+                        Some(self.parse_type()?)
                     } else {
-                        unreachable!()
+                        self.parse_optional_type()?
                     };
 
                     args.push(FunctionParameter {
                         parameter_name: argument_name,
                         parameter_type: argument_type,
+                        hints: parameter_hints,
+                        span: self.span_from(start),
                     });
 
                     // Optional comma
@@ -628,45 +1254,151 @@ where
                     });
                 }
                 _ => {
-                    let line = token.line;
-                    let column = token.column;
+                    let line = token.span.line;
+                    let column = token.span.column;
                     return Err(self.error(
-                        "parse_func_decl: unexpected token in parameter list",
+                        "parse_parameters: unexpected token in parameter list",
                         line,
                         column,
                     ));
                 }
             }
         }
+        Ok(args)
+    }
 
-        // Optional return type
-        let return_type = if let Some(ret_type_token) =
-            self.consume_if(|t| matches!(t.kind, TokenKind::TypeIdentifier(_)))
-        {
-            if let TokenKind::TypeIdentifier(t) = ret_type_token.kind {
-                Some(t)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        // Function body (use shared parse_body)
-        let body = self.parse_body()?;
+    /// Parse a type if one follows. Return types, let bindings and the
+    /// parameters of anonymous functions may leave it out.
+    fn parse_optional_type(&mut self) -> ParseResult<Option<TypeIdentifier>> {
+        match self.peek() {
+            Some(Token {
+                kind:
+                    TokenKind::TypeIdentifier(_)
+                    | TokenKind::Identifier(_)
+                    | TokenKind::Punctuation(Punctuation::OpenParen)
+                    | TokenKind::Operator(Operator::BitAnd | Operator::And),
+                ..
+            }) => Ok(Some(self.parse_type()?)),
+            _ => Ok(None),
+        }
+    }
 
-        Ok(Function {
-            signature: FunctionSignature {
-                name,
-                parameters: args,
-                return_type,
+    /// Parse a type: a type identifier, a pointer type '&' type, a safe pointer
+    /// type 'unique' | 'shared' | 'weak' '&' type, a function
+    /// type '(' types ')' '->' type or a product type '(' type '*' type ... ')',
+    /// whose components may be labeled as in '(' 'label type '*' ... ')'.
+    fn parse_type(&mut self) -> ParseResult<TypeIdentifier> {
+        let token = self.expect_next("parse_type: expected a type, found none")?;
+        match token.kind {
+            TokenKind::TypeIdentifier(t) => Ok(t),
+            TokenKind::Identifier(name) => match Ownership::from_qualifier(&name) {
+                Some(ownership)
+                    if matches!(
+                        self.peek().map(|t| &t.kind),
+                        Some(TokenKind::Operator(Operator::BitAnd | Operator::And))
+                    ) =>
+                {
+                    let pointer = self.expect_next("parse_type: expected '&'")?;
+                    let pointee = self.parse_type()?;
+                    // The qualifier applies to the outer pointer of '&&'
+                    let pointee = match pointer.kind {
+                        TokenKind::Operator(Operator::And) => {
+                            TypeIdentifier::Pointer(Box::new(pointee))
+                        }
+                        _ => pointee,
+                    };
+                    Ok(TypeIdentifier::SafePointer(ownership, Box::new(pointee)))
+                }
+                _ => Ok(TypeIdentifier::Named(name)),
             },
-            body: FunctionBody::Statements(body),
-        })
+            TokenKind::Operator(Operator::BitAnd) => {
+                Ok(TypeIdentifier::Pointer(Box::new(self.parse_type()?)))
+            }
+            // '&&' is lexed as one token but here it is a pointer to a pointer
+            TokenKind::Operator(Operator::And) => Ok(TypeIdentifier::Pointer(Box::new(
+                TypeIdentifier::Pointer(Box::new(self.parse_type()?)),
+            ))),
+            TokenKind::Punctuation(Punctuation::OpenParen)
+                if matches!(self.peek().map(|t| &t.kind), Some(TokenKind::Label(_))) =>
+            {
+                let fields = self.parse_labeled_fields()?;
+                self.expect_token(
+                    |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseParen)),
+                    "parse_type: expected ')' after a labeled product type",
+                )?;
+                Ok(TypeIdentifier::Labeled(fields))
+            }
+            TokenKind::Punctuation(Punctuation::OpenParen) => {
+                let mut parameters = Vec::new();
+                let mut is_product = false;
+                while self
+                    .consume_if(|t| {
+                        matches!(t.kind, TokenKind::Punctuation(Punctuation::CloseParen))
+                    })
+                    .is_none()
+                {
+                    parameters.push(self.parse_type()?);
+                    if self
+                        .consume_if(|t| matches!(t.kind, TokenKind::Operator(Operator::Multiply)))
+                        .is_some()
+                    {
+                        is_product = true;
+                        continue;
+                    }
+                    self.consume_if(|t| {
+                        matches!(t.kind, TokenKind::Punctuation(Punctuation::Comma))
+                    });
+                }
+                if is_product {
+                    return Ok(TypeIdentifier::Tuple(parameters));
+                }
+                self.expect_token(
+                    |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::Arrow)),
+                    "parse_type: expected '->' after function parameter types",
+                )?;
+                let return_type = self.parse_type()?;
+                Ok(TypeIdentifier::Function {
+                    parameters,
+                    return_type: Box::new(return_type),
+                })
+            }
+            _ => Err(self.error(
+                "parse_type: expected a type",
+                token.span.line,
+                token.span.column,
+            )),
+        }
+    }
+
+    /// Parse the fields of a labeled product type after its '(', up to the
+    /// ')' or the ';' before the contracts it signs.
+    fn parse_labeled_fields(&mut self) -> ParseResult<Vec<(String, TypeIdentifier)>> {
+        let mut fields = Vec::new();
+        loop {
+            let label_token = self.expect_token(
+                |t| matches!(t.kind, TokenKind::Label(_)),
+                "parse_type: expected a label in a labeled product type",
+            )?;
+            let TokenKind::Label(label) = label_token.kind else {
+                unreachable!()
+            };
+            fields.push((label, self.parse_type()?));
+            if self
+                .consume_if(|t| matches!(t.kind, TokenKind::Operator(Operator::Multiply)))
+                .is_none()
+            {
+                break;
+            }
+        }
+        Ok(fields)
     }
 
     /// Parse a block body: expects '{' then parses statements until matching '}'.
     fn parse_body(&mut self) -> ParseResult<Vec<Statement>> {
+        self.nested(Self::parse_block)
+    }
+
+    fn parse_block(&mut self) -> ParseResult<Vec<Statement>> {
         self.expect_token(
             |t| matches!(t.kind, TokenKind::Punctuation(Punctuation::OpenCurly)),
             "parse_body: expected '{'",
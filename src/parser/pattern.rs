@@ -0,0 +1,51 @@
+use crate::token::{Literal, Span, TypeIdentifier};
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+impl Pattern {
+    pub fn new(kind: PatternKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    /// The names this pattern binds, in source order.
+    pub fn bindings(&self) -> Vec<&String> {
+        match &self.kind {
+            PatternKind::Wildcard | PatternKind::Literal(_) => Vec::new(),
+            PatternKind::Binding { name, .. } => vec![name],
+            PatternKind::Tuple(elements) => elements.iter().flat_map(Pattern::bindings).collect(),
+            PatternKind::Labeled(fields) => fields
+                .iter()
+                .flat_map(|(_, pattern)| pattern.bindings())
+                .collect(),
+            PatternKind::Variant { payload, .. } => payload
+                .as_ref()
+                .map(|payload| payload.bindings())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PatternKind {
+    // _
+    Wildcard,
+    // A name, optionally annotated with the type it must have: x or x int
+    Binding {
+        name: String,
+        binding_type: Option<TypeIdentifier>,
+    },
+    Literal(Literal),
+    // (<pattern>, <pattern>, ...)
+    Tuple(Vec<Pattern>),
+    // { <label> = <pattern>, ... }
+    Labeled(Vec<(String, Pattern)>),
+    // '<variant> [<pattern>]
+    Variant {
+        label: String,
+        payload: Option<Box<Pattern>>,
+    },
+}
@@ -0,0 +1,96 @@
+use crate::token::Operator;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+/// A row of the table in spec §"Operator precedence and associativity".
+/// Levels are numbered like the spec: 1 binds tightest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Precedence {
+    pub level: u8,
+    pub associativity: Associativity,
+}
+
+const fn left(level: u8) -> Precedence {
+    Precedence {
+        level,
+        associativity: Associativity::Left,
+    }
+}
+
+const fn right(level: u8) -> Precedence {
+    Precedence {
+        level,
+        associativity: Associativity::Right,
+    }
+}
+
+// Level 1 (grouping, calls and field access) is parsed by the atom itself
+const PREFIX: Precedence = right(2);
+pub const ASSIGNMENT: Precedence = right(13);
+
+pub const PREFIX_OPERATORS: &[(Operator, Precedence)] = &[
+    (Operator::Minus, PREFIX),
+    (Operator::Not, PREFIX),
+    (Operator::BitNot, PREFIX),
+    (Operator::Deref, PREFIX),
+    (Operator::AddressOf, PREFIX),
+];
+
+pub const INFIX_OPERATORS: &[(Operator, Precedence)] = &[
+    (Operator::Multiply, left(3)),
+    (Operator::Divide, left(3)),
+    (Operator::Modulo, left(3)),
+    (Operator::Plus, left(4)),
+    (Operator::Minus, left(4)),
+    (Operator::ShiftLeft, left(5)),
+    (Operator::ShiftRight, left(5)),
+    (Operator::LesserThan, left(6)),
+    (Operator::LesserEqual, left(6)),
+    (Operator::GreaterThan, left(6)),
+    (Operator::GreaterEqual, left(6)),
+    (Operator::Equals, left(7)),
+    (Operator::Different, left(7)),
+    (Operator::StrictEquals, left(7)),
+    (Operator::StrictDifferent, left(7)),
+    (Operator::BitAnd, left(8)),
+    (Operator::BitXor, left(9)),
+    (Operator::BitOr, left(10)),
+    (Operator::And, left(11)),
+    (Operator::Or, left(12)),
+    (Operator::Assign, ASSIGNMENT),
+    (Operator::PlusAssign, ASSIGNMENT),
+    (Operator::MinusAssign, ASSIGNMENT),
+    (Operator::MultiplyAssign, ASSIGNMENT),
+    (Operator::DivideAssign, ASSIGNMENT),
+    (Operator::ModuloAssign, ASSIGNMENT),
+];
+
+fn lookup(table: &[(Operator, Precedence)], operator: Operator) -> Option<Precedence> {
+    table
+        .iter()
+        .find(|(candidate, _)| *candidate == operator)
+        .map(|(_, precedence)| *precedence)
+}
+
+/// The operator a token means when written before an operand: `&x` takes
+/// the address of `x`.
+pub fn as_prefix(operator: Operator) -> Operator {
+    match operator {
+        Operator::BitAnd => Operator::AddressOf,
+        other => other,
+    }
+}
+
+/// Precedence of `operator` written before its operand.
+pub fn prefix(operator: Operator) -> Option<Precedence> {
+    lookup(PREFIX_OPERATORS, operator)
+}
+
+/// Precedence of `operator` written between two operands.
+pub fn infix(operator: Operator) -> Option<Precedence> {
+    lookup(INFIX_OPERATORS, operator)
+}
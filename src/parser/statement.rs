@@ -1,20 +1,80 @@
 use crate::parser::VariableDeclaration;
+use crate::parser::contract::ContractImplementation;
 use crate::parser::expression::Expression;
 use crate::parser::function::Function;
+use crate::parser::hint::Hint;
+use crate::parser::pattern::Pattern;
+use crate::token::{Operator, Span, TypeIdentifier};
 
 #[derive(Debug, Clone)]
-pub enum Statement {
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+impl Statement {
+    pub fn new(kind: StatementKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum StatementKind {
     VariableDeclaration(VariableDeclaration),
     Assignment {
         identifier: String,
         expr: Expression,
     },
+    // deref <pointer> = expr, a write through a pointer. The target is the
+    // whole 'deref' expression, checked like a read of the pointed-to value.
+    Store {
+        target: Expression,
+        expr: Expression,
+    },
+    // identifier op= expr, kept apart from plain assignment so it can be printed back as written
+    CompoundAssignment {
+        identifier: String,
+        operator: Operator,
+        expr: Expression,
+    },
+    // identifier++ or identifier--
+    IncrementDecrement {
+        identifier: String,
+        operator: Operator,
+    },
     Expression(Expression),
     FunctionDeclaration(Function),
+    // type <name> = <type>; a labeled product may sign contracts after a ';'
+    TypeDeclaration {
+        name: String,
+        definition: TypeIdentifier,
+        contracts: Vec<ContractImplementation>,
+    },
     Return(Option<Expression>),
     If {
         condition: Expression,
         then_branch: Vec<Statement>,
         else_branch: Option<Vec<Statement>>,
     },
+    // for <condition> { <body> }, runs the body while the condition holds
+    For {
+        condition: Expression,
+        body: Vec<Statement>,
+    },
+    Hinted {
+        hints: Vec<Hint>,
+        statement: Box<Statement>,
+    },
+    // match <expression> { | <pattern> -> <arm> ... }
+    Match {
+        scrutinee: Expression,
+        arms: Vec<MatchArm>,
+    },
+}
+
+/// An arm runs a block, or a single statement written right after the '->'.
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Vec<Statement>,
 }
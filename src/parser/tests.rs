@@ -0,0 +1,666 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::{
+        lexer::Lexer,
+        parser::{
+            Expression, ExpressionKind, FunctionBody, Pattern, PatternKind, Statement,
+            StatementKind, VariableDeclaration,
+            precedence::{self, Associativity, Precedence},
+        },
+        test_support::{parse, try_parse},
+        token::{Literal, Operator, Ownership, Span, TokenKind, TypeIdentifier},
+    };
+
+    const SPEC: &str = include_str!("../../docs/language_specification.md");
+
+    /// Parse `src` as an initializer and render it fully parenthesized.
+    fn shape(src: &str) -> String {
+        let ast = parse(&format!("let v int = {};", src));
+        let StatementKind::VariableDeclaration(var_decl) = &ast.statements[0].kind else {
+            panic!("expected a variable declaration");
+        };
+        render(var_decl.expression.as_ref().unwrap())
+    }
+
+    fn render(expr: &Expression) -> String {
+        match &expr.kind {
+            ExpressionKind::Binary {
+                left,
+                operator,
+                right,
+            } => format!("({:?} {} {})", operator, render(left), render(right)),
+            ExpressionKind::Unary {
+                operator,
+                expression,
+            } => format!("({:?} {})", operator, render(expression)),
+            ExpressionKind::Literal(Literal::Integer(value)) => value.to_string(),
+            ExpressionKind::Identifier(name) => name.clone(),
+            ExpressionKind::Grouping(expression) => render(expression),
+            ExpressionKind::Call { callee, args } => format!(
+                "{}({})",
+                render(callee),
+                args.iter().map(render).collect::<Vec<_>>().join(", ")
+            ),
+            ExpressionKind::Tuple(elements) => format!(
+                "({})",
+                elements.iter().map(render).collect::<Vec<_>>().join(", ")
+            ),
+            ExpressionKind::Cast { expression, target } => {
+                format!("cast_{}({})", target, render(expression))
+            }
+            ExpressionKind::Variant { label, payload, .. } => match payload {
+                Some(payload) => format!("'{} {}", label, render(payload)),
+                None => format!("'{}", label),
+            },
+            ExpressionKind::Field { receiver, field } => format!("{}.{}", render(receiver), field),
+            ExpressionKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => format!(
+                "{}.{}({})",
+                render(receiver),
+                method,
+                args.iter().map(render).collect::<Vec<_>>().join(", ")
+            ),
+            ExpressionKind::Propagate(expression) => format!("{}?", render(expression)),
+            other => format!("{:?}", other),
+        }
+    }
+
+    /// Rows of the spec's precedence table: level, operator symbols, associativity.
+    fn spec_precedence_rows() -> Vec<(u8, Vec<String>, Associativity)> {
+        let section = SPEC
+            .split("#### Operator precedence and associativity")
+            .nth(1)
+            .expect("spec has a precedence section");
+        section
+            .lines()
+            .skip_while(|line| !line.starts_with('|'))
+            .take_while(|line| line.starts_with('|'))
+            .skip(2) // header and separator
+            .map(|row| {
+                let cells: Vec<&str> = row.trim_matches('|').split(" | ").collect();
+                let level = cells[0]
+                    .trim()
+                    .split(' ')
+                    .next()
+                    .and_then(|level| level.parse().ok())
+                    .expect("row starts with a level");
+                let symbols = cells[1]
+                    .split('`')
+                    .skip(1)
+                    .step_by(2)
+                    .map(|symbol| symbol.replace("\\|", "|"))
+                    .collect();
+                let associativity = match cells[2].trim() {
+                    "Left-to-right" => Associativity::Left,
+                    "Right-to-left" => Associativity::Right,
+                    other => panic!("unknown associativity '{}'", other),
+                };
+                (level, symbols, associativity)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_statement_spans() {
+        let ast = parse("let x int = 1 + 2;\nprint_int(x);");
+        // The trailing semicolon is not part of the statement
+        assert_eq!(ast.statements[0].span, Span::new(1, 1, 1, 18));
+        assert_eq!(ast.statements[1].span, Span::new(2, 1, 2, 13));
+    }
+
+    #[test]
+    fn test_expression_spans() {
+        let ast = parse("let x int = (1 + 2) * 3;");
+        let StatementKind::VariableDeclaration(var_decl) = &ast.statements[0].kind else {
+            panic!("expected a variable declaration");
+        };
+        assert_eq!(var_decl.span, Span::new(1, 1, 1, 24));
+        let expression = var_decl.expression.as_ref().unwrap();
+        assert_eq!(expression.span, Span::new(1, 13, 1, 24));
+        let ExpressionKind::Binary { left, right, .. } = &expression.kind else {
+            panic!("expected a binary expression");
+        };
+        assert_eq!(left.span, Span::new(1, 13, 1, 20));
+        assert_eq!(right.span, Span::new(1, 23, 1, 24));
+    }
+
+    #[test]
+    fn test_function_spans() {
+        let ast = parse("function add(a int, b int) int {\n  return a + b;\n}");
+        let StatementKind::FunctionDeclaration(function) = &ast.statements[0].kind else {
+            panic!("expected a function declaration");
+        };
+        assert_eq!(function.span, Span::new(1, 1, 3, 2));
+        assert_eq!(
+            function.signature.parameters[1].span,
+            Span::new(1, 21, 1, 26)
+        );
+        let crate::parser::FunctionBody::Statements(body) = &function.body else {
+            panic!("expected a function body");
+        };
+        assert_eq!(body[0].span, Span::new(2, 3, 2, 15));
+    }
+
+    #[test]
+    fn test_precedence_table_matches_spec() {
+        let rows = spec_precedence_rows();
+        assert_eq!(rows.len(), 13);
+        let (mut prefix, mut infix_count) = (Vec::new(), 0);
+        for (level, symbols, associativity) in rows {
+            // Level 1 is grouping, calls and field access, parsed by the atom
+            if level == 1 {
+                continue;
+            }
+            let expected = Precedence {
+                level,
+                associativity,
+            };
+            for symbol in symbols {
+                let kinds: Vec<TokenKind> = Lexer::new(&symbol).map(|t| t.kind).collect();
+                let [TokenKind::Operator(operator)] = &kinds[..] else {
+                    panic!("'{}' does not lex to a single operator", symbol);
+                };
+                if level == 2 {
+                    let operator = precedence::as_prefix(*operator);
+                    assert_eq!(precedence::prefix(operator), Some(expected), "{}", symbol);
+                    if !prefix.contains(&operator) {
+                        prefix.push(operator);
+                    }
+                } else {
+                    assert_eq!(precedence::infix(*operator), Some(expected), "{}", symbol);
+                    infix_count += 1;
+                }
+            }
+        }
+        assert_eq!(prefix.len(), precedence::PREFIX_OPERATORS.len());
+        assert_eq!(infix_count, precedence::INFIX_OPERATORS.len());
+    }
+
+    #[test]
+    fn test_binary_precedence() {
+        assert_eq!(shape("1 + 2 * 3"), "(Plus 1 (Multiply 2 3))");
+        assert_eq!(shape("1 * 2 + 3 % 4"), "(Plus (Multiply 1 2) (Modulo 3 4))");
+        assert_eq!(shape("a || b && c"), "(Or a (And b c))");
+        assert_eq!(shape("a & b == c"), "(BitAnd a (Equals b c))");
+        assert_eq!(shape("a | b ^ c & d"), "(BitOr a (BitXor b (BitAnd c d)))");
+        assert_eq!(
+            shape("1 << 2 + 3 < 4"),
+            "(LesserThan (ShiftLeft 1 (Plus 2 3)) 4)"
+        );
+        assert_eq!(
+            shape("a === b && c !== d"),
+            "(And (StrictEquals a b) (StrictDifferent c d))"
+        );
+    }
+
+    #[test]
+    fn test_left_associativity() {
+        assert_eq!(shape("1 - 2 - 3"), "(Minus (Minus 1 2) 3)");
+        assert_eq!(shape("8 / 4 / 2"), "(Divide (Divide 8 4) 2)");
+        assert_eq!(shape("(1 - 2) - 3"), "(Minus (Minus 1 2) 3)");
+        assert_eq!(shape("1 - (2 - 3)"), "(Minus 1 (Minus 2 3))");
+    }
+
+    #[test]
+    fn test_prefix_and_postfix_bind_tightest() {
+        assert_eq!(shape("-a * b"), "(Multiply (Minus a) b)");
+        assert_eq!(
+            shape("!a && ~b == c"),
+            "(And (Not a) (Equals (BitNot b) c))"
+        );
+        assert_eq!(shape("- -a"), "(Minus (Minus a))");
+        assert_eq!(shape("-f(1) + 2"), "(Plus (Minus f(1)) 2)");
+        assert_eq!(shape("deref p + 1"), "(Plus (Deref p) 1)");
+        assert_eq!(shape("&a & b"), "(BitAnd (AddressOf a) b)");
+        assert_eq!(shape("addressof a"), "(AddressOf a)");
+    }
+
+    #[test]
+    fn test_update_statements_keep_their_form() {
+        let ast = parse("i += 2; i %= 3; i++; i--;");
+        let StatementKind::CompoundAssignment {
+            identifier,
+            operator,
+            ..
+        } = &ast.statements[0].kind
+        else {
+            panic!("expected a compound assignment");
+        };
+        assert_eq!(identifier, "i");
+        assert_eq!(*operator, Operator::PlusAssign);
+        assert!(matches!(
+            ast.statements[1].kind,
+            StatementKind::CompoundAssignment {
+                operator: Operator::ModuloAssign,
+                ..
+            }
+        ));
+        assert!(matches!(
+            ast.statements[2].kind,
+            StatementKind::IncrementDecrement {
+                operator: Operator::Increment,
+                ..
+            }
+        ));
+        assert!(matches!(
+            ast.statements[3].kind,
+            StatementKind::IncrementDecrement {
+                operator: Operator::Decrement,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_increment_is_not_an_expression() {
+        let src = "let x int = i++;";
+        let err = try_parse(src).unwrap_err();
+        assert_eq!(err.message, "'++' is a statement, not an expression");
+        assert_eq!((err.line, err.column), (1, 14));
+    }
+
+    #[test]
+    fn test_declaration_without_initializer() {
+        let ast = parse("let x int;\nlet y int = 1;");
+        let StatementKind::VariableDeclaration(var_decl) = &ast.statements[0].kind else {
+            panic!("expected a variable declaration");
+        };
+        assert!(var_decl.expression.is_none());
+        assert_eq!(var_decl.span, Span::new(1, 1, 1, 10));
+        assert_eq!(ast.statements.len(), 2);
+    }
+
+    #[test]
+    fn test_declaration_without_annotation() {
+        let ast = parse("let x = 1;");
+        let StatementKind::VariableDeclaration(var_decl) = &ast.statements[0].kind else {
+            panic!("expected a variable declaration");
+        };
+        assert!(matches!(
+            var_decl.pattern.kind,
+            PatternKind::Binding {
+                binding_type: None,
+                ..
+            }
+        ));
+        assert!(var_decl.expression.is_some());
+
+        let src = "let coords { x = 10, y = 20 };";
+        let err = try_parse(src).unwrap_err();
+        assert_eq!(
+            err.message,
+            "parse_var_decl: expected '=' before the initializer"
+        );
+        assert_eq!((err.line, err.column), (1, 12));
+        let ast = parse("let coords = { x = 10, y = 20 };");
+        let StatementKind::VariableDeclaration(var_decl) = &ast.statements[0].kind else {
+            panic!("expected a variable declaration");
+        };
+        assert!(matches!(
+            var_decl.expression.as_ref().unwrap().kind,
+            ExpressionKind::Struct(_)
+        ));
+    }
+
+    #[test]
+    fn test_destructuring_patterns() {
+        let ast = parse("let (a int, _, { x = b, y = (c, d) }) = v;");
+        let StatementKind::VariableDeclaration(var_decl) = &ast.statements[0].kind else {
+            panic!("expected a variable declaration");
+        };
+        let PatternKind::Tuple(elements) = &var_decl.pattern.kind else {
+            panic!("expected a tuple pattern");
+        };
+        assert_eq!(elements.len(), 3);
+        assert!(matches!(
+            &elements[0].kind,
+            PatternKind::Binding {
+                binding_type: Some(TypeIdentifier::Number),
+                ..
+            }
+        ));
+        assert!(matches!(elements[1].kind, PatternKind::Wildcard));
+        assert_eq!(var_decl.pattern.bindings(), ["a", "b", "c", "d"]);
+        assert_eq!(var_decl.pattern.span, Span::new(1, 5, 1, 38));
+    }
+
+    #[test]
+    fn test_product_types_and_literals() {
+        let ast = parse(
+            "let p (int * bool) = (1, true);
+             let s ('x int * 'y int) = { x = 1, y = 2 };",
+        );
+        let types: Vec<String> = ast
+            .statements
+            .iter()
+            .map(|statement| match &statement.kind {
+                StatementKind::VariableDeclaration(VariableDeclaration {
+                    pattern:
+                        Pattern {
+                            kind:
+                                PatternKind::Binding {
+                                    binding_type: Some(binding_type),
+                                    ..
+                                },
+                            ..
+                        },
+                    ..
+                }) => binding_type.to_string(),
+                _ => panic!("expected an annotated declaration"),
+            })
+            .collect();
+        assert_eq!(types, ["(int * bool)", "('x int * 'y int)"]);
+        assert_eq!(shape("(1, 2 + 3)"), "(1, (Plus 2 3))");
+        assert_eq!(shape("(1 + 2)"), "(Plus 1 2)");
+    }
+
+    #[test]
+    fn test_pointer_types() {
+        let ast = parse(
+            "let a &int = &x;
+             let b unique &Dog = &d;
+             let c shared &&int = &p;
+             let d weak &int = w;
+             let e unique = u;",
+        );
+        let types: Vec<TypeIdentifier> = ast
+            .statements
+            .iter()
+            .map(|statement| match &statement.kind {
+                StatementKind::VariableDeclaration(VariableDeclaration {
+                    pattern:
+                        Pattern {
+                            kind:
+                                PatternKind::Binding {
+                                    binding_type: Some(binding_type),
+                                    ..
+                                },
+                            ..
+                        },
+                    ..
+                }) => binding_type.clone(),
+                _ => panic!("expected an annotated declaration"),
+            })
+            .collect();
+        let rendered: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+        assert_eq!(
+            rendered,
+            ["&int", "unique &Dog", "shared &&int", "weak &int", "unique"]
+        );
+        // The qualifier belongs to the outer pointer of '&&'
+        assert_eq!(
+            types[2],
+            TypeIdentifier::SafePointer(
+                Ownership::Shared,
+                Box::new(TypeIdentifier::Pointer(Box::new(TypeIdentifier::Number)))
+            )
+        );
+        // Without a pointer after it a qualifier is just a type name
+        assert_eq!(types[4], TypeIdentifier::Named("unique".into()));
+    }
+
+    #[test]
+    fn test_store_through_pointer() {
+        let ast = parse("deref p = 42;");
+        let StatementKind::Store { target, expr } = &ast.statements[0].kind else {
+            panic!("expected a store");
+        };
+        assert_eq!(render(target), "(Deref p)");
+        assert_eq!(render(expr), "42");
+        assert_eq!(ast.statements[0].span, Span::new(1, 1, 1, 13));
+
+        let src = "-x = 1;";
+        let err = try_parse(src).unwrap_err();
+        assert_eq!(
+            err.message,
+            "parse_statement: only a variable or 'deref' of a pointer can be assigned to"
+        );
+    }
+
+    #[test]
+    fn test_for_loop() {
+        let ast = parse("for i < n { i++; }");
+        let StatementKind::For { condition, body } = &ast.statements[0].kind else {
+            panic!("expected a for loop");
+        };
+        assert_eq!(render(condition), "(LesserThan i n)");
+        assert!(matches!(
+            body[..],
+            [Statement {
+                kind: StatementKind::IncrementDecrement { .. },
+                ..
+            }]
+        ));
+        assert_eq!(ast.statements[0].span, Span::new(1, 1, 1, 19));
+    }
+
+    #[test]
+    fn test_unit_literal() {
+        let ast = parse("let u unit = ();");
+        let StatementKind::VariableDeclaration(var_decl) = &ast.statements[0].kind else {
+            panic!("expected a variable declaration");
+        };
+        assert!(matches!(
+            var_decl.expression.as_ref().unwrap().kind,
+            ExpressionKind::Literal(Literal::Unit)
+        ));
+        assert_eq!(shape("f()"), "f()");
+    }
+
+    #[test]
+    fn test_type_declaration() {
+        let ast = parse("type Age = int; type Point = ('x Age * 'y Age); let a Age = 1;");
+        let StatementKind::TypeDeclaration {
+            name, definition, ..
+        } = &ast.statements[1].kind
+        else {
+            panic!("expected a type declaration, got {:?}", ast.statements[1]);
+        };
+        assert_eq!(name, "Point");
+        assert_eq!(definition.to_string(), "('x Age * 'y Age)");
+        let StatementKind::VariableDeclaration(var_decl) = &ast.statements[2].kind else {
+            panic!("expected a variable declaration");
+        };
+        assert!(matches!(
+            &var_decl.pattern.kind,
+            PatternKind::Binding {
+                binding_type: Some(TypeIdentifier::Named(name)),
+                ..
+            } if name == "Age"
+        ));
+    }
+
+    #[test]
+    fn test_sum_types_and_variants() {
+        let ast = parse("type Option = 'None + 'Some int;");
+        let StatementKind::TypeDeclaration { definition, .. } = &ast.statements[0].kind else {
+            panic!("expected a type declaration");
+        };
+        assert_eq!(definition.to_string(), "'None + 'Some int");
+
+        // The payload is a single operand
+        assert_eq!(shape("'Some a + b"), "(Plus 'Some a b)");
+        assert_eq!(shape("'Some (a + b)"), "'Some (Plus a b)");
+        assert_eq!(shape("'None"), "'None");
+        assert_eq!(shape("f(x)?.get(1).size"), "f(x)?.get(1).size");
+
+        // A struct literal payload may name its type
+        let ast = parse("let r R = 'Err E { code = 1 };");
+        let StatementKind::VariableDeclaration(var_decl) = &ast.statements[0].kind else {
+            panic!("expected a variable declaration");
+        };
+        let Some(ExpressionKind::Variant {
+            label,
+            payload: Some(payload),
+            payload_type,
+        }) = var_decl.expression.as_ref().map(|expr| &expr.kind)
+        else {
+            panic!("expected a variant with a payload");
+        };
+        assert_eq!(label, "Err");
+        assert_eq!(payload_type.as_deref(), Some("E"));
+        assert!(matches!(payload.kind, ExpressionKind::Struct(_)));
+    }
+
+    #[test]
+    fn test_contract_implementations() {
+        let ast = parse(
+            "type NotFound = (
+                'path string
+                ;
+                Error {
+                    function get_error_id(self) int { return 1; }
+                    function get_error_message(self) string { return \"not found\"; }
+                }
+            )",
+        );
+        let StatementKind::TypeDeclaration {
+            definition,
+            contracts,
+            ..
+        } = &ast.statements[0].kind
+        else {
+            panic!("expected a type declaration");
+        };
+        assert_eq!(definition.to_string(), "('path string)");
+        assert_eq!(contracts.len(), 1);
+        assert_eq!(contracts[0].contract, "Error");
+        let methods: Vec<&str> = contracts[0]
+            .methods
+            .iter()
+            .map(|method| method.signature.name.as_str())
+            .collect();
+        assert_eq!(methods, ["get_error_id", "get_error_message"]);
+        // 'self' has the type that signs the contract
+        assert_eq!(
+            contracts[0].methods[0].signature.parameters[0].parameter_type,
+            Some(TypeIdentifier::Named("NotFound".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_match() {
+        let ast = parse(
+            "match r {
+            | 'Ok v -> x = v
+            | 1 -> x = 1
+            | 'Err _ -> {
+                return;
+            }
+            | _ -> return
+            }",
+        );
+        let StatementKind::Match { scrutinee, arms } = &ast.statements[0].kind else {
+            panic!("expected a match, got {:?}", ast.statements[0]);
+        };
+        assert_eq!(render(scrutinee), "r");
+        assert_eq!(arms.len(), 4);
+        assert!(matches!(
+            &arms[0].pattern.kind,
+            PatternKind::Variant { label, payload: Some(_) } if label == "Ok"
+        ));
+        // A '|' outside brackets ends a single statement arm
+        let StatementKind::Assignment { expr, .. } = &arms[0].body[0].kind else {
+            panic!("expected an assignment");
+        };
+        assert_eq!(render(expr), "v");
+        assert!(matches!(
+            arms[1].pattern.kind,
+            PatternKind::Literal(Literal::Integer(1))
+        ));
+        assert!(matches!(arms[3].body[0].kind, StatementKind::Return(None)));
+
+        let ast = parse("match r { | 'Ok v -> x = (v | 1) | _ -> x = 2 }");
+        let StatementKind::Match { arms, .. } = &ast.statements[0].kind else {
+            panic!("expected a match");
+        };
+        let StatementKind::Assignment { expr, .. } = &arms[0].body[0].kind else {
+            panic!("expected an assignment");
+        };
+        assert_eq!(render(expr), "(BitOr v 1)");
+    }
+
+    #[test]
+    fn test_panic() {
+        let ast = parse(
+            "function f(b bool) int {
+                if b {
+                    panic
+                }
+                match b {
+                | true -> panic \"yes\"
+                | false -> panic
+                }
+            }",
+        );
+        let StatementKind::FunctionDeclaration(function) = &ast.statements[0].kind else {
+            panic!("expected a function");
+        };
+        let FunctionBody::Statements(body) = &function.body else {
+            panic!("expected a body");
+        };
+        let StatementKind::If { then_branch, .. } = &body[0].kind else {
+            panic!("expected an if");
+        };
+        assert!(matches!(
+            &then_branch[0].kind,
+            StatementKind::Expression(Expression {
+                kind: ExpressionKind::Panic(None),
+                ..
+            })
+        ));
+        let StatementKind::Match { arms, .. } = &body[1].kind else {
+            panic!("expected a match");
+        };
+        let messages: Vec<Option<String>> = arms
+            .iter()
+            .map(|arm| match &arm.body[0].kind {
+                StatementKind::Expression(Expression {
+                    kind: ExpressionKind::Panic(message),
+                    ..
+                }) => message
+                    .as_ref()
+                    .map(|message| format!("{:?}", message.kind)),
+                other => panic!("expected a panic, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            messages,
+            [Some("Literal(String(\"yes\"))".to_string()), None]
+        );
+    }
+
+    #[test]
+    fn test_casts() {
+        assert_eq!(
+            shape("f.cast_int() + cast_int(2)"),
+            "(Plus cast_int(f) cast_int(2))"
+        );
+        // The dot after an integer starts a cast, not a fraction
+        assert_eq!(
+            shape("1.cast_float().cast_int()"),
+            "cast_int(cast_float(1))"
+        );
+        assert_eq!(shape("-x.cast_int()"), "(Minus cast_int(x))");
+        // Other functions are still plain calls
+        assert_eq!(shape("cast_spell(1)"), "cast_spell(1)");
+
+        for (src, message) in [
+            (
+                "let x int = y.cast_int(1);",
+                "parse_atom: a cast after '.' takes no arguments",
+            ),
+            (
+                "let x int = cast_int(1, 2);",
+                "parse_atom: a cast takes exactly one value",
+            ),
+        ] {
+            let err = try_parse(src).unwrap_err();
+            assert_eq!(err.message, message);
+        }
+    }
+}
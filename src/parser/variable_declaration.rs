@@ -1,19 +1,22 @@
 use crate::parser::expression::Expression;
-use crate::token::TypeIdentifier;
+use crate::parser::pattern::Pattern;
+use crate::token::Span;
 
 #[derive(Debug, Clone)]
 pub struct VariableDeclaration {
-    pub identifier: String,
-    pub variable_type: TypeIdentifier,
-    pub expression: Expression,
+    // A single binding for `let x int`, or a tuple or labeled pattern to destructure the initializer
+    pub pattern: Pattern,
+    // None for `let x int;`, which must be assigned before it is read
+    pub expression: Option<Expression>,
+    pub span: Span,
 }
 
 impl VariableDeclaration {
-    pub fn new(identifier: String, variable_type: TypeIdentifier, expression: Expression) -> Self {
+    pub fn new(pattern: Pattern, expression: Option<Expression>, span: Span) -> Self {
         Self {
-            identifier,
-            variable_type: variable_type,
+            pattern,
             expression,
+            span,
         }
     }
 }
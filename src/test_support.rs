@@ -0,0 +1,43 @@
+//! The front end pipeline the test modules run their sources through.
+
+use crate::{
+    hints::HintRegistry,
+    hir::Hir,
+    lexer::Lexer,
+    parser::{Ast, Parser, parser::ParseError},
+    type_checker::TypeChecker,
+};
+
+/// Lex and parse `src`, keeping the parse error.
+pub(crate) fn try_parse(src: &str) -> Result<Ast, ParseError> {
+    let lexer = Lexer::new(src);
+    let mut parser = Parser::new(
+        lexer.collect::<Vec<_>>().into_iter(),
+        "test".into(),
+        src.into(),
+    );
+    parser.parse_program()
+}
+
+/// Lex and parse `src`, which the test expects to be well formed.
+pub(crate) fn parse(src: &str) -> Ast {
+    try_parse(src).expect("test source should parse")
+}
+
+/// Parse `src` and move the promises onto their parameters, as the driver
+/// does before validating hints and type checking.
+pub(crate) fn parse_with_promises(src: &str) -> Ast {
+    let mut ast = parse(src);
+    HintRegistry::new().bind_promises(&mut ast);
+    ast
+}
+
+/// Run `src` through the whole front end, which the test expects to type
+/// check without diagnostics, and lower it.
+pub(crate) fn lower(src: &str) -> Hir {
+    let ast = parse_with_promises(src);
+    let mut type_checker = TypeChecker::new(&ast);
+    let diagnostics = type_checker.check_ast();
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    type_checker.lower()
+}
@@ -6,4 +6,7 @@ pub enum Keyword {
     For,
     Return,
     Function,
+    Type,
+    Match,
+    Panic,
 }
@@ -1,7 +1,28 @@
+use std::fmt;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     Integer(u32),
     Float(f32),
     Boolean(bool),
     Character(char),
+    String(String),
+    // (), built by the parser from its two tokens
+    Unit,
+    // The pointer that points nowhere
+    Null,
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Integer(value) => write!(f, "{}", value),
+            Literal::Float(value) => write!(f, "{}", value),
+            Literal::Boolean(value) => write!(f, "{}", value),
+            Literal::Character(value) => write!(f, "'{}'", value),
+            Literal::String(value) => write!(f, "{:?}", value),
+            Literal::Unit => write!(f, "()"),
+            Literal::Null => write!(f, "null"),
+        }
+    }
 }
@@ -2,6 +2,8 @@ pub mod keyword;
 pub mod literal;
 pub mod operator;
 pub mod punctuation;
+pub mod span;
+#[allow(clippy::module_inception)]
 pub mod token;
 pub mod type_identifier;
 
@@ -9,5 +11,6 @@ pub use keyword::Keyword;
 pub use literal::Literal;
 pub use operator::Operator;
 pub use punctuation::Punctuation;
+pub use span::Span;
 pub use token::{Token, TokenKind};
-pub use type_identifier::TypeIdentifier;
+pub use type_identifier::{Ownership, TypeDefinitions, TypeIdentifier};
@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Operator {
     // Arithmetic
@@ -5,19 +7,91 @@ pub enum Operator {
     Minus,
     Multiply,
     Divide,
+    Modulo,
+    // Bitwise
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    ShiftLeft,
+    ShiftRight,
     // Boolean opeartor
     Equals,
     Different,
+    StrictEquals,
+    StrictDifferent,
     GreaterThan,
     LesserThan,
     GreaterEqual,
     LesserEqual,
     // Assignments
     Assign,
+    PlusAssign,
+    MinusAssign,
+    MultiplyAssign,
+    DivideAssign,
+    ModuloAssign,
     Increment,
     Decrement,
     // Logical operators
     And,
     Or,
     Not,
+    // Pointers
+    Deref,
+    AddressOf,
+}
+
+impl Operator {
+    /// The binary operation a compound assignment applies: `+=` adds.
+    pub fn compound_operation(self) -> Option<Operator> {
+        match self {
+            Operator::PlusAssign => Some(Operator::Plus),
+            Operator::MinusAssign => Some(Operator::Minus),
+            Operator::MultiplyAssign => Some(Operator::Multiply),
+            Operator::DivideAssign => Some(Operator::Divide),
+            Operator::ModuloAssign => Some(Operator::Modulo),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operator::Plus => "+",
+            Operator::Minus => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Modulo => "%",
+            Operator::BitAnd => "&",
+            Operator::BitOr => "|",
+            Operator::BitXor => "^",
+            Operator::BitNot => "~",
+            Operator::ShiftLeft => "<<",
+            Operator::ShiftRight => ">>",
+            Operator::Equals => "==",
+            Operator::Different => "!=",
+            Operator::StrictEquals => "===",
+            Operator::StrictDifferent => "!==",
+            Operator::GreaterThan => ">",
+            Operator::LesserThan => "<",
+            Operator::GreaterEqual => ">=",
+            Operator::LesserEqual => "<=",
+            Operator::Assign => "=",
+            Operator::PlusAssign => "+=",
+            Operator::MinusAssign => "-=",
+            Operator::MultiplyAssign => "*=",
+            Operator::DivideAssign => "/=",
+            Operator::ModuloAssign => "%=",
+            Operator::Increment => "++",
+            Operator::Decrement => "--",
+            Operator::And => "&&",
+            Operator::Or => "||",
+            Operator::Not => "!",
+            Operator::Deref => "deref",
+            Operator::AddressOf => "addressof",
+        };
+        write!(f, "{}", symbol)
+    }
 }
@@ -7,4 +7,8 @@ pub enum Punctuation {
     Semicolon,
     Comma,
     Colon,
+    At,
+    Arrow,
+    Dot,
+    Question,
 }
@@ -2,6 +2,7 @@ use crate::token::keyword::Keyword;
 use crate::token::literal::Literal;
 use crate::token::operator::Operator;
 use crate::token::punctuation::Punctuation;
+use crate::token::span::Span;
 use crate::token::type_identifier::TypeIdentifier;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -9,6 +10,8 @@ pub enum TokenKind {
     // The identifier token contains the name of the identifier as a string
     Identifier(String),
     TypeIdentifier(TypeIdentifier),
+    // A label such as 'name, naming a field of a labeled product
+    Label(String),
     Literal(Literal),
     Keyword(Keyword),
     Operator(Operator),
@@ -18,12 +21,11 @@ pub enum TokenKind {
 #[derive(Debug, Clone)]
 pub struct Token {
     pub kind: TokenKind,
-    pub line: usize,
-    pub column: usize,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(kind: TokenKind, line: usize, column: usize) -> Self {
-        return Self { kind, line, column };
+    pub fn new(kind: TokenKind, span: Span) -> Self {
+        Self { kind, span }
     }
 }
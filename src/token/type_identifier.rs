@@ -1,8 +1,317 @@
+use std::{collections::HashMap, fmt};
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+/// The definitions of the named types in scope, by name.
+pub type TypeDefinitions = HashMap<String, TypeIdentifier>;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum TypeIdentifier {
     Number,
+    Float,
     Boolean,
     Char,
+    String,
+    // The type of (), what a function without a return type returns
+    Unit,
+    // The type of an expression that never produces a value, such as panic.
+    // It fits wherever a value is expected.
+    Never,
     UserDefinedType,
-}
\ No newline at end of file
+    // A name given to a type by a type declaration: type Age = int;
+    Named(String),
+    // &<type>, a raw pointer
+    Pointer(Box<TypeIdentifier>),
+    // unique &<type>, shared &<type> or weak &<type>
+    SafePointer(Ownership, Box<TypeIdentifier>),
+    // The type of null, which fits every pointer type
+    Null,
+    // (parameters) -> return_type
+    Function {
+        parameters: Vec<TypeIdentifier>,
+        return_type: Box<TypeIdentifier>,
+    },
+    // (<type> * <type> * ...)
+    Tuple(Vec<TypeIdentifier>),
+    // ('<label> <type> * '<label> <type> * ...)
+    Labeled(Vec<(String, TypeIdentifier)>),
+    // '<label> [<type>] + '<label> [<type>] + ..., only as the definition of a named type
+    Sum(Vec<(String, Option<TypeIdentifier>)>),
+}
+
+/// How a safe pointer holds the value it points to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ownership {
+    // The only owner, ownership moves on assignment
+    Unique,
+    // One of the owners counted by a reference count
+    Shared,
+    // Not an owner, it does not keep the value alive
+    Weak,
+}
+
+impl Ownership {
+    /// The ownership spelled `name` before a pointer type, if any.
+    pub fn from_qualifier(name: &str) -> Option<Ownership> {
+        match name {
+            "unique" => Some(Ownership::Unique),
+            "shared" => Some(Ownership::Shared),
+            "weak" => Some(Ownership::Weak),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Ownership {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ownership::Unique => write!(f, "unique"),
+            Ownership::Shared => write!(f, "shared"),
+            Ownership::Weak => write!(f, "weak"),
+        }
+    }
+}
+
+impl TypeIdentifier {
+    /// The primitive type spelled `name` in source, if any.
+    pub fn primitive(name: &str) -> Option<TypeIdentifier> {
+        match name {
+            "int" => Some(TypeIdentifier::Number),
+            "float" => Some(TypeIdentifier::Float),
+            "bool" => Some(TypeIdentifier::Boolean),
+            "char" => Some(TypeIdentifier::Char),
+            "string" => Some(TypeIdentifier::String),
+            "unit" => Some(TypeIdentifier::Unit),
+            _ => None,
+        }
+    }
+
+    /// The type a named type stands for, following names until a type that is
+    /// not a name. Other types, and names without a definition, are their own
+    /// underlying type.
+    pub fn underlying<'a>(&'a self, definitions: &'a TypeDefinitions) -> &'a TypeIdentifier {
+        let mut current = self;
+        while let TypeIdentifier::Named(name) = current
+            && let Some(definition) = definitions.get(name)
+        {
+            current = definition;
+        }
+        current
+    }
+
+    /// Type identity, what `===` requires: the same structure with the same
+    /// names at every level, so `Age` is identical to neither `int` nor `Year`.
+    pub fn is_identical(&self, other: &TypeIdentifier) -> bool {
+        self == other
+    }
+
+    /// Structural equivalence, what `==` and assignment require: the same
+    /// structure once every named type is replaced by its definition.
+    pub fn is_equivalent(&self, other: &TypeIdentifier, definitions: &TypeDefinitions) -> bool {
+        fn equivalent<'a>(
+            left: &'a TypeIdentifier,
+            right: &'a TypeIdentifier,
+            definitions: &'a TypeDefinitions,
+            assumed: &mut Vec<(&'a TypeIdentifier, &'a TypeIdentifier)>,
+        ) -> bool {
+            // A named type may contain itself through a pointer. Meeting the
+            // same pair again adds nothing: any difference between them shows
+            // up in the comparison already under way.
+            if matches!(left, TypeIdentifier::Named(_)) || matches!(right, TypeIdentifier::Named(_))
+            {
+                if assumed.contains(&(left, right)) {
+                    return true;
+                }
+                assumed.push((left, right));
+            }
+            let mut all_equivalent = |left: &'a [TypeIdentifier], right: &'a [TypeIdentifier]| {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right)
+                        .all(|(l, r)| equivalent(l, r, definitions, assumed))
+            };
+            match (left.underlying(definitions), right.underlying(definitions)) {
+                (TypeIdentifier::Pointer(left), TypeIdentifier::Pointer(right)) => {
+                    equivalent(left, right, definitions, assumed)
+                }
+                (
+                    TypeIdentifier::SafePointer(left_ownership, left),
+                    TypeIdentifier::SafePointer(right_ownership, right),
+                ) => {
+                    left_ownership == right_ownership
+                        && equivalent(left, right, definitions, assumed)
+                }
+                (
+                    TypeIdentifier::Function {
+                        parameters: left_parameters,
+                        return_type: left_return,
+                    },
+                    TypeIdentifier::Function {
+                        parameters: right_parameters,
+                        return_type: right_return,
+                    },
+                ) => {
+                    all_equivalent(left_parameters, right_parameters)
+                        && equivalent(left_return, right_return, definitions, assumed)
+                }
+                (TypeIdentifier::Tuple(left), TypeIdentifier::Tuple(right)) => {
+                    all_equivalent(left, right)
+                }
+                (TypeIdentifier::Labeled(left), TypeIdentifier::Labeled(right)) => {
+                    left.len() == right.len()
+                        && left.iter().zip(right).all(|((l, l_type), (r, r_type))| {
+                            l == r && equivalent(l_type, r_type, definitions, assumed)
+                        })
+                }
+                (TypeIdentifier::Sum(left), TypeIdentifier::Sum(right)) => {
+                    left.len() == right.len()
+                        && left
+                            .iter()
+                            .zip(right)
+                            .all(|((l, l_payload), (r, r_payload))| {
+                                l == r
+                                    && match (l_payload, r_payload) {
+                                        (Some(l_payload), Some(r_payload)) => {
+                                            equivalent(l_payload, r_payload, definitions, assumed)
+                                        }
+                                        (l_payload, r_payload) => l_payload == r_payload,
+                                    }
+                            })
+                }
+                (left, right) => left == right,
+            }
+        }
+        equivalent(self, other, definitions, &mut Vec::new())
+    }
+
+    /// Whether a value of this type holds a unique pointer, itself or in one of
+    /// its components. Such a value moves on assignment instead of being copied.
+    pub fn moves(&self, definitions: &TypeDefinitions) -> bool {
+        fn holds_unique<'a>(
+            current: &'a TypeIdentifier,
+            definitions: &'a TypeDefinitions,
+            seen: &mut Vec<&'a String>,
+        ) -> bool {
+            match current {
+                TypeIdentifier::SafePointer(ownership, _) => *ownership == Ownership::Unique,
+                TypeIdentifier::Named(name) => {
+                    if seen.contains(&name) {
+                        return false;
+                    }
+                    seen.push(name);
+                    definitions
+                        .get(name)
+                        .is_some_and(|definition| holds_unique(definition, definitions, seen))
+                }
+                TypeIdentifier::Tuple(elements) => elements
+                    .iter()
+                    .any(|element| holds_unique(element, definitions, seen)),
+                TypeIdentifier::Labeled(fields) => fields
+                    .iter()
+                    .any(|(_, field_type)| holds_unique(field_type, definitions, seen)),
+                TypeIdentifier::Sum(variants) => variants.iter().any(|(_, payload)| {
+                    payload
+                        .as_ref()
+                        .is_some_and(|payload| holds_unique(payload, definitions, seen))
+                }),
+                _ => false,
+            }
+        }
+        holds_unique(self, definitions, &mut Vec::new())
+    }
+
+    /// The names of the named types this type mentions, in source order.
+    pub fn names(&self) -> Vec<&String> {
+        self.mentioned(true)
+    }
+
+    /// The names of the named types a value of this type holds a value of
+    /// itself. Pointers and functions only refer to the values of the types
+    /// they mention, so their names are left out.
+    pub fn contained_names(&self) -> Vec<&String> {
+        self.mentioned(false)
+    }
+
+    fn mentioned<'a>(&'a self, through_references: bool) -> Vec<&'a String> {
+        let mentioned = |component: &'a TypeIdentifier| component.mentioned(through_references);
+        match self {
+            TypeIdentifier::Named(name) => vec![name],
+            TypeIdentifier::Pointer(_)
+            | TypeIdentifier::SafePointer(..)
+            | TypeIdentifier::Function { .. }
+                if !through_references =>
+            {
+                Vec::new()
+            }
+            TypeIdentifier::Pointer(pointee) | TypeIdentifier::SafePointer(_, pointee) => {
+                mentioned(pointee)
+            }
+            TypeIdentifier::Function {
+                parameters,
+                return_type,
+            } => parameters
+                .iter()
+                .chain(std::iter::once(return_type.as_ref()))
+                .flat_map(mentioned)
+                .collect(),
+            TypeIdentifier::Tuple(elements) => elements.iter().flat_map(mentioned).collect(),
+            TypeIdentifier::Labeled(fields) => fields
+                .iter()
+                .flat_map(|(_, field_type)| mentioned(field_type))
+                .collect(),
+            TypeIdentifier::Sum(variants) => variants
+                .iter()
+                .flat_map(|(_, payload)| payload.iter().flat_map(mentioned))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for TypeIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeIdentifier::Number => write!(f, "int"),
+            TypeIdentifier::Float => write!(f, "float"),
+            TypeIdentifier::Boolean => write!(f, "bool"),
+            TypeIdentifier::Char => write!(f, "char"),
+            TypeIdentifier::String => write!(f, "string"),
+            TypeIdentifier::Unit => write!(f, "unit"),
+            TypeIdentifier::Never => write!(f, "never"),
+            TypeIdentifier::Null => write!(f, "null"),
+            TypeIdentifier::UserDefinedType => write!(f, "<user type>"),
+            TypeIdentifier::Named(name) => write!(f, "{}", name),
+            TypeIdentifier::Pointer(pointee) => write!(f, "&{}", pointee),
+            TypeIdentifier::SafePointer(ownership, pointee) => {
+                write!(f, "{} &{}", ownership, pointee)
+            }
+            TypeIdentifier::Function {
+                parameters,
+                return_type,
+            } => {
+                let parameters: Vec<String> = parameters.iter().map(|p| p.to_string()).collect();
+                write!(f, "({}) -> {}", parameters.join(", "), return_type)
+            }
+            TypeIdentifier::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                write!(f, "({})", elements.join(" * "))
+            }
+            TypeIdentifier::Labeled(fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(label, field_type)| format!("'{} {}", label, field_type))
+                    .collect();
+                write!(f, "({})", fields.join(" * "))
+            }
+            TypeIdentifier::Sum(variants) => {
+                let variants: Vec<String> = variants
+                    .iter()
+                    .map(|(label, payload)| match payload {
+                        Some(payload) => format!("'{} {}", label, payload),
+                        None => format!("'{}", label),
+                    })
+                    .collect();
+                write!(f, "{}", variants.join(" + "))
+            }
+        }
+    }
+}
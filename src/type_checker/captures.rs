@@ -0,0 +1,165 @@
+use std::collections::HashSet;
+
+use crate::parser::{Expression, ExpressionKind, Lambda, Statement, StatementKind};
+
+/// Names a lambda reads that it does not bind itself, in order of first use.
+pub(super) fn free_variables(lambda: &Lambda) -> Vec<String> {
+    let mut collector = Collector {
+        bound: vec![
+            lambda
+                .parameters
+                .iter()
+                .map(|p| p.parameter_name.clone())
+                .collect(),
+        ],
+        free: Vec::new(),
+    };
+    collector.statements(&lambda.body);
+    collector.free
+}
+
+struct Collector {
+    // The names bound by each block enclosing the statement being visited,
+    // the parameters first
+    bound: Vec<HashSet<String>>,
+    free: Vec<String>,
+}
+
+impl Collector {
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    /// Visit statements in a block of their own, whose names vanish at its end.
+    fn block(&mut self, bound: HashSet<String>, statements: &[Statement]) {
+        self.bound.push(bound);
+        self.statements(statements);
+        self.bound.pop();
+    }
+
+    fn bind(&mut self, name: &str) {
+        if let Some(block) = self.bound.last_mut() {
+            block.insert(name.to_string());
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::VariableDeclaration(var_decl) => {
+                if let Some(expression) = &var_decl.expression {
+                    self.expression(expression);
+                }
+                for name in var_decl.pattern.bindings() {
+                    self.bind(name);
+                }
+            }
+            StatementKind::Assignment { identifier, expr }
+            | StatementKind::CompoundAssignment {
+                identifier, expr, ..
+            } => {
+                self.expression(expr);
+                self.name(identifier);
+            }
+            StatementKind::Store { target, expr } => {
+                self.expression(expr);
+                self.expression(target);
+            }
+            StatementKind::IncrementDecrement { identifier, .. } => self.name(identifier),
+            StatementKind::TypeDeclaration { .. } => {}
+            StatementKind::Expression(expr) => self.expression(expr),
+            StatementKind::FunctionDeclaration(function) => {
+                // Named functions cannot capture, only their name is bound
+                self.bind(&function.signature.name);
+            }
+            StatementKind::Return(expr) => {
+                if let Some(expr) = expr {
+                    self.expression(expr);
+                }
+            }
+            StatementKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expression(condition);
+                self.block(HashSet::new(), then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.block(HashSet::new(), else_branch);
+                }
+            }
+            StatementKind::For { condition, body } => {
+                self.expression(condition);
+                self.block(HashSet::new(), body);
+            }
+            StatementKind::Hinted { statement, .. } => self.statement(statement),
+            StatementKind::Match { scrutinee, arms } => {
+                self.expression(scrutinee);
+                for arm in arms {
+                    let bound = arm.pattern.bindings().into_iter().cloned().collect();
+                    self.block(bound, &arm.body);
+                }
+            }
+        }
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        match &expr.kind {
+            ExpressionKind::Binary { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            ExpressionKind::Unary { expression, .. }
+            | ExpressionKind::Grouping(expression)
+            | ExpressionKind::Hinted { expression, .. }
+            | ExpressionKind::Cast { expression, .. }
+            | ExpressionKind::Propagate(expression)
+            | ExpressionKind::Field {
+                receiver: expression,
+                ..
+            } => self.expression(expression),
+            ExpressionKind::Variant { payload, .. } | ExpressionKind::Panic(payload) => {
+                if let Some(payload) = payload {
+                    self.expression(payload);
+                }
+            }
+            ExpressionKind::MethodCall { receiver, args, .. } => {
+                self.expression(receiver);
+                for arg in args {
+                    self.expression(arg);
+                }
+            }
+            ExpressionKind::Literal(_) => {}
+            ExpressionKind::Identifier(name) => self.name(name),
+            ExpressionKind::Call { callee, args } => {
+                self.expression(callee);
+                for arg in args {
+                    self.expression(arg);
+                }
+            }
+            ExpressionKind::Tuple(elements) => {
+                for element in elements {
+                    self.expression(element);
+                }
+            }
+            ExpressionKind::Struct(fields) => {
+                for (_, value) in fields {
+                    self.expression(value);
+                }
+            }
+            ExpressionKind::Lambda(inner) => {
+                // Whatever a nested lambda captures, this one must capture too
+                for name in free_variables(inner) {
+                    self.name(&name);
+                }
+            }
+        }
+    }
+
+    fn name(&mut self, name: &String) {
+        if !self.bound.iter().any(|block| block.contains(name)) && !self.free.contains(name) {
+            self.free.push(name.clone());
+        }
+    }
+}
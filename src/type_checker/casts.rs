@@ -0,0 +1,87 @@
+use crate::{
+    hir::hir::{CastKind, RangeCheck},
+    token::{TypeDefinitions, TypeIdentifier},
+};
+
+/// The conversions a safe cast performs besides the identity, from and to
+/// underlying types. Those that may meet a value the target type cannot
+/// hold check it at run time.
+const CONVERSIONS: &[(TypeIdentifier, TypeIdentifier, Option<RangeCheck>)] = &[
+    (TypeIdentifier::Number, TypeIdentifier::Float, None),
+    (
+        TypeIdentifier::Float,
+        TypeIdentifier::Number,
+        Some(RangeCheck::FloatToInt),
+    ),
+    (TypeIdentifier::Char, TypeIdentifier::Number, None),
+    (
+        TypeIdentifier::Number,
+        TypeIdentifier::Char,
+        Some(RangeCheck::IntToChar),
+    ),
+    (TypeIdentifier::Boolean, TypeIdentifier::Number, None),
+    (
+        TypeIdentifier::Number,
+        TypeIdentifier::Boolean,
+        Some(RangeCheck::IntToBool),
+    ),
+];
+
+/// How a safe cast converts a `from` into a `to`, if it can.
+pub(super) fn safe_cast(
+    from: &TypeIdentifier,
+    to: &TypeIdentifier,
+    definitions: &TypeDefinitions,
+) -> Option<CastKind> {
+    if from.is_equivalent(to, definitions) {
+        return Some(CastKind::Safe(None));
+    }
+    let (from, to) = (from.underlying(definitions), to.underlying(definitions));
+    CONVERSIONS
+        .iter()
+        .find(|(source, target, _)| source == from && target == to)
+        .map(|(_, _, check)| CastKind::Safe(*check))
+}
+
+/// The type of a safe cast to `target` that checks the value first: the
+/// converted value, or an `Error` for a value that does not fit.
+pub(super) fn checked_result(target: &TypeIdentifier) -> TypeIdentifier {
+    TypeIdentifier::Sum(vec![
+        ("Ok".to_string(), Some(target.clone())),
+        (
+            "Err".to_string(),
+            Some(TypeIdentifier::Named("Error".to_string())),
+        ),
+    ])
+}
+
+/// The size in bytes of a value, as raw casts compare them. Products are
+/// the sum of their components, with no padding, and a sum is a 4 byte tag
+/// followed by room for its largest payload. Strings, whose length varies,
+/// and types not known yet have no size.
+pub(super) fn size_of(value_type: &TypeIdentifier, definitions: &TypeDefinitions) -> Option<usize> {
+    let total = |types: Vec<&TypeIdentifier>| {
+        types
+            .into_iter()
+            .map(|component| size_of(component, definitions))
+            .sum()
+    };
+    match value_type.underlying(definitions) {
+        TypeIdentifier::Number | TypeIdentifier::Float | TypeIdentifier::Char => Some(4),
+        TypeIdentifier::Boolean => Some(1),
+        TypeIdentifier::Unit | TypeIdentifier::Never => Some(0),
+        TypeIdentifier::Pointer(_)
+        | TypeIdentifier::SafePointer(..)
+        | TypeIdentifier::Null
+        | TypeIdentifier::Function { .. } => Some(8),
+        TypeIdentifier::Tuple(elements) => total(elements.iter().collect()),
+        TypeIdentifier::Labeled(fields) => total(fields.iter().map(|(_, field)| field).collect()),
+        TypeIdentifier::Sum(variants) => variants
+            .iter()
+            .filter_map(|(_, payload)| payload.as_ref())
+            .map(|payload| size_of(payload, definitions))
+            .try_fold(0, |largest, size| Some(largest.max(size?)))
+            .map(|largest| 4 + largest),
+        TypeIdentifier::String | TypeIdentifier::Named(_) | TypeIdentifier::UserDefinedType => None,
+    }
+}
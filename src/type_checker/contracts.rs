@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use crate::token::TypeIdentifier;
+
+/// A contract: the methods a type must define to sign it, by name, with
+/// their types leaving `self` out.
+pub(super) type Contract = Vec<(String, TypeIdentifier)>;
+
+/// The contracts the language provides.
+pub(super) fn builtin_contracts() -> HashMap<String, Contract> {
+    let method = |name: &str, return_type| {
+        (
+            name.to_string(),
+            TypeIdentifier::Function {
+                parameters: Vec::new(),
+                return_type: Box::new(return_type),
+            },
+        )
+    };
+    let mut contracts = HashMap::new();
+
+    // contract Error {
+    //     function get_error_id(self) int;
+    //     function get_error_message(self) string;
+    // }
+    contracts.insert(
+        "Error".to_string(),
+        vec![
+            method("get_error_id", TypeIdentifier::Number),
+            method("get_error_message", TypeIdentifier::String),
+        ],
+    );
+
+    contracts
+}
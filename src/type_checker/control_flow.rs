@@ -0,0 +1,161 @@
+use crate::parser::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::token::Span;
+
+/// Returns true if running `statements` always ends in a return statement or
+/// a panic, so nothing after them runs.
+pub(super) fn always_returns(statements: &[Statement]) -> bool {
+    statements.iter().any(statement_always_returns)
+}
+
+fn statement_always_returns(statement: &Statement) -> bool {
+    match &statement.kind {
+        StatementKind::Return(_) => true,
+        StatementKind::Expression(expr) => panics(expr),
+        StatementKind::If {
+            then_branch,
+            else_branch: Some(else_branch),
+            ..
+        } => always_returns(then_branch) && always_returns(else_branch),
+        // Matches are exhaustive, so one arm always runs
+        StatementKind::Match { arms, .. } => {
+            !arms.is_empty() && arms.iter().all(|arm| always_returns(&arm.body))
+        }
+        StatementKind::Hinted { statement, .. } => statement_always_returns(statement),
+        _ => false,
+    }
+}
+
+/// One path on which running `statements` reaches their end without
+/// returning, as the branch taken at each `if` and `match` on the way. None when every
+/// path returns.
+pub(super) fn fall_through_path(statements: &[Statement]) -> Option<Vec<String>> {
+    let mut steps = Vec::new();
+    for statement in statements {
+        steps.extend(statement_fall_through_path(statement)?);
+    }
+    Some(steps)
+}
+
+fn statement_fall_through_path(statement: &Statement) -> Option<Vec<String>> {
+    match &statement.kind {
+        StatementKind::Return(_) => None,
+        StatementKind::Expression(expr) if panics(expr) => None,
+        StatementKind::If {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            let span = statement.span;
+            if let Some(path) = fall_through_path(then_branch) {
+                let mut steps = vec![format!("the condition of the 'if' at {} is true", span)];
+                steps.extend(path);
+                return Some(steps);
+            }
+            match else_branch {
+                Some(else_branch) => {
+                    let path = fall_through_path(else_branch)?;
+                    let mut steps = vec![format!("the 'if' at {} takes its else branch", span)];
+                    steps.extend(path);
+                    Some(steps)
+                }
+                None => Some(vec![format!(
+                    "the condition of the 'if' at {} is false",
+                    span
+                )]),
+            }
+        }
+        StatementKind::Match { arms, .. } => arms.iter().find_map(|arm| {
+            let path = fall_through_path(&arm.body)?;
+            let mut steps = vec![format!(
+                "the 'match' at {} takes the arm at {}",
+                statement.span, arm.pattern.span
+            )];
+            steps.extend(path);
+            Some(steps)
+        }),
+        StatementKind::Hinted { statement, .. } => statement_fall_through_path(statement),
+        _ => Some(Vec::new()),
+    }
+}
+
+/// Whether evaluating `expr` always panics.
+fn panics(expr: &Expression) -> bool {
+    match &expr.kind {
+        ExpressionKind::Panic(_) => true,
+        ExpressionKind::Grouping(expression) | ExpressionKind::Hinted { expression, .. } => {
+            panics(expression)
+        }
+        _ => false,
+    }
+}
+
+/// The first unreachable statement of every block in `statements`, nested
+/// `if` branches and `match` arms included, each with the statement that
+/// always returns or panics before it. Bodies of nested functions are left to
+/// their own analysis.
+pub(super) fn unreachable_statements(statements: &[Statement]) -> Vec<(Span, Span)> {
+    let mut found = Vec::new();
+    for (i, statement) in statements.iter().enumerate() {
+        let mut inner = statement;
+        while let StatementKind::Hinted { statement, .. } = &inner.kind {
+            inner = statement;
+        }
+        match &inner.kind {
+            StatementKind::If {
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                found.extend(unreachable_statements(then_branch));
+                if let Some(else_branch) = else_branch {
+                    found.extend(unreachable_statements(else_branch));
+                }
+            }
+            StatementKind::Match { arms, .. } => {
+                for arm in arms {
+                    found.extend(unreachable_statements(&arm.body));
+                }
+            }
+            StatementKind::For { body, .. } => found.extend(unreachable_statements(body)),
+            _ => {}
+        }
+        if statement_always_returns(statement) {
+            if let Some(next) = statements.get(i + 1) {
+                found.push((next.span, statement.span));
+            }
+            break;
+        }
+    }
+    found
+}
+
+/// The variables `statements` assign, in nested blocks too. Bodies of nested
+/// functions are left to their own analysis.
+pub(super) fn assigned_names(statements: &[Statement]) -> Vec<&String> {
+    statements
+        .iter()
+        .flat_map(|statement| match &statement.kind {
+            StatementKind::Assignment { identifier, .. }
+            | StatementKind::CompoundAssignment { identifier, .. }
+            | StatementKind::IncrementDecrement { identifier, .. } => vec![identifier],
+            StatementKind::If {
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                let mut names = assigned_names(then_branch);
+                names.extend(assigned_names(else_branch.as_deref().unwrap_or_default()));
+                names
+            }
+            StatementKind::For { body, .. } => assigned_names(body),
+            StatementKind::Match { arms, .. } => arms
+                .iter()
+                .flat_map(|arm| assigned_names(&arm.body))
+                .collect(),
+            StatementKind::Hinted { statement, .. } => {
+                assigned_names(std::slice::from_ref(statement.as_ref()))
+            }
+            _ => Vec::new(),
+        })
+        .collect()
+}
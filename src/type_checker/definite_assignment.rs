@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use crate::token::Span;
+
+/// Variables that are not definitely assigned, each with one path through the
+/// program on which it is still missing a value.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Unassigned {
+    paths: HashMap<String, Path>,
+}
+
+/// The steps of a path on which a variable has no value.
+#[derive(Debug, Clone)]
+pub(super) struct Path {
+    // Where the path moved the value out, for a variable that had one
+    pub(super) moved_at: Option<Span>,
+    // Each step with the span of the statement it is about, in the order
+    // the joins found them
+    steps: Vec<(Span, String)>,
+}
+
+impl Path {
+    /// The steps in source order, each pointing at where it happens.
+    pub(super) fn describe(&self) -> String {
+        let mut steps: Vec<&(Span, String)> = self.steps.iter().collect();
+        steps.sort_by_key(|(span, _)| (span.line, span.column));
+        let steps: Vec<&str> = steps.iter().map(|(_, step)| step.as_str()).collect();
+        steps.join(" -> ")
+    }
+}
+
+impl Unassigned {
+    pub(super) fn declare(&mut self, name: &str, span: Span) {
+        self.paths.insert(
+            name.to_string(),
+            Path {
+                moved_at: None,
+                steps: vec![(
+                    span,
+                    format!("'{}' is declared without a value at {}", name, span),
+                )],
+            },
+        );
+    }
+
+    /// Record that the value of `name` moved out at `span`, leaving it without one.
+    pub(super) fn moved(&mut self, name: &str, span: Span) {
+        self.paths.insert(
+            name.to_string(),
+            Path {
+                moved_at: Some(span),
+                steps: vec![(span, format!("'{}' is moved at {}", name, span))],
+            },
+        );
+    }
+
+    pub(super) fn assign(&mut self, name: &str) {
+        self.paths.remove(name);
+    }
+
+    /// A path on which `name` has no value, if there is one.
+    pub(super) fn path(&self, name: &str) -> Option<&Path> {
+        self.paths.get(name)
+    }
+
+    /// Give `name` the state it has in `saved`, used when a block that shadowed it ends.
+    pub(super) fn restore(&mut self, name: &str, saved: &Unassigned) {
+        match saved.paths.get(name) {
+            Some(steps) => self.paths.insert(name.to_string(), steps.clone()),
+            None => self.paths.remove(name),
+        };
+    }
+
+    /// The subset of variables for which `keep` holds.
+    pub(super) fn filter(&self, keep: impl Fn(&str) -> bool) -> Unassigned {
+        Unassigned {
+            paths: self
+                .paths
+                .iter()
+                .filter(|(name, _)| keep(name))
+                .map(|(name, path)| (name.clone(), path.clone()))
+                .collect(),
+        }
+    }
+
+    /// Join the states at the end of both branches of the `if` at `span`. A
+    /// branch that always returns never reaches the join and is passed as None.
+    pub(super) fn join(
+        before: &Unassigned,
+        then_state: Option<Unassigned>,
+        else_state: Option<Unassigned>,
+        has_else: bool,
+        span: Span,
+    ) -> Unassigned {
+        let (then_state, else_state) = match (then_state, else_state) {
+            (Some(then_state), Some(else_state)) => (then_state, else_state),
+            (Some(only), None) | (None, Some(only)) => return only,
+            // Nothing after the if is reachable
+            (None, None) => return before.clone(),
+        };
+        let else_step = if has_else {
+            format!("the 'if' at {} takes its else branch", span)
+        } else {
+            format!("the condition of the 'if' at {} is false", span)
+        };
+        Unassigned::join_paths(
+            then_state,
+            else_state,
+            (
+                span,
+                format!("the condition of the 'if' at {} is true", span),
+            ),
+            (span, else_step),
+        )
+    }
+
+    /// Join the states before the `for` at `span` and at the end of its body,
+    /// None when the body always returns. The loop ends after any number of
+    /// runs of its body, none included.
+    pub(super) fn join_loop(
+        before: &Unassigned,
+        after_body: Option<Unassigned>,
+        span: Span,
+    ) -> Unassigned {
+        let Some(after_body) = after_body else {
+            return before.clone();
+        };
+        Unassigned::join_paths(
+            after_body,
+            before.clone(),
+            (span, format!("the 'for' at {} runs its body", span)),
+            (span, format!("the 'for' at {} does not run its body", span)),
+        )
+    }
+
+    /// Join two states, adding its step to the path of a variable that has
+    /// a value in the other state.
+    fn join_paths(
+        left: Unassigned,
+        right: Unassigned,
+        left_step: (Span, String),
+        right_step: (Span, String),
+    ) -> Unassigned {
+        let mut joined = Unassigned::default();
+        for (name, path) in &left.paths {
+            let mut path = path.clone();
+            if !right.paths.contains_key(name) {
+                path.steps.push(left_step.clone());
+            }
+            joined.paths.insert(name.clone(), path);
+        }
+        for (name, mut path) in right.paths {
+            joined.paths.entry(name).or_insert_with(|| {
+                path.steps.push(right_step.clone());
+                path
+            });
+        }
+        joined
+    }
+
+    /// Whether a value moved out of a variable since `before`.
+    pub(super) fn moved_since(&self, before: &Unassigned) -> bool {
+        self.paths.iter().any(|(name, path)| {
+            path.moved_at.is_some()
+                && before.paths.get(name).map(|path| path.moved_at) != Some(path.moved_at)
+        })
+    }
+
+    /// Join the states at the end of the arms of the `match` at `span`, each
+    /// with the span of its pattern. Arms that always return are passed as None.
+    pub(super) fn join_arms(
+        before: &Unassigned,
+        arms: Vec<(Option<Unassigned>, Span)>,
+        span: Span,
+    ) -> Unassigned {
+        let reachable: Vec<(Unassigned, Span)> = arms
+            .into_iter()
+            .filter_map(|(state, arm)| Some((state?, arm)))
+            .collect();
+        if reachable.is_empty() {
+            // Nothing after the match is reachable
+            return before.clone();
+        }
+        let mut joined = Unassigned::default();
+        for (state, arm) in &reachable {
+            for (name, path) in &state.paths {
+                joined.paths.entry(name.clone()).or_insert_with(|| {
+                    let mut path = path.clone();
+                    // The arm only belongs on the path when another arm assigns the name
+                    if reachable
+                        .iter()
+                        .any(|(other, _)| !other.paths.contains_key(name))
+                    {
+                        path.steps.push((
+                            *arm,
+                            format!("the 'match' at {} takes the arm at {}", span, arm),
+                        ));
+                    }
+                    path
+                });
+            }
+        }
+        joined
+    }
+}
@@ -0,0 +1,74 @@
+use crate::parser::{Pattern, PatternKind};
+use crate::token::{Literal, TypeDefinitions, TypeIdentifier};
+
+/// A value of `value_type` that none of `patterns` matches, as an error
+/// message names it. None when the patterns cover every value.
+pub(super) fn missing_case(
+    patterns: &[&Pattern],
+    value_type: &TypeIdentifier,
+    definitions: &TypeDefinitions,
+) -> Option<String> {
+    if patterns
+        .iter()
+        .any(|pattern| is_irrefutable(pattern, value_type, definitions))
+    {
+        return None;
+    }
+    match value_type.underlying(definitions) {
+        // Every variant needs an arm whose payload pattern always matches
+        TypeIdentifier::Sum(variants) => variants
+            .iter()
+            .find(|(label, payload_type)| {
+                !patterns.iter().any(|pattern| match &pattern.kind {
+                    PatternKind::Variant {
+                        label: covered,
+                        payload,
+                    } => {
+                        covered == label
+                            && match (payload, payload_type) {
+                                (Some(payload), Some(payload_type)) => {
+                                    is_irrefutable(payload, payload_type, definitions)
+                                }
+                                _ => true,
+                            }
+                    }
+                    _ => false,
+                })
+            })
+            .map(|(label, _)| format!("'{}", label)),
+        TypeIdentifier::Boolean => [true, false]
+            .into_iter()
+            .find(|value| {
+                !patterns.iter().any(|pattern| {
+                    matches!(&pattern.kind, PatternKind::Literal(Literal::Boolean(b)) if b == value)
+                })
+            })
+            .map(|value| value.to_string()),
+        _ => Some("every other value".to_string()),
+    }
+}
+
+/// Whether `pattern` matches every value of `value_type`.
+fn is_irrefutable(
+    pattern: &Pattern,
+    value_type: &TypeIdentifier,
+    definitions: &TypeDefinitions,
+) -> bool {
+    match (&pattern.kind, value_type.underlying(definitions)) {
+        (PatternKind::Wildcard | PatternKind::Binding { .. }, _) => true,
+        (PatternKind::Literal(Literal::Unit), _) => true,
+        (PatternKind::Tuple(elements), TypeIdentifier::Tuple(element_types)) => elements
+            .iter()
+            .zip(element_types)
+            .all(|(element, element_type)| is_irrefutable(element, element_type, definitions)),
+        (PatternKind::Labeled(fields), TypeIdentifier::Labeled(field_types)) => {
+            fields.iter().all(|(label, field)| {
+                field_types
+                    .iter()
+                    .find(|(l, _)| l == label)
+                    .is_some_and(|(_, field_type)| is_irrefutable(field, field_type, definitions))
+            })
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,83 @@
+use crate::parser::{ExpressionKind, Hint, HintPlacement};
+use crate::token::{Literal, Span};
+
+// Inline parameter hints that loosen or only describe the binding, so there is
+// nothing for the caller to prove.
+const UNCHECKED_REQUIREMENTS: &[&str] = &["nullable", "unused"];
+
+// Hints that forbid the function from writing to the parameter at all.
+const NO_WRITE_PROMISES: &[&str] = &["readonly", "immutable"];
+
+const CACHE_LINE_BYTES: u32 = 64;
+
+/// Returns true if the hint is an inline parameter hint the caller must prove.
+pub(super) fn is_requirement(hint: &Hint) -> bool {
+    hint.placement == HintPlacement::Inline && !UNCHECKED_REQUIREMENTS.contains(&hint.name.as_str())
+}
+
+pub(super) fn forbids_write(hint: &Hint) -> bool {
+    NO_WRITE_PROMISES.contains(&hint.name.as_str())
+}
+
+pub(super) fn forbids_read(hint: &Hint) -> bool {
+    hint.name == "writeonly"
+}
+
+/// Returns true if knowing `fact` about a value proves `requirement`.
+pub(super) fn satisfies(fact: &Hint, requirement: &Hint) -> bool {
+    if let (Some(known), Some(needed)) = (alignment(fact), alignment(requirement)) {
+        return needed != 0 && known % needed == 0;
+    }
+    canonical_name(&fact.name) == canonical_name(&requirement.name)
+}
+
+/// Facts every value that cannot be null carries, such as a literal other
+/// than null or the address of a variable.
+pub(super) fn never_null(span: Span) -> Vec<Hint> {
+    vec![Hint {
+        name: "nonnull".to_string(),
+        arguments: Vec::new(),
+        placement: HintPlacement::Inline,
+        span,
+    }]
+}
+
+/// Whether knowing `facts` about a pointer proves it is not null.
+pub(super) fn proves_nonnull(facts: &[Hint]) -> bool {
+    facts.iter().any(|fact| fact.name == "nonnull")
+}
+
+/// Render a hint the way it is written in source, e.g. `@aligned(32)`.
+pub(super) fn render(hint: &Hint) -> String {
+    if hint.arguments.is_empty() {
+        return format!("@{}", hint.name);
+    }
+    let arguments: Vec<String> = hint
+        .arguments
+        .iter()
+        .map(|argument| match &argument.kind {
+            ExpressionKind::Literal(Literal::Integer(value)) => value.to_string(),
+            ExpressionKind::Identifier(name) => name.clone(),
+            _ => "..".to_string(),
+        })
+        .collect();
+    format!("@{}({})", hint.name, arguments.join(", "))
+}
+
+fn alignment(hint: &Hint) -> Option<u32> {
+    match hint.name.as_str() {
+        "aligned" => match hint.arguments.first().map(|argument| &argument.kind) {
+            Some(ExpressionKind::Literal(Literal::Integer(bytes))) => Some(*bytes),
+            _ => None,
+        },
+        "cache_aligned" => Some(CACHE_LINE_BYTES),
+        _ => None,
+    }
+}
+
+fn canonical_name(name: &str) -> &str {
+    match name {
+        "noalias" => "restrict",
+        other => other,
+    }
+}
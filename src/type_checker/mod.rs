@@ -0,0 +1,14 @@
+mod captures;
+mod casts;
+mod contracts;
+mod control_flow;
+mod definite_assignment;
+mod exhaustiveness;
+mod hint_facts;
+mod operators;
+mod scope;
+mod tests;
+#[allow(clippy::module_inception)]
+pub mod type_checker;
+
+pub use type_checker::TypeChecker;
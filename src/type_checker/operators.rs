@@ -0,0 +1,106 @@
+use crate::token::{Operator, TypeDefinitions, TypeIdentifier};
+
+/// The operand types a binary operator accepts and the type it produces.
+/// Both operands must have equivalent types.
+pub(super) struct OperandRule {
+    pub accepts: fn(&TypeIdentifier, &TypeDefinitions) -> bool,
+    // The accepted operand types, as named in error messages
+    pub expected: &'static str,
+    // None when the result has the type of the operands
+    pub result: Option<TypeIdentifier>,
+}
+
+pub(super) fn binary_rule(operator: Operator) -> Option<OperandRule> {
+    let rule = |accepts: fn(&TypeIdentifier, &TypeDefinitions) -> bool, expected, result| {
+        Some(OperandRule {
+            accepts,
+            expected,
+            result,
+        })
+    };
+    match operator {
+        Operator::Plus | Operator::Minus | Operator::Multiply | Operator::Divide => {
+            rule(is_numeric, "int or float", None)
+        }
+        Operator::Modulo
+        | Operator::BitAnd
+        | Operator::BitOr
+        | Operator::BitXor
+        | Operator::ShiftLeft
+        | Operator::ShiftRight => rule(is_integer, "int", None),
+        Operator::Equals
+        | Operator::Different
+        | Operator::StrictEquals
+        | Operator::StrictDifferent => rule(
+            has_equality,
+            "int, float, char, bool, string, unit, a pointer or a product of them",
+            Some(TypeIdentifier::Boolean),
+        ),
+        Operator::GreaterThan
+        | Operator::LesserThan
+        | Operator::GreaterEqual
+        | Operator::LesserEqual => rule(
+            is_ordered,
+            "int, float or char",
+            Some(TypeIdentifier::Boolean),
+        ),
+        Operator::And | Operator::Or => rule(is_boolean, "bool", Some(TypeIdentifier::Boolean)),
+        _ => None,
+    }
+}
+
+/// Whether `operator` compares type identity as well as values.
+pub(super) fn is_strict(operator: Operator) -> bool {
+    matches!(operator, Operator::StrictEquals | Operator::StrictDifferent)
+}
+
+/// Whether `operand` is a pointer of any kind, or null.
+pub(super) fn is_pointer(operand: &TypeIdentifier, definitions: &TypeDefinitions) -> bool {
+    matches!(
+        operand.underlying(definitions),
+        TypeIdentifier::Pointer(_) | TypeIdentifier::SafePointer(..) | TypeIdentifier::Null
+    )
+}
+
+pub(super) fn is_numeric(operand: &TypeIdentifier, definitions: &TypeDefinitions) -> bool {
+    matches!(
+        operand.underlying(definitions),
+        TypeIdentifier::Number | TypeIdentifier::Float
+    )
+}
+
+pub(super) fn is_integer(operand: &TypeIdentifier, definitions: &TypeDefinitions) -> bool {
+    *operand.underlying(definitions) == TypeIdentifier::Number
+}
+
+fn is_boolean(operand: &TypeIdentifier, definitions: &TypeDefinitions) -> bool {
+    *operand.underlying(definitions) == TypeIdentifier::Boolean
+}
+
+fn is_ordered(operand: &TypeIdentifier, definitions: &TypeDefinitions) -> bool {
+    is_numeric(operand, definitions) || *operand.underlying(definitions) == TypeIdentifier::Char
+}
+
+/// Values compare equal component by component, so a product has equality
+/// when all its components do. Pointers compare their addresses. Functions
+/// never have equality.
+fn has_equality(operand: &TypeIdentifier, definitions: &TypeDefinitions) -> bool {
+    match operand.underlying(definitions) {
+        TypeIdentifier::Number
+        | TypeIdentifier::Float
+        | TypeIdentifier::Char
+        | TypeIdentifier::Boolean
+        | TypeIdentifier::String
+        | TypeIdentifier::Unit
+        | TypeIdentifier::Pointer(_)
+        | TypeIdentifier::SafePointer(..)
+        | TypeIdentifier::Null => true,
+        TypeIdentifier::Tuple(elements) => elements
+            .iter()
+            .all(|element| has_equality(element, definitions)),
+        TypeIdentifier::Labeled(fields) => fields
+            .iter()
+            .all(|(_, field_type)| has_equality(field_type, definitions)),
+        _ => false,
+    }
+}
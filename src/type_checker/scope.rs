@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use crate::hir::hir::BindingId;
+use crate::token::{Span, TypeIdentifier};
+
+#[derive(Debug, Clone)]
+struct Binding {
+    id: BindingId,
+    var_type: TypeIdentifier,
+    span: Span,
+}
+
+/// The variables in scope as a stack of blocks, the globals at the bottom.
+/// A lookup walks from the innermost block outwards, so an inner declaration
+/// shadows an outer one until its block ends.
+#[derive(Debug, Clone)]
+pub(super) struct Scopes {
+    frames: Vec<HashMap<String, Binding>>,
+}
+
+impl Default for Scopes {
+    fn default() -> Self {
+        Self {
+            frames: vec![HashMap::new()],
+        }
+    }
+}
+
+impl Scopes {
+    /// Only the global scope, which is all a named function can see.
+    pub(super) fn globals(&self) -> Scopes {
+        Scopes {
+            frames: self.frames[..1].to_vec(),
+        }
+    }
+
+    /// Whether no block is open, only the globals.
+    pub(super) fn at_top_level(&self) -> bool {
+        self.frames.len() == 1
+    }
+
+    pub(super) fn push(&mut self) {
+        self.frames.push(HashMap::new());
+    }
+
+    /// End the innermost block, returning the names that were declared in it.
+    pub(super) fn pop(&mut self) -> Vec<String> {
+        self.frames
+            .pop()
+            .map(|frame| frame.into_keys().collect())
+            .unwrap_or_default()
+    }
+
+    /// Declare `name` in the innermost block. Declaring a name twice in the
+    /// same block is refused with the span of the first declaration.
+    pub(super) fn declare(
+        &mut self,
+        name: &str,
+        id: BindingId,
+        var_type: TypeIdentifier,
+        span: Span,
+    ) -> Result<(), Span> {
+        let frame = self
+            .frames
+            .last_mut()
+            .expect("the global scope is never popped");
+        if let Some(previous) = frame.get(name) {
+            return Err(previous.span);
+        }
+        frame.insert(name.to_string(), Binding { id, var_type, span });
+        Ok(())
+    }
+
+    pub(super) fn get(&self, name: &str) -> Option<&TypeIdentifier> {
+        self.lookup(name).map(|binding| &binding.var_type)
+    }
+
+    /// The binding `name` refers to at this point.
+    pub(super) fn resolve(&self, name: &str) -> Option<BindingId> {
+        self.lookup(name).map(|binding| binding.id)
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.frames.iter().rev().find_map(|frame| frame.get(name))
+    }
+
+    pub(super) fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Whether `name` resolves to a binding other than a global.
+    pub(super) fn is_local(&self, name: &str) -> bool {
+        self.frames
+            .iter()
+            .rposition(|frame| frame.contains_key(name))
+            .is_some_and(|depth| depth > 0)
+    }
+}
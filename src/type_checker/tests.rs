@@ -0,0 +1,1690 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::{
+        diagnostic::Diagnostic,
+        test_support::parse_with_promises as parse,
+        token::Span,
+        type_checker::{TypeChecker, type_checker::LambdaKind},
+    };
+
+    fn diagnostics(src: &str) -> Vec<Diagnostic> {
+        let ast = parse(src);
+        let mut type_checker = TypeChecker::new(&ast);
+        type_checker.check_ast()
+    }
+
+    fn check(src: &str) -> Vec<String> {
+        diagnostics(src)
+            .into_iter()
+            .filter(|diagnostic| diagnostic.is_error())
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    fn warnings(src: &str) -> Vec<Diagnostic> {
+        let diagnostics = diagnostics(src);
+        assert!(
+            diagnostics.iter().all(|diagnostic| !diagnostic.is_error()),
+            "{:?}",
+            diagnostics
+        );
+        diagnostics
+    }
+
+    fn lambdas(src: &str) -> Vec<LambdaKind> {
+        let ast = parse(src);
+        let mut type_checker = TypeChecker::new(&ast);
+        let errors = type_checker.check_program();
+        assert!(errors.is_empty(), "{:?}", errors);
+        type_checker.lambdas().to_vec()
+    }
+
+    #[test]
+    fn test_requirement_proven_by_hinted_binding() {
+        let errors = check(
+            "function process(@aligned(32) data int) {}
+             @aligned(64) let vectors int = 1;
+             let copy int = vectors;
+             process(copy);",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_requirement_not_proven() {
+        let errors = diagnostics(
+            "function process(@aligned(32) data int) {}
+             @aligned(16) let vectors int = 1;
+             process(vectors);",
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, "E0303");
+        assert!(
+            errors[0]
+                .message
+                .contains("requirement '@aligned(32)' of parameter 'data'")
+        );
+        assert_eq!(errors[0].labels[0].span.line, 1);
+        assert!(errors[0].notes[0].contains("@aligned(16)"), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_requirement_available_inside_function() {
+        let errors = check(
+            "function inner(@nonnull x int) {}
+             function outer(@nonnull y int) { inner(y); }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_temporaries_cannot_be_written_by_the_caller() {
+        let errors = check(
+            "function f(@readonly x int, @immutable y int) int { return x + y; }
+             let a int = f(10, f(1, 2) + 3);",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        // A variable could still be written, it has to be declared so
+        let errors = check(
+            "function f(@readonly x int) int { return x; }
+             let b int = 4;
+             let c int = f(b);",
+        );
+        assert_eq!(
+            errors,
+            ["Argument 1 of 'f' does not prove requirement '@readonly' of parameter 'x'"]
+        );
+    }
+
+    #[test]
+    fn test_update_checked_against_promises() {
+        let errors = check(
+            "@aligned(n, 32) function f(n int) {
+                 n += 1;
+                 n++;
+             }",
+        );
+        assert_eq!(
+            errors,
+            [
+                "Assignment to 'n' breaks the promise '@aligned(32)(n)': the assigned value is not known to be @aligned(32)",
+                "Assignment to 'n' breaks the promise '@aligned(32)(n)': the assigned value is not known to be @aligned(32)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_readonly_promise_broken_by_assignment() {
+        let errors = check("@readonly(x) function f(x int) { x = 1; }");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("promises '@readonly(x)' but assigns to 'x'"));
+    }
+
+    #[test]
+    fn test_writeonly_promise_broken_by_read() {
+        let errors = check("@writeonly(x) function f(x int) { let y int = x; }");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("promises '@writeonly(x)' but reads 'x'"));
+    }
+
+    #[test]
+    fn test_writeonly_promise_kept_through_call() {
+        let errors = check(
+            "@writeonly(buffer) function fill(buffer int) { buffer = 1; }
+             @writeonly(x) function f(x int) { fill(x); }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_lambda_called_through_variable() {
+        let errors = check(
+            "let double (int) -> int = func(x int) int { return x * 2; };
+             let doubled int = double(5);",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_function_typed_parameter() {
+        let errors = check(
+            "function apply_twice(f (int) -> int, x int) int { return f(f(x)); }
+             function inc(x int) int { return x + 1; }
+             let four int = apply_twice(inc, 2);",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_function_value_type_mismatch() {
+        let errors = check(
+            "function apply(f (int) -> int, x int) int { return f(x); }
+             let r int = apply(function(b bool) int { return 1; }, 2);",
+        );
+        assert_eq!(
+            errors,
+            ["Type mismatch in argument 1 of 'apply': expected (int) -> int, got (bool) -> int"]
+        );
+    }
+
+    #[test]
+    fn test_call_non_function_value() {
+        let errors = check("let x int = 1; let y int = x(2);");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("Cannot call a value of type int"));
+        let errors = check("let y int = missing(2);");
+        assert_eq!(errors, ["Call to undefined function 'missing'"]);
+    }
+
+    #[test]
+    fn test_closure_captures_enclosing_local() {
+        let kinds = lambdas(
+            "function make_adder(n int) (int) -> int {
+                 return function(x int) int { return x + n; };
+             }
+             let add5 (int) -> int = make_adder(5);
+             let res int = add5(10);",
+        );
+        assert_eq!(
+            kinds,
+            vec![LambdaKind::Closure {
+                captures: vec!["n".to_string()]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_lambda_without_captures_is_pure() {
+        let kinds = lambdas(
+            "function make_double() (int) -> int {
+                 return function(x int) int { let y int = x; return y * 2; };
+             }",
+        );
+        assert_eq!(kinds, vec![LambdaKind::Pure]);
+    }
+
+    #[test]
+    fn test_block_local_does_not_hide_a_capture() {
+        let kinds = lambdas(
+            "function make(x int, c bool) () -> int {
+                 return function() int {
+                     if c {
+                         let x int = 1;
+                     }
+                     return x;
+                 };
+             }",
+        );
+        assert_eq!(
+            kinds,
+            vec![LambdaKind::Closure {
+                captures: vec!["c".to_string(), "x".to_string()]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_call_before_definition() {
+        let errors = check(
+            "let x int = later(1);
+             function later(n int) int { return n; }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_mutual_recursion() {
+        let errors = check(
+            "function is_even(n int) bool {
+                 if n == 1 { return false; }
+                 return is_odd(n - 1);
+             }
+             function is_odd(n int) bool {
+                 if n == 1 { return true; }
+                 return is_even(n - 1);
+             }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_forward_declaration_matches_definition() {
+        let errors = check(
+            "function forward_declared(x int) int;
+             let y int = forward_declared(2);
+             function forward_declared(x int) int { return x * 2; }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_forward_declaration_mismatch() {
+        let errors = check(
+            "function f(x int) bool;
+             function f(x int) int { return x; }",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("declared as (int) -> bool, but also as (int) -> int"));
+    }
+
+    #[test]
+    fn test_call_to_declared_but_undefined_function() {
+        let errors = check("function f(x int) int; let y int = f(1);");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("declared but never defined"));
+    }
+
+    #[test]
+    fn test_duplicate_definition() {
+        let errors = check(
+            "function f(x int) int { return x; }
+             function f(x int) int { return x; }",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("defined more than once"));
+    }
+
+    #[test]
+    fn test_errors_point_at_source() {
+        let ast = parse("let a int = 1;\nlet b bool = a;\nprint_int(true);");
+        let mut type_checker = TypeChecker::new(&ast);
+        let errors = type_checker.check_program();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].span, Span::new(2, 1, 2, 15));
+        assert_eq!(errors[1].span, Span::new(3, 11, 3, 15));
+        assert_eq!(errors[1].code, "E0100");
+    }
+
+    #[test]
+    fn test_every_statement_of_a_body_is_checked() {
+        let errors = check(
+            "function f(c bool) int {
+                 let a int = true;
+                 if c {
+                     let b bool = 1;
+                     c = 1;
+                 }
+                 let g () -> int = function() int { return c; };
+                 return 1;
+             }",
+        );
+        assert_eq!(
+            errors,
+            [
+                "Type mismatch in variable declaration 'a': declared as int, but got bool",
+                "Type mismatch in variable declaration 'b': declared as bool, but got int",
+                "Type mismatch in assignment to 'c': variable is bool, but got int",
+                "Function '<anonymous function>' returns bool, but declared as int",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_negation_and_bitwise_not() {
+        let errors = check("let a int = 5; let b int = -a; let c int = ~0b1100; let d int = - -b;");
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = check("let a int = -true;");
+        assert_eq!(errors, ["Cannot negate a value of type bool"]);
+        let errors = check("let a bool = ~false;");
+        assert_eq!(
+            errors,
+            ["Bitwise NOT requires an integer, got a value of type bool"]
+        );
+    }
+
+    #[test]
+    fn test_address_of_and_deref() {
+        let errors = check(
+            "let x int = 5;
+             let p &int = addressof x;
+             let q &int = &x;
+             let pp &&int = &p;
+             let y int = deref p + deref deref pp;",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = check("let x int = 5; let y int = deref x;");
+        assert_eq!(errors, ["Cannot dereference a value of type int"]);
+        let errors = check("let p &int = &5;");
+        assert_eq!(errors, ["Can only take the address of a variable"]);
+        let errors = check("let x int = 5; let p &bool = &x;");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("declared as &bool"), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_compound_assignment_and_increment() {
+        let errors = check(
+            "let i int = 1;
+             i += 2; i -= 1; i *= 3; i /= 2; i %= 5;
+             i++; i--;
+             function f(n int) int { n++; return n; }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = check("let i int = 1; i += true;");
+        assert_eq!(
+            errors,
+            ["Type mismatch in compound assignment to 'i': variable is int, but got bool"]
+        );
+        let errors = check("let b bool = true; b++;");
+        assert_eq!(
+            errors,
+            ["Operator '++' requires a variable of type int, but 'b' is bool"]
+        );
+        let errors = check("j -= 1;");
+        assert_eq!(errors, ["Assignment to undeclared variable 'j'"]);
+        let errors = diagnostics("print_int++; print_int = 1;");
+        let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Cannot assign to function 'print_int'",
+                "Cannot assign to function 'print_int'"
+            ]
+        );
+        assert!(errors.iter().all(|error| error.code == "E0300"));
+    }
+
+    #[test]
+    fn test_update_target_must_be_mutable() {
+        let errors = check("@immutable let i int = 1; i++;");
+        assert_eq!(errors, ["Cannot assign to immutable variable 'i'"]);
+        let errors = check("@readonly let i int = 1; i = 2;");
+        assert_eq!(errors, ["Cannot assign to immutable variable 'i'"]);
+        // A shadowing declaration without the hint is mutable, until its block ends
+        let errors = check("@immutable let i int = 1; if true { let i int = 2; i += 1; } i = 3;");
+        assert_eq!(errors, ["Cannot assign to immutable variable 'i'"]);
+
+        let errors = check("@readonly(n) function f(n int) { n += 1; }");
+        assert_eq!(
+            errors,
+            ["Function promises '@readonly(n)' but assigns to 'n'"]
+        );
+        let errors = check("@writeonly(n) function f(n int) { n++; }");
+        assert_eq!(errors, ["Function promises '@writeonly(n)' but reads 'n'"]);
+    }
+
+    #[test]
+    fn test_definitely_assigned() {
+        let errors = check(
+            "let c bool = true;
+             let x int;
+             if c { x = 1; } else { x = 2; }
+             let y int;
+             if c { y = 1; } else { return; }
+             let z int;
+             z = x + y;
+             print_int(z);",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_read_before_assignment() {
+        let errors = diagnostics("let x int;\nlet y int = x;");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, "E0400");
+        assert_eq!(
+            errors[0].message,
+            "Variable 'x' is read before it is definitely assigned"
+        );
+        assert_eq!(
+            errors[0].notes,
+            ["path: 'x' is declared without a value at 1:1 -> 'x' is read at 2:13"]
+        );
+        let errors = check("let x int; x++;");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Variable 'x' is read before"));
+    }
+
+    #[test]
+    fn test_missing_assignment_path() {
+        let errors = diagnostics(
+            "let c bool = true;
+let x int;
+if c {
+    x = 1;
+}
+print_int(x);",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0].notes[0].ends_with(
+                "path: 'x' is declared without a value at 2:1 -> the condition of the 'if' at 3:1 is false -> 'x' is read at 6:11"
+            ),
+            "{:?}",
+            errors
+        );
+
+        let errors = diagnostics(
+            "let c bool = true;
+let x int;
+if c {
+    print_int(1);
+} else {
+    x = 1;
+}
+print_int(x);",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0].notes[0].contains("-> the condition of the 'if' at 3:1 is true ->"),
+            "{:?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn test_let_type_inferred_from_initializer() {
+        let errors = check(
+            "let x = 1;
+             let b = x > 2;
+             let y int = x + 1;
+             let f = function(n int) int { return n; };
+             let z int = f(y);",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = check("let b = true; let x int = b;");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("declared as int, but got bool"));
+    }
+
+    #[test]
+    fn test_let_type_ambiguity() {
+        let errors = check("let x;");
+        assert_eq!(
+            errors,
+            vec!["Cannot infer the type of 'x': add a type annotation or an initializer"]
+        );
+    }
+
+    #[test]
+    fn test_lambda_parameters_inferred_from_expected_type() {
+        let errors = check(
+            "function apply(f (int) -> int, x int) int { return f(x); }
+             let r int = apply(function(n) int { return n * 2; }, 2);
+             let g (bool) -> bool = function(b) bool { return !b; };",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = check("let g = function(n) int { return n; };");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Cannot infer the type of parameter 'n'"));
+    }
+
+    #[test]
+    fn test_tuple_destructuring() {
+        let errors = check(
+            "let point (int * int) = (1, 2);
+             let (x int, y int) = point;
+             let (a, (b, _)) = (true, ('c', 3));
+             let sum int = x + y;
+             let flag bool = a;
+             let letter char = b;",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_tuple_destructuring_errors() {
+        let errors = check("let (x, y, z) = (1, 2);");
+        assert_eq!(
+            errors,
+            vec!["Tuple pattern has 3 elements, but the value of type (int * int) has 2"]
+        );
+
+        let errors = check("let (x int, y int) = (1, true);");
+        assert_eq!(
+            errors,
+            vec!["Type mismatch in destructuring: 'y' is declared as int, but the value is bool"]
+        );
+
+        let errors = check("let (x, y) = 5;");
+        assert_eq!(
+            errors,
+            vec!["Cannot destructure a value of type int with a tuple pattern"]
+        );
+
+        let errors = check("let (x, 1) = (1, 2);");
+        assert_eq!(
+            errors,
+            vec!["A 'let' pattern must always match, but the literal pattern 1 may not"]
+        );
+    }
+
+    #[test]
+    fn test_labeled_destructuring() {
+        let errors = check(
+            "let person ('name char * 'age int) = { name = 'j', age = 25 };
+             let { age = years int } = person;
+             let { name = initial, age = _ } = person;
+             let older int = years + 1;
+             let first char = initial;",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = check(
+            "let person ('name char * 'age int) = { name = 'j', age = 25 };
+             let { height = h } = person;",
+        );
+        assert_eq!(
+            errors,
+            vec!["Type ('name char * 'age int) has no field 'height'"]
+        );
+
+        let errors = check("let s = { x = 1, x = 2 };");
+        assert_eq!(errors, vec!["Field 'x' is given more than once"]);
+    }
+
+    #[test]
+    fn test_block_declarations_do_not_leak() {
+        let errors = check("if true { let x int = 1; } print_int(x);");
+        assert_eq!(errors, ["Use of undeclared variable 'x'"]);
+    }
+
+    #[test]
+    fn test_shadowing_and_redeclaration() {
+        let errors = check(
+            "let x int = 1;
+             if true {
+                 let x bool = true;
+                 let y bool = x;
+             }
+             let z int = x;",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = diagnostics(
+            "let x int = 1;
+let x int = 2;",
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, "E0002");
+        assert_eq!(
+            errors[0].message,
+            "Variable 'x' is already declared in this scope"
+        );
+        assert_eq!(errors[0].labels[0].message, "first declared here");
+        assert_eq!(errors[0].labels[0].span.line, 1);
+        assert_eq!(errors[0].labels[0].span.column, 5);
+
+        let errors = diagnostics("function f(n int) { let n int = 1; }");
+        assert_eq!(errors[0].labels[0].span.column, 12);
+        let errors = diagnostics("function f(n int, n int) {}");
+        assert_eq!(errors[0].labels[0].span.column, 12);
+    }
+
+    #[test]
+    fn test_functions_see_globals() {
+        let errors = check(
+            "let limit int = 10;
+             function clamp(n int) int {
+                 let limit bool = true;
+                 return n;
+             }
+             function get() int { return limit; }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        // Locals of an enclosing function are not globals
+        let errors = check(
+            "function outer() {
+                 let local int = 1;
+                 function inner() int { return local; }
+             }",
+        );
+        assert_eq!(errors, ["Use of undeclared variable 'local'"]);
+    }
+
+    #[test]
+    fn test_lambda_reading_a_global_is_pure() {
+        let kinds = lambdas(
+            "let base int = 1;
+             function make() (int) -> int {
+                 return function(x int) int { return x + base; };
+             }",
+        );
+        assert_eq!(kinds, [LambdaKind::Pure]);
+    }
+
+    #[test]
+    fn test_returns_on_every_path() {
+        let errors = check(
+            "function sign(n int) int {
+                 if n < 1 {
+                     return 1;
+                 } else {
+                     return 2;
+                 }
+             }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_path_falling_off_the_end() {
+        let errors = diagnostics(
+            "function f(n int) int {
+    if n < 1 {
+        return 1;
+    }
+}",
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, "E0401");
+        assert_eq!(
+            errors[0].message,
+            "Function 'f' can reach the end of its body without returning a value"
+        );
+        assert_eq!(
+            errors[0].notes,
+            ["path: the condition of the 'if' at 2:5 is false"]
+        );
+
+        let errors = diagnostics(
+            "function g(n int) int {
+    if n < 1 {
+        print_int(n);
+    } else {
+        return 1;
+    }
+}",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0].notes[0].ends_with("path: the condition of the 'if' at 2:5 is true"),
+            "{:?}",
+            errors
+        );
+
+        let errors = check("function h() int { print_int(1); }");
+        assert_eq!(
+            errors,
+            ["Function 'h' can reach the end of its body without returning a value"]
+        );
+    }
+
+    #[test]
+    fn test_nested_return_checked_against_declared_type() {
+        let errors = check(
+            "function f(n int) int {
+                 if n < 1 {
+                     return true;
+                 }
+                 return n;
+             }",
+        );
+        assert_eq!(errors, ["Function 'f' returns bool, but declared as int"]);
+    }
+
+    #[test]
+    fn test_unreachable_code_after_return() {
+        let warnings = warnings(
+            "function f(n int) int {
+    return n;
+    print_int(n);
+}
+function g(n int) int {
+    if n < 1 {
+        return 1;
+        print_int(n);
+    } else {
+        return 2;
+    }
+    print_int(n);
+}",
+        );
+        let found: Vec<_> = warnings
+            .iter()
+            .map(|warning| {
+                assert_eq!(warning.code, "W0001");
+                assert_eq!(warning.message, "Unreachable statement");
+                (warning.span.line, warning.labels[0].span.line)
+            })
+            .collect();
+        assert_eq!(found, [(3, 2), (8, 7), (12, 6)]);
+    }
+
+    #[test]
+    fn test_unit_type() {
+        let errors = check(
+            "function log(n int) unit { print_int(n); return; }
+             function nothing() {}
+             let u unit = ();
+             let v = nothing();
+             let w unit = log(1);
+             let f () -> unit = nothing;
+             let () = v;",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_unit_is_not_a_value_of_other_types() {
+        let errors = check("function f() {} let x int = f();");
+        assert_eq!(
+            errors,
+            ["Type mismatch in variable declaration 'x': declared as int, but got unit"]
+        );
+
+        let errors = check("function f() { return 1; }");
+        assert_eq!(errors, ["Function 'f' returns int, but declared as unit"]);
+
+        let errors = check("function f() int { return; }");
+        assert_eq!(errors, ["Function 'f' returns unit, but declared as int"]);
+    }
+
+    #[test]
+    fn test_float_char_and_bool_operators() {
+        let errors = check(
+            "let x float = 1.5f * 2.0f - -0.5f;
+             let y float = x / 3.0f;
+             y += 1.0f;
+             let bigger bool = x > y;
+             let same bool = 'a' == 'b';
+             let before bool = 'a' < 'z';
+             let flag bool = true;
+             let unchanged bool = flag == true && flag != false;",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = check("let x float = 2.0f; x++;");
+        assert_eq!(
+            errors,
+            ["Operator '++' requires a variable of type int, but 'x' is float"]
+        );
+        let errors = check("let r float = 5.0f % 2.0f;");
+        assert_eq!(
+            errors,
+            ["The left operand of '%' must be int, but it is float"]
+        );
+        let errors = check("let b bool = true < false;");
+        assert_eq!(
+            errors,
+            ["The left operand of '<' must be int, float or char, but it is bool"]
+        );
+    }
+
+    #[test]
+    fn test_mixed_operands_point_at_the_wrong_one() {
+        let errors = diagnostics("let x int = 1 + 2.5f;");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "Mismatched operands of '+': the left operand is int, but the right operand is float"
+        );
+        assert_eq!(errors[0].span, Span::new(1, 17, 1, 21));
+        assert_eq!(errors[0].labels[0].span, Span::new(1, 13, 1, 14));
+
+        let errors = diagnostics("let b bool = 1 == true;");
+        assert_eq!(
+            errors[0].message,
+            "Mismatched operands of '==': the left operand is int, but the right operand is bool"
+        );
+        assert_eq!(errors[0].span, Span::new(1, 19, 1, 23));
+
+        // An operand the operator never takes is reported on its own
+        let errors = diagnostics("let n int = 'c' + 1;");
+        assert_eq!(
+            errors[0].message,
+            "The left operand of '+' must be int or float, but it is char"
+        );
+        assert_eq!(errors[0].span, Span::new(1, 13, 1, 16));
+        let errors = diagnostics("let n int = 1 + 'c';");
+        assert_eq!(
+            errors[0].message,
+            "The right operand of '+' must be int or float, but it is char"
+        );
+        assert_eq!(errors[0].span, Span::new(1, 17, 1, 20));
+    }
+
+    #[test]
+    fn test_structural_equality_on_named_types() {
+        let errors = check(
+            "type Age = int;
+             type Year = int;
+             type Person = (bool * Age);
+             let a Age = 25;
+             let y Year = 25;
+             let i int = a;
+             let same bool = a == y && a == i && a != 26;
+             let older Age = a + 1;
+             let p1 (bool * int) = (true, 20);
+             let p2 Person = (true, 20);
+             let equal bool = p1 == p2;
+             let s1 ('x int * 'y Age) = { x = 1, y = 2 };
+             let s2 ('x Year * 'y int) = { x = 1, y = 2 };
+             let labeled bool = s1 == s2;
+             let (flag, age) = p2;
+             let next Age = age + 1;",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = check("type Age = int; let a Age = 1; let b bool = a == true;");
+        assert_eq!(
+            errors,
+            ["Mismatched operands of '==': the left operand is Age, but the right operand is bool"]
+        );
+        let errors = check(
+            "let p1 (int * int) = (1, 2); let p2 (int * bool) = (1, true); let b bool = p1 == p2;",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Mismatched operands of '=='"));
+        // Function types have no equality, not even inside a product
+        let errors = check(
+            "function f() {} let g () -> unit = f; let b bool = g == g;
+             let h bool = (1, f) == (1, f);",
+        );
+        assert_eq!(
+            errors,
+            [
+                "The left operand of '==' must be int, float, char, bool, string, unit, a pointer or a product of them, but it is () -> unit",
+                "The left operand of '==' must be int, float, char, bool, string, unit, a pointer or a product of them, but it is (int * () -> unit)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strict_equality_requires_identical_types() {
+        let found = warnings(
+            "type Age = int;
+             type Year = int;
+             let a Age = 25;
+             let y Year = 25;
+             let i int = 25;
+             let same bool = a === a;
+             let never bool = a === y;
+             let always bool = a !== i;
+             let p1 (bool * int) = (true, 20);
+             let p2 (bool * Age) = (true, 20);
+             let p3 (bool * Age) = (true, 20);
+             let mixed bool = p1 === p2;
+             let identical bool = p2 === p3;",
+        );
+        let messages: Vec<_> = found.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "'===' is always false: Age and Year are different types",
+                "'!==' is always true: Age and int are different types",
+                "'===' is always false: (bool * int) and (bool * Age) are different types",
+            ]
+        );
+        assert!(found.iter().all(|w| w.code == "W0002"));
+        assert_eq!(found[0].span, Span::new(7, 31, 7, 38));
+    }
+
+    #[test]
+    fn test_type_declaration_errors() {
+        let errors = diagnostics("let a Age = 1;");
+        assert_eq!(errors[0].code, "E0005");
+        assert_eq!(errors[0].message, "Unknown type 'Age'");
+
+        let errors = check("type A = (int * B); type B = int;");
+        assert!(errors.is_empty(), "{:?}", errors);
+        let errors = check("type A = int; type A = bool;");
+        assert_eq!(errors, ["Type 'A' is declared more than once"]);
+        let errors = check("function f(n Meters) {}");
+        assert_eq!(errors, ["Unknown type 'Meters'"]);
+        let errors = check("if true { type Local = int; }");
+        assert_eq!(errors, ["Type 'Local' must be declared at the top level"]);
+    }
+
+    #[test]
+    fn test_recursive_types() {
+        let errors = check(
+            "type Dog = ('name string * 'next unique &Dog);
+             type Tree = 'leaf int + 'node (&Branch * &Branch);
+             type Branch = Tree;
+             function second(d Dog) string {
+                 let next Dog = deref d.next;
+                 return next.name;
+             }
+             function main() {
+                 let none unique &Dog = null;
+                 let last Dog = { name = \"Rex\", next = none };
+                 let first Dog = { name = \"Fido\", next = &last };
+                 let same bool = first == first;
+                 let name string = second(first);
+             }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let loops = diagnostics("type A = (int * B); type B = 'left A + 'right int;");
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].code, "E0008");
+        assert_eq!(loops[0].message, "Type 'A' contains itself");
+        assert_eq!(
+            loops[0].notes,
+            [
+                "'A' contains 'B', which contains 'A'",
+                "refer to it through a pointer, such as unique &A"
+            ]
+        );
+        let errors = check("type Same = Same;");
+        assert_eq!(errors, ["Type 'Same' contains itself"]);
+    }
+
+    #[test]
+    fn test_safe_casts() {
+        let errors = check(
+            "type Celsius = float;
+             type Checked = 'Ok int + 'Err Error;
+             function convert(t Celsius) Checked {
+                 let a int = t.cast_int()?;
+                 let b float = cast_float(a);
+                 let c char = cast_char(a + 60)?;
+                 let d bool = cast_bool(cast_int(c) - 99)?;
+                 let e int = cast_int(true);
+                 match cast_char(e) {
+                 | 'Ok c -> return 'Ok cast_int(c)
+                 | 'Err err -> return 'Err err
+                 }
+             }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        // A cast that checks the value gives a result, not the target type
+        let errors = check("let a int = cast_int(2.5f);");
+        assert_eq!(
+            errors,
+            [
+                "Type mismatch in variable declaration 'a': declared as int, but got 'Ok int + 'Err Error"
+            ]
+        );
+
+        let errors = diagnostics("let b bool = cast_bool(2.5f);");
+        assert_eq!(errors[0].code, "E0106");
+        assert_eq!(errors[0].message, "Cannot cast float to bool");
+        assert_eq!(errors[0].labels[0].message, "this is float");
+        let errors = check("let u unit = cast_unit(1);");
+        assert_eq!(errors, ["Cannot cast int to unit"]);
+        // The cast has the target type
+        let errors = check("let c char = cast_int('a');");
+        assert_eq!(
+            errors[0],
+            "Type mismatch in variable declaration 'c': declared as char, but got int"
+        );
+    }
+
+    #[test]
+    fn test_raw_casts() {
+        let errors = check(
+            "@raw_cast let a int = 'c';
+             @raw_cast let b float = a;
+             @raw_cast let p (char * char) = (1, 2.5f);",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = diagnostics("@raw_cast let w int = true;");
+        assert_eq!(errors[0].code, "E0107");
+        assert_eq!(
+            errors[0].message,
+            "Cannot raw cast bool to int: bool is 1 byte, but int is 4 bytes"
+        );
+        let errors = check("@raw_cast let w int;");
+        assert_eq!(
+            errors,
+            ["'@raw_cast' needs a single variable with a type annotation and an initializer"]
+        );
+        // Without the hint the types must still match
+        let errors = diagnostics("let a int = 'c';");
+        assert_eq!(errors[0].code, "E0100");
+    }
+
+    const DIVISION_ERROR: &str = "
+        type DivisionByZeroError = (
+            'dividend int
+            ;
+            Error {
+                function get_error_id(self) int { return 1; }
+                function get_error_message(self) string { return \"division by zero\"; }
+            }
+        )
+        type DivideResult = 'Ok int + 'Err DivisionByZeroError;
+        function divide(a int, b int) DivideResult {
+            if b == a - a {
+                return 'Err DivisionByZeroError { dividend = a };
+            }
+            return 'Ok (a / b);
+        }
+    ";
+
+    #[test]
+    fn test_variants() {
+        let errors = check(
+            "type Option = 'None + 'Some int;
+             let a Option = 'None;
+             let b Option = 'Some (1 + 2);
+             function wrap(n int) Option { return 'Some n; }
+             let c Option = wrap(1);",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = diagnostics("type Option = 'None + 'Some int; let o Option = 'Any 1;");
+        assert_eq!(errors[0].code, "E0108");
+        assert_eq!(errors[0].message, "Type Option has no variant 'Any");
+        let errors = check("type Option = 'None + 'Some int; let o Option = 'Some;");
+        assert_eq!(
+            errors,
+            ["Variant 'Some of Option carries a value of type int, but none is given"]
+        );
+        let errors = check("type Option = 'None + 'Some int; let o Option = 'None 1;");
+        assert_eq!(errors, ["Variant 'None of Option carries no value"]);
+        let errors = check("type Option = 'None + 'Some int; let o Option = 'Some true;");
+        assert_eq!(
+            errors,
+            ["Type mismatch in the payload of variant 'Some: expected int, got bool"]
+        );
+        // Without an expected type there is no sum type to build
+        let errors = diagnostics("let o = 'None;");
+        assert_eq!(errors[0].code, "E0102");
+        let errors = check("type Twice = 'A + 'A int;");
+        assert_eq!(errors, ["Variant 'A is given more than once"]);
+    }
+
+    #[test]
+    fn test_match_exhaustiveness() {
+        let errors = check(
+            "type Option = 'None + 'Some int;
+             function get(o Option) int {
+                 match o {
+                 | 'Some n -> return n
+                 | 'None -> return 1
+                 }
+             }
+             function is_one(o Option) bool {
+                 match o {
+                 | 'Some 1 -> return true
+                 | _ -> return false
+                 }
+             }
+             function flip(b bool) bool {
+                 match b {
+                 | true -> return false
+                 | false -> return true
+                 }
+             }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = diagnostics(
+            "type Option = 'None + 'Some int;
+             let o Option = 'None;
+             match o { | 'Some 1 -> print_int(1) | 'None -> print_int(2) }",
+        );
+        assert_eq!(errors[0].code, "E0203");
+        assert_eq!(
+            errors[0].message,
+            "Match on a value of type Option does not cover 'Some"
+        );
+        let errors = check("match true { | true -> print_int(1) }");
+        assert_eq!(
+            errors,
+            ["Match on a value of type bool does not cover false"]
+        );
+        let errors = check("match 3 { | 1 -> print_int(1) }");
+        assert_eq!(
+            errors,
+            ["Match on a value of type int does not cover every other value"]
+        );
+        let errors = check("match 3 { | true -> print_int(1) | _ -> print_int(2) }");
+        assert_eq!(
+            errors,
+            ["Literal pattern true is bool, but the value is int"]
+        );
+        // A variant pattern may not match, so a let cannot use one
+        let errors =
+            diagnostics("type Option = 'None + 'Some int; let o Option = 'None; let 'Some n = o;");
+        assert_eq!(errors[0].code, "E0201");
+    }
+
+    #[test]
+    fn test_match_arms_and_definite_assignment() {
+        let diagnostics = diagnostics(
+            "type Option = 'None + 'Some int;
+             function get(o Option) int {
+                 let n int;
+                 match o {
+                 | 'Some m -> n = m
+                 | 'None -> print_int(1)
+                 }
+                 return n;
+             }",
+        );
+        assert_eq!(diagnostics[0].code, "E0400");
+        assert!(
+            diagnostics[0].notes[0].contains("the 'match' at 4:18 takes the arm at 6:20"),
+            "{:?}",
+            diagnostics[0].notes
+        );
+        // Names bound by an arm's pattern are only visible in that arm
+        let errors = check(
+            "type Option = 'None + 'Some int;
+             let o Option = 'None;
+             match o { | 'Some m -> print_int(m) | 'None -> print_int(m) }",
+        );
+        assert_eq!(errors, ["Use of undeclared variable 'm'"]);
+    }
+
+    #[test]
+    fn test_error_contract() {
+        let errors = check(&format!(
+            "{}
+             function log_error(err Error) {{
+                 print_int(err.get_error_id());
+             }}
+             function main() {{
+                 match divide(1, 2) {{
+                 | 'Err err -> {{
+                     log_error(err);
+                     print_int(err.dividend);
+                 }}
+                 | 'Ok value -> print_int(value)
+                 }}
+             }}",
+            DIVISION_ERROR
+        ));
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = diagnostics(
+            "type E = ('code int ; Error { function get_error_id(self) int { return 1; } })",
+        );
+        assert_eq!(errors[0].code, "E0007");
+        assert_eq!(
+            errors[0].message,
+            "Type 'E' signs contract 'Error' but does not define its method 'get_error_message'"
+        );
+        let errors = check(
+            "type E = ('code int ; Error {
+                 function get_error_id(self) bool { return true; }
+                 function get_error_message(self) string { return \"e\"; }
+             })",
+        );
+        assert_eq!(
+            errors,
+            [
+                "Method 'get_error_id' of 'E' does not match contract 'Error': it must be () -> int, but is () -> bool"
+            ]
+        );
+        let errors =
+            check("type E = ('code int ; Printable { function show(self) int { return 1; } })");
+        assert_eq!(errors, ["Unknown contract 'Printable'"]);
+        // Only types that sign the contract are accepted where it is expected
+        let errors =
+            check("type Plain = ('code int); function log(err Error) {} log({ code = 1 });");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Type mismatch in argument 1 of 'log'"));
+        let errors = check(&format!(
+            "{} function f(e DivisionByZeroError) int {{ return e.get_size(); }}",
+            DIVISION_ERROR
+        ));
+        assert_eq!(
+            errors,
+            ["Type DivisionByZeroError has no method 'get_size'"]
+        );
+        let errors = check("function f(self) {}");
+        assert_eq!(
+            errors,
+            ["Parameter 'self' of 'f' has no type: only the 'self' of a method can leave it out"]
+        );
+    }
+
+    #[test]
+    fn test_error_propagation() {
+        let errors = check(&format!(
+            "{}
+             type Quotients = 'Ok (int * int) + 'Err Error;
+             function both(a int, b int) Quotients {{
+                 let first int = divide(a, b)?;
+                 let second int = divide(b, a)?;
+                 return 'Ok (first, second);
+             }}",
+            DIVISION_ERROR
+        ));
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = diagnostics(&format!(
+            "{} function f(a int) int {{ let q int = divide(a, a)?; return q; }}",
+            DIVISION_ERROR
+        ));
+        assert_eq!(errors[0].code, "E0109");
+        assert_eq!(
+            errors[0].message,
+            "'?' returns the 'Err of DivideResult, but function 'f' returns int"
+        );
+        let errors = diagnostics(
+            "type Option = 'None + 'Some int;
+             function f(o Option) Option { let n int = o?; return 'Some n; }",
+        );
+        assert_eq!(errors[0].code, "E0109");
+    }
+
+    #[test]
+    fn test_panic_diverges() {
+        let errors = check(
+            "function must(b bool) int {
+                 if b {
+                     return 1;
+                 }
+                 panic \"not true\";
+             }
+             function pick(b bool) int {
+                 let n int;
+                 if b {
+                     n = 1;
+                 } else {
+                     panic;
+                 }
+                 return n;
+             }
+             let never int = panic \"no value\";",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let warnings = warnings("function f() int { panic; return 1; }");
+        assert_eq!(warnings[0].code, "W0001");
+        assert_eq!(
+            warnings[0].labels[0].message,
+            "this statement always returns or panics"
+        );
+        let errors = check("function f() { panic 3; }");
+        assert_eq!(
+            errors,
+            ["The message of 'panic' must be a string, but it is int"]
+        );
+    }
+
+    #[test]
+    fn test_for_loops() {
+        let errors = check(
+            "function consume(p unique &int) {}
+             function make() unique &int {
+                 let x int = 1;
+                 return &x;
+             }
+             function f(n int, p unique &int, q &int) int {
+                 let i int = 1;
+                 let total int = 1;
+                 for i < n {
+                     i++;
+                     p = make();
+                     consume(p);
+                 }
+                 for q != null {
+                     total += deref q;
+                     q = null;
+                 }
+                 return total;
+             }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = check("for 1 { }");
+        assert_eq!(errors, ["For condition has to be boolean"]);
+        // A later run of the body starts from what the earlier one left
+        let moved = diagnostics(
+            "function consume(p unique &int) {}
+             function f(c bool, p unique &int) {
+                 for c {
+                     consume(p);
+                 }
+             }",
+        );
+        assert_eq!(moved.len(), 1, "{:?}", moved);
+        assert_eq!(moved[0].message, "Use of moved value 'p'");
+        let errors = check(
+            "function f(c bool) int {
+                 let x int = 1;
+                 let p &int = &x;
+                 for c {
+                     let n int = deref p;
+                     p = null;
+                 }
+                 return 1;
+             }
+             function g(c bool) int {
+                 let x int;
+                 for c {
+                     x = 1;
+                 }
+                 return x;
+             }",
+        );
+        assert_eq!(
+            errors,
+            [
+                "Cannot dereference 'p': it may be null",
+                "Variable 'x' is read before it is definitely assigned"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unique_pointers_move() {
+        let errors = check(
+            "function take(p unique &int) int {
+                 return deref p;
+             }
+             function main() {
+                 let x int = 5;
+                 let p unique &int = addressof x;
+                 let q unique &int = p;
+                 p = q;
+                 let n int = take(p) + 1;
+             }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let moved = diagnostics(
+            "function main() {
+                 let x int = 5;
+                 let p unique &int = &x;
+                 let q unique &int = p;
+                 let n int = deref p;
+             }",
+        );
+        assert_eq!(moved[0].message, "Use of moved value 'p'");
+        assert_eq!(moved[0].code, "E0402");
+        assert_eq!(moved[0].labels[0].message, "value moved here");
+        // A value holding a unique pointer moves along with it
+        let errors = check(
+            "type Node = ('value int * 'next unique &int);
+             function main(c bool) {
+                 let x int = 5;
+                 let p unique &int = &x;
+                 if c {
+                     let n Node = { value = 1, next = p };
+                 }
+                 let again = (p, 1);
+             }",
+        );
+        assert_eq!(errors, ["Use of moved value 'p'"]);
+        let nested = diagnostics(
+            "type Node = ('value int * 'next unique &int);
+             function main(c bool) {
+                 let x int = 5;
+                 let n Node = { value = 1, next = &x };
+                 let m = n;
+                 let o Node = n;
+             }",
+        );
+        assert!(
+            nested[0].notes[0].contains("'n' is moved at 5:26"),
+            "{:?}",
+            nested[0].notes
+        );
+        // The path lists its steps in source order
+        let branch = diagnostics(
+            "function main(c bool) {
+                 let x int = 5;
+                 let p unique &int = &x;
+                 if c {
+                     let q unique &int = p;
+                 }
+                 let r unique &int = p;
+             }",
+        );
+        assert_eq!(
+            branch[0].notes,
+            [
+                "path: the condition of the 'if' at 4:18 is true -> 'p' is moved at 5:42 -> 'p' is read at 7:38"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_double_ownership() {
+        let diagnostics = diagnostics(
+            "let x int = 5;
+             let p unique &int = addressof x;
+             let q shared &int = &x;",
+        );
+        assert_eq!(diagnostics[0].code, "E0403");
+        assert_eq!(
+            diagnostics[0].message,
+            "'x' is already owned by the unique pointer at 2:34"
+        );
+        // A raw pointer does not own what it points to
+        let errors = check(
+            "let x int = 5;
+             let r &int = &x;
+             let p unique &int = &x;",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_shared_and_weak_pointers() {
+        let errors = check(
+            "let x int = 5;
+             let p shared &int = &x;
+             let q shared &int = p;
+             let w weak &int = q;
+             let n int = deref p + deref q;",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        let errors = check(
+            "let x int = 5;
+             let p shared &int = &x;
+             let w weak &int = p;
+             let n int = deref w;",
+        );
+        assert_eq!(
+            errors,
+            [
+                "Cannot dereference a value of type weak &int: a weak pointer must be upgraded to a shared pointer first"
+            ]
+        );
+        let errors = check(
+            "let x int = 5;
+             let w weak &int = &x;",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("declared as weak &int"), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_null_checks_narrow_raw_pointers() {
+        let errors = check(
+            "function early(p &int) int {
+                 if p == null {
+                     return 1;
+                 }
+                 return deref p;
+             }
+             function inside(p &int) int {
+                 if p != null {
+                     return deref p;
+                 }
+                 return 2;
+             }
+             function marked(@nonnull p &int) int {
+                 return deref p;
+             }
+             function main() {
+                 let x int = 5;
+                 let p &int = &x;
+                 let q &int = null;
+                 let n int = early(q) + inside(q) + marked(p) + deref p;
+             }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let diagnostics = diagnostics(
+            "function f(p &int) int {
+                 if p != null {
+                     let n int = deref p;
+                 }
+                 return deref p;
+             }",
+        );
+        assert_eq!(diagnostics[0].code, "E0404");
+        assert_eq!(
+            diagnostics[0].message,
+            "Cannot dereference 'p': it may be null"
+        );
+        let errors = check(
+            "let x int = 5;
+             let p &int = &x;
+             p = null;
+             let n int = deref p;",
+        );
+        assert_eq!(errors, ["Cannot dereference 'p': it may be null"]);
+        let errors = check("@nonnull let p &int = null;");
+        assert_eq!(
+            errors,
+            ["A variable declared @nonnull cannot be initialized with null"]
+        );
+        let errors = check("let p = null;");
+        assert_eq!(
+            errors,
+            ["Cannot infer the pointer type of 'p' from null: add a type annotation"]
+        );
+        // null fits every pointer type, and nothing else
+        let errors = check(
+            "let p unique &int = null;
+             let w weak &int = null;
+             let n int = null;",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("but got null"), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_null_facts_end_with_their_branch() {
+        for src in [
+            "function f(c bool, q &int) int {
+                 let x int = 1;
+                 let p &int = q;
+                 if c {
+                     p = &x;
+                 }
+                 return deref p;
+             }",
+            "function g(p &int) int {
+                 if p != null {
+                     p = null;
+                 } else {
+                     return 1;
+                 }
+                 return deref p;
+             }",
+            "function h(p &int) int {
+                 if p == null {
+                     return 1;
+                 } else {
+                     p = null;
+                 }
+                 return deref p;
+             }",
+        ] {
+            let diagnostics = diagnostics(src);
+            assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+            assert_eq!(diagnostics[0].code, "E0404");
+            assert_eq!(
+                diagnostics[0].message,
+                "Cannot dereference 'p': it may be null"
+            );
+        }
+
+        // What every branch that falls through proves still holds
+        let errors = check(
+            "function f(c bool, q &int) int {
+                 let x int = 1;
+                 let y int = 2;
+                 let p &int = q;
+                 if c {
+                     p = &x;
+                 } else {
+                     p = &y;
+                 }
+                 return deref p;
+             }
+             function g(p &int) int {
+                 if p != null {
+                     let n int = 1;
+                 } else {
+                     return 1;
+                 }
+                 return deref p;
+             }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_store_through_pointer() {
+        let errors = check(
+            "function f(p &int, @nonnull q &int, u unique &int) {
+                 deref q = 1;
+                 deref u = 2;
+                 if p != null {
+                     deref p = 3;
+                 }
+             }",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        // A write needs the same proof that the pointer is not null as a read
+        let diagnostics = diagnostics(
+            "function f(p &int) {
+                 deref p = 1;
+             }",
+        );
+        assert_eq!(diagnostics[0].code, "E0404");
+        assert_eq!(
+            diagnostics[0].message,
+            "Cannot dereference 'p': it may be null"
+        );
+        let errors = check(
+            "let x int = 5;
+             let p &int = &x;
+             deref p = true;
+             let w weak &int = null;
+             deref w = 1;",
+        );
+        assert_eq!(
+            errors,
+            [
+                "Type mismatch in write through a pointer: it points to int, but got bool",
+                "Cannot dereference a value of type weak &int: a weak pointer must be upgraded to a shared pointer first",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pointer_arithmetic_only_on_raw_pointers() {
+        let errors = check(
+            "let x int = 5;
+             let p &int = &x;
+             let second &int = p + 1;
+             let first &int = second - 1;
+             let same bool = first == p;",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        let errors = check(
+            "let x int = 5;
+             let p unique &int = &x;
+             let q unique &int = p + 1;",
+        );
+        assert_eq!(
+            errors,
+            [
+                "Pointer arithmetic is only allowed on raw pointers, but the left operand of '+' is unique &int"
+            ]
+        );
+        let errors = check(
+            "let x int = 5;
+             let p &int = &x;
+             let q &int = p + 1.5;",
+        );
+        assert_eq!(
+            errors,
+            ["The offset added to a pointer with '+' must be int, but it is float"]
+        );
+    }
+}
@@ -0,0 +1,2861 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    diagnostic::Diagnostic,
+    hir::{
+        self, Annotations, Hir,
+        hir::{BindingId, CastKind, Resolution},
+    },
+    parser::{
+        Ast, ContractImplementation, Expression, ExpressionKind, Function, FunctionBody,
+        FunctionParameter, FunctionSignature, Hint, HintPlacement, Lambda, MatchArm, Pattern,
+        PatternKind, Statement, StatementKind, VariableDeclaration,
+    },
+    token::{Literal, Operator, Ownership, Span, TypeDefinitions, TypeIdentifier},
+    type_checker::{
+        captures, casts,
+        contracts::{self, Contract},
+        control_flow::{always_returns, assigned_names, fall_through_path, unreachable_statements},
+        definite_assignment::Unassigned,
+        exhaustiveness, hint_facts, operators,
+        scope::Scopes,
+    },
+};
+
+pub struct TypeChecker<'a> {
+    ast: &'a Ast,
+    // Variables visible at the statement being checked, innermost block last
+    scopes: Scopes,
+    functions: HashMap<String, FunctionSignature>,
+    // What each named type stands for
+    named_types: TypeDefinitions,
+    // The contracts types can sign, by name
+    contracts: HashMap<String, Contract>,
+    // The contracts each named type signs
+    signed: HashMap<String, Vec<String>>,
+    // The methods of each named type, by type and method name, 'self' included
+    methods: HashMap<(String, String), FunctionSignature>,
+    // Hints known to hold for each variable, used to prove parameter requirements
+    facts: HashMap<String, Vec<Hint>>,
+    // Promise hints the function being checked made about its parameters
+    promises: HashMap<String, Vec<Hint>>,
+    // How many function or lambda bodies enclose the statement being checked
+    function_depth: usize,
+    // Capture analysis result of every lambda, in the order they were checked
+    lambdas: Vec<LambdaKind>,
+    // Functions that have a forward declaration but no definition
+    undefined_functions: HashSet<String>,
+    // Variables declared with a hint that forbids writing to them
+    immutable: HashSet<String>,
+    // Variables that may not have a value yet, or whose value moved out
+    unassigned: Unassigned,
+    // Variables a unique or shared pointer took the address of, with its
+    // ownership and where it was taken
+    owners: HashMap<BindingId, (Ownership, Span)>,
+    // Name and return type of the function whose body is being checked
+    current_function: Option<(String, TypeIdentifier)>,
+    // Errors of the statements checked so far. An error ends the check of its
+    // statement, but not of the statements after it.
+    errors: Vec<Diagnostic>,
+    warnings: Vec<Diagnostic>,
+    // Types, bindings and name resolutions found so far, the input to lowering
+    annotations: Annotations,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LambdaKind {
+    // Reads nothing from an enclosing function
+    Pure,
+    // Reads locals of an enclosing function, captured by value on creation
+    Closure { captures: Vec<String> },
+}
+
+// Boxed so results stay small on the happy path
+type TypeCheckerResult<T> = Result<T, Box<Diagnostic>>;
+
+fn load_std_functions() -> HashMap<String, FunctionSignature> {
+    let mut function_signatures = HashMap::new();
+
+    // print_int(n int) -> None (accept any type)
+    let print_param = FunctionParameter {
+        parameter_name: "n".to_string(),
+        parameter_type: Some(TypeIdentifier::Number),
+        hints: Vec::new(),
+        span: Span::default(),
+    };
+    let print_signature = FunctionSignature {
+        name: "print_int".to_string(),
+        parameters: vec![print_param.clone()],
+        return_type: None,
+    };
+    function_signatures.insert("print_int".to_string(), print_signature);
+
+    // println(v) -> None (accept any type)
+    let println_signature = FunctionSignature {
+        name: "println".to_string(),
+        parameters: vec![print_param],
+        return_type: None,
+    };
+    function_signatures.insert("println".to_string(), println_signature);
+
+    // read_int() -> Number
+    let read_int_signature = FunctionSignature {
+        name: "read_int".to_string(),
+        parameters: vec![],
+        return_type: Some(TypeIdentifier::Number),
+    };
+    function_signatures.insert("read_int".to_string(), read_int_signature);
+
+    // read_char() -> Char
+    let read_char_signature = FunctionSignature {
+        name: "read_char".to_string(),
+        parameters: vec![],
+        return_type: Some(TypeIdentifier::Char),
+    };
+    function_signatures.insert("read_char".to_string(), read_char_signature);
+
+    function_signatures
+}
+
+/// The type of a parameter. Named functions always spell it out and lambda
+/// parameters are filled in by inference before they are used.
+fn parameter_type(parameter: &FunctionParameter) -> TypeIdentifier {
+    parameter
+        .parameter_type
+        .clone()
+        .unwrap_or(TypeIdentifier::UserDefinedType)
+}
+
+/// Fill in the types a lambda leaves out from the function type its context
+/// expects. Annotated parameters are kept as written.
+fn infer_parameters(
+    lambda: &Lambda,
+    expected: Option<&TypeIdentifier>,
+) -> TypeCheckerResult<Vec<FunctionParameter>> {
+    let expected = match expected {
+        Some(TypeIdentifier::Function { parameters, .. })
+            if parameters.len() == lambda.parameters.len() =>
+        {
+            Some(parameters)
+        }
+        _ => None,
+    };
+    let mut parameters = lambda.parameters.clone();
+    for (i, parameter) in parameters.iter_mut().enumerate() {
+        if parameter.parameter_type.is_some() {
+            continue;
+        }
+        let Some(expected) = expected else {
+            return Err(Diagnostic::error(
+                "E0102",
+                format!(
+                    "Cannot infer the type of parameter '{}': annotate it or use the lambda where a function type of {} parameters is expected",
+                    parameter.parameter_name,
+                    lambda.parameters.len()
+                ),
+                parameter.span,
+            ).into());
+        };
+        parameter.parameter_type = Some(expected[i].clone());
+    }
+    Ok(parameters)
+}
+
+/// The first field of a struct literal or labeled pattern whose label was
+/// already used by an earlier field.
+fn repeated_label<T>(fields: &[(String, T)]) -> Option<&(String, T)> {
+    fields
+        .iter()
+        .enumerate()
+        .find(|(i, (label, _))| fields[..*i].iter().any(|(previous, _)| previous == label))
+        .map(|(_, field)| field)
+}
+
+/// The type of a literal value.
+fn literal_type(literal: &Literal) -> TypeIdentifier {
+    match literal {
+        Literal::Integer(_) => TypeIdentifier::Number,
+        Literal::Float(_) => TypeIdentifier::Float,
+        Literal::Boolean(_) => TypeIdentifier::Boolean,
+        Literal::Character(_) => TypeIdentifier::Char,
+        Literal::String(_) => TypeIdentifier::String,
+        Literal::Unit => TypeIdentifier::Unit,
+        Literal::Null => TypeIdentifier::Null,
+    }
+}
+
+/// The variable a condition compares with null, and whether the condition
+/// holding means the variable is not null.
+fn null_test(condition: &Expression) -> Option<(&String, bool)> {
+    let ExpressionKind::Binary {
+        left,
+        operator,
+        right,
+    } = &condition.kind
+    else {
+        return None;
+    };
+    let nonnull_when_true = match operator {
+        Operator::Different | Operator::StrictDifferent => true,
+        Operator::Equals | Operator::StrictEquals => false,
+        _ => return None,
+    };
+    match (&left.kind, &right.kind) {
+        (ExpressionKind::Identifier(name), ExpressionKind::Literal(Literal::Null))
+        | (ExpressionKind::Literal(Literal::Null), ExpressionKind::Identifier(name)) => {
+            Some((name, nonnull_when_true))
+        }
+        _ => None,
+    }
+}
+
+/// The facts known on both of two paths. A fact is the same on both when it
+/// comes from the same hint, assignment or null test, and not being null is
+/// the same fact wherever it was learned.
+fn common_facts(
+    left: &HashMap<String, Vec<Hint>>,
+    right: &HashMap<String, Vec<Hint>>,
+) -> HashMap<String, Vec<Hint>> {
+    left.iter()
+        .filter_map(|(name, left_facts)| {
+            let right_facts = right.get(name)?;
+            let common: Vec<Hint> = left_facts
+                .iter()
+                .filter(|fact| {
+                    right_facts.iter().any(|other| {
+                        other.name == fact.name
+                            && (other.span == fact.span || fact.name == "nonnull")
+                    })
+                })
+                .cloned()
+                .collect();
+            (!common.is_empty()).then(|| (name.clone(), common))
+        })
+        .collect()
+}
+
+/// Whether `expr` makes a new value rather than naming a variable, such as a
+/// literal or the result of a call. Nothing else can write to that value.
+fn is_temporary(expr: &Expression) -> bool {
+    match &expr.kind {
+        ExpressionKind::Grouping(expression) | ExpressionKind::Hinted { expression, .. } => {
+            is_temporary(expression)
+        }
+        ExpressionKind::Identifier(_) => false,
+        _ => true,
+    }
+}
+
+/// The type a function returns. Leaving the return type out means unit.
+fn return_type(declared: &Option<TypeIdentifier>) -> TypeIdentifier {
+    declared.clone().unwrap_or(TypeIdentifier::Unit)
+}
+
+/// The type of a method as its callers see it, 'self' left out.
+fn method_type(signature: &FunctionSignature) -> TypeIdentifier {
+    TypeIdentifier::Function {
+        parameters: signature.parameters[1..]
+            .iter()
+            .map(parameter_type)
+            .collect(),
+        return_type: Box::new(return_type(&signature.return_type)),
+    }
+}
+
+/// The type of a named function used as a value.
+fn function_type(signature: &FunctionSignature) -> TypeIdentifier {
+    TypeIdentifier::Function {
+        parameters: signature.parameters.iter().map(parameter_type).collect(),
+        return_type: Box::new(return_type(&signature.return_type)),
+    }
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(ast: &'a Ast) -> Self {
+        let std_functions = load_std_functions();
+        Self {
+            ast,
+            scopes: Scopes::default(),
+            functions: std_functions,
+            named_types: HashMap::new(),
+            contracts: contracts::builtin_contracts(),
+            signed: HashMap::new(),
+            methods: HashMap::new(),
+            facts: HashMap::new(),
+            promises: HashMap::new(),
+            function_depth: 0,
+            lambdas: Vec::new(),
+            undefined_functions: HashSet::new(),
+            immutable: HashSet::new(),
+            unassigned: Unassigned::default(),
+            owners: HashMap::new(),
+            current_function: None,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            annotations: Annotations::default(),
+        }
+    }
+
+    /// Check the whole program, returning its errors and warnings in source order.
+    pub fn check_ast(&mut self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.check_program();
+        diagnostics.append(&mut self.warnings);
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.span.line, diagnostic.span.column));
+        diagnostics
+    }
+
+    /// The typed, name-resolved form of the program. Only meaningful once
+    /// `check_ast` has reported no errors.
+    pub fn lower(&self) -> Hir {
+        hir::lower(self.ast, &self.annotations)
+    }
+
+    /// Warnings found by the checks run so far.
+    #[allow(dead_code)]
+    pub(crate) fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    /// Capture analysis of every lambda checked so far.
+    #[allow(dead_code)]
+    pub(crate) fn lambdas(&self) -> &[LambdaKind] {
+        &self.lambdas
+    }
+
+    pub(crate) fn check_program(&mut self) -> Vec<Diagnostic> {
+        let mut errors = self.collect_types();
+        errors.extend(self.collect_methods());
+        errors.extend(self.collect_signatures());
+        self.check_statements(&self.ast.statements);
+        errors.append(&mut self.errors);
+        errors
+    }
+
+    /// Register every named type before checking anything else, so they can
+    /// be used anywhere, including in the definitions of the types declared
+    /// before them. A type may refer to itself only through a pointer or a
+    /// function, since a value cannot hold a value of its own type.
+    fn collect_types(&mut self) -> Vec<Diagnostic> {
+        let mut errors = Vec::new();
+        let mut declared = Vec::new();
+        for statement in &self.ast.statements {
+            let mut statement = statement;
+            while let StatementKind::Hinted {
+                statement: inner, ..
+            } = &statement.kind
+            {
+                statement = inner;
+            }
+            let StatementKind::TypeDeclaration {
+                name, definition, ..
+            } = &statement.kind
+            else {
+                continue;
+            };
+            if self.named_types.contains_key(name) {
+                errors.push(Diagnostic::error(
+                    "E0002",
+                    format!("Type '{}' is declared more than once", name),
+                    statement.span,
+                ));
+                continue;
+            }
+            if self.contracts.contains_key(name) {
+                errors.push(Diagnostic::error(
+                    "E0002",
+                    format!("Type '{}' has the name of a contract", name),
+                    statement.span,
+                ));
+                continue;
+            }
+            if let TypeIdentifier::Sum(variants) = definition
+                && let Some((label, _)) = repeated_label(variants)
+            {
+                errors.push(Diagnostic::error(
+                    "E0202",
+                    format!("Variant '{} is given more than once", label),
+                    statement.span,
+                ));
+                continue;
+            }
+            self.named_types.insert(name.clone(), definition.clone());
+            declared.push((name, definition, statement.span));
+        }
+        for (_, definition, span) in &declared {
+            if let Err(err) = self.check_known_type(definition, *span) {
+                errors.push(*err);
+            }
+        }
+        for (name, _, span) in declared {
+            if let Some(cycle) = self.containment_cycle(name) {
+                let mut diagnostic =
+                    Diagnostic::error("E0008", format!("Type '{}' contains itself", name), span);
+                if cycle.len() > 1 {
+                    let path: Vec<String> =
+                        cycle.iter().map(|name| format!("'{}'", name)).collect();
+                    diagnostic = diagnostic.with_note(format!(
+                        "{} contains {}, which contains {}",
+                        path[0],
+                        path[1..].join(", which contains "),
+                        path[0]
+                    ));
+                }
+                errors.push(diagnostic.with_note(format!(
+                    "refer to it through a pointer, such as unique &{}",
+                    name
+                )));
+                // Leaving it out breaks the cycle for the other types in it
+                self.named_types.remove(name);
+            }
+        }
+        errors
+    }
+
+    /// The named types a value of type `name` holds one after the other until
+    /// it holds a `name` again, starting with `name`, if it does.
+    fn containment_cycle<'b>(&'b self, name: &'b String) -> Option<Vec<&'b String>> {
+        fn search<'b>(
+            named_types: &'b TypeDefinitions,
+            target: &String,
+            path: &mut Vec<&'b String>,
+        ) -> bool {
+            let Some(definition) = named_types.get(*path.last().unwrap()) else {
+                return false;
+            };
+            for contained in definition.contained_names() {
+                if contained == target {
+                    return true;
+                }
+                if path.contains(&contained) {
+                    continue;
+                }
+                path.push(contained);
+                if search(named_types, target, path) {
+                    return true;
+                }
+                path.pop();
+            }
+            false
+        }
+        let mut path = vec![name];
+        search(&self.named_types, name, &mut path).then_some(path)
+    }
+
+    /// Register the methods of every type that signs a contract, checking
+    /// that they are the methods the contract requires. Runs once every type
+    /// is known, so methods may mention types declared after theirs.
+    fn collect_methods(&mut self) -> Vec<Diagnostic> {
+        let mut errors = Vec::new();
+        for statement in &self.ast.statements {
+            let mut statement = statement;
+            while let StatementKind::Hinted {
+                statement: inner, ..
+            } = &statement.kind
+            {
+                statement = inner;
+            }
+            let StatementKind::TypeDeclaration {
+                name, contracts, ..
+            } = &statement.kind
+            else {
+                continue;
+            };
+            for implementation in contracts {
+                if let Err(err) = self.collect_contract_methods(name, implementation) {
+                    errors.push(*err);
+                }
+            }
+        }
+        errors
+    }
+
+    fn collect_contract_methods(
+        &mut self,
+        owner: &str,
+        implementation: &ContractImplementation,
+    ) -> TypeCheckerResult<()> {
+        let contract_name = &implementation.contract;
+        let Some(contract) = self.contracts.get(contract_name).cloned() else {
+            return Err(Diagnostic::error(
+                "E0007",
+                format!("Unknown contract '{}'", contract_name),
+                implementation.span,
+            )
+            .into());
+        };
+        let signed = self.signed.entry(owner.to_string()).or_default();
+        if signed.contains(contract_name) {
+            return Err(Diagnostic::error(
+                "E0002",
+                format!(
+                    "Type '{}' signs contract '{}' more than once",
+                    owner, contract_name
+                ),
+                implementation.span,
+            )
+            .into());
+        }
+        signed.push(contract_name.clone());
+        for method in &implementation.methods {
+            let signature = &method.signature;
+            if !matches!(method.body, FunctionBody::Statements(_)) {
+                return Err(Diagnostic::error(
+                    "E0007",
+                    format!("Method '{}' of '{}' needs a body", signature.name, owner),
+                    method.span,
+                )
+                .into());
+            }
+            if signature
+                .parameters
+                .first()
+                .is_none_or(|receiver| receiver.parameter_name != "self")
+            {
+                return Err(Diagnostic::error(
+                    "E0007",
+                    format!(
+                        "Method '{}' of '{}' must take 'self' as its first parameter",
+                        signature.name, owner
+                    ),
+                    method.span,
+                )
+                .into());
+            }
+            self.check_signature_types(&signature.parameters, &signature.return_type, method.span)?;
+            let Some((_, required)) = contract.iter().find(|(name, _)| *name == signature.name)
+            else {
+                return Err(Diagnostic::error(
+                    "E0007",
+                    format!(
+                        "Contract '{}' has no method '{}'",
+                        contract_name, signature.name
+                    ),
+                    method.span,
+                )
+                .into());
+            };
+            let defined = method_type(signature);
+            if !self.equivalent(required, &defined) {
+                return Err(Diagnostic::error(
+                    "E0007",
+                    format!(
+                        "Method '{}' of '{}' does not match contract '{}': it must be {}, but is {}",
+                        signature.name, owner, contract_name, required, defined
+                    ),
+                    method.span,
+                )
+                .into());
+            }
+            let key = (owner.to_string(), signature.name.clone());
+            if self.methods.insert(key, signature.clone()).is_some() {
+                return Err(Diagnostic::error(
+                    "E0002",
+                    format!(
+                        "Method '{}' of '{}' is defined more than once",
+                        signature.name, owner
+                    ),
+                    method.span,
+                )
+                .into());
+            }
+        }
+        if let Some((missing, _)) = contract.iter().find(|(name, _)| {
+            !implementation
+                .methods
+                .iter()
+                .any(|method| method.signature.name == *name)
+        }) {
+            return Err(Diagnostic::error(
+                "E0007",
+                format!(
+                    "Type '{}' signs contract '{}' but does not define its method '{}'",
+                    owner, contract_name, missing
+                ),
+                implementation.span,
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Check that every named type `annotation` mentions has been declared,
+    /// as a type or as a contract.
+    fn check_known_type(&self, annotation: &TypeIdentifier, span: Span) -> TypeCheckerResult<()> {
+        match annotation.names().into_iter().find(|name| {
+            !self.named_types.contains_key(*name) && !self.contracts.contains_key(*name)
+        }) {
+            Some(name) => {
+                Err(Diagnostic::error("E0005", format!("Unknown type '{}'", name), span).into())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Check the parameter and return types a function or lambda spells out.
+    fn check_signature_types(
+        &self,
+        parameters: &[FunctionParameter],
+        declared_return_type: &Option<TypeIdentifier>,
+        span: Span,
+    ) -> TypeCheckerResult<()> {
+        for parameter in parameters {
+            if let Some(parameter_type) = &parameter.parameter_type {
+                self.check_known_type(parameter_type, parameter.span)?;
+            }
+        }
+        if let Some(declared_return_type) = declared_return_type {
+            self.check_known_type(declared_return_type, span)?;
+        }
+        Ok(())
+    }
+
+    /// Type equivalence as assignment and `==` see it, named types standing
+    /// for their definition.
+    fn equivalent(&self, left: &TypeIdentifier, right: &TypeIdentifier) -> bool {
+        left.is_equivalent(right, &self.named_types)
+    }
+
+    /// Whether a value of type `actual` can be stored where `expected` is
+    /// required: an equivalent type, any type that signs the contract
+    /// `expected` names, or never, which has no values to store.
+    fn assignable(&self, expected: &TypeIdentifier, actual: &TypeIdentifier) -> bool {
+        if self.equivalent(expected, actual) || *actual == TypeIdentifier::Never {
+            return true;
+        }
+        if *actual == TypeIdentifier::Null
+            && matches!(
+                self.underlying(expected),
+                TypeIdentifier::Pointer(_) | TypeIdentifier::SafePointer(..)
+            )
+        {
+            return true;
+        }
+        // A weak pointer is made from a shared one, without counting as an owner
+        if let (
+            TypeIdentifier::SafePointer(Ownership::Weak, weak),
+            TypeIdentifier::SafePointer(Ownership::Shared, shared),
+        ) = (self.underlying(expected), self.underlying(actual))
+        {
+            return self.equivalent(&weak, &shared);
+        }
+        let TypeIdentifier::Named(contract) = expected else {
+            return false;
+        };
+        let mut current = actual;
+        while let TypeIdentifier::Named(name) = current {
+            if self
+                .signed
+                .get(name)
+                .is_some_and(|signed| signed.contains(contract))
+            {
+                return true;
+            }
+            match self.named_types.get(name) {
+                Some(definition) => current = definition,
+                None => break,
+            }
+        }
+        false
+    }
+
+    /// The structure of a type with the names at its head looked through.
+    fn underlying(&self, named: &TypeIdentifier) -> TypeIdentifier {
+        named.underlying(&self.named_types).clone()
+    }
+
+    /// Register every top-level function signature before checking any body, so
+    /// functions can be called before they are defined.
+    fn collect_signatures(&mut self) -> Vec<Diagnostic> {
+        let mut errors = Vec::new();
+        let mut defined = HashSet::new();
+        let mut declared = HashSet::new();
+        for statement in &self.ast.statements {
+            let StatementKind::FunctionDeclaration(function) = &statement.kind else {
+                continue;
+            };
+            let name = &function.signature.name;
+            let is_definition = matches!(function.body, FunctionBody::Statements(_));
+            if is_definition && !defined.insert(name.clone()) {
+                errors.push(Diagnostic::error(
+                    "E0002",
+                    format!("Function '{}' is defined more than once", name),
+                    function.span,
+                ));
+                continue;
+            }
+            if let Some(previous) = self.functions.get(name)
+                && declared.contains(name)
+            {
+                let previous_type = function_type(previous);
+                let current_type = function_type(&function.signature);
+                if previous_type != current_type {
+                    errors.push(Diagnostic::error("E0003", format!(
+                            "Declarations of function '{}' do not match: declared as {}, but also as {}",
+                            name, previous_type, current_type
+                        ), function.span));
+                    continue;
+                }
+            }
+            declared.insert(name.clone());
+            // The definition's signature wins, it carries the parameter hints that are checked
+            if is_definition || !defined.contains(name) {
+                self.functions
+                    .insert(name.clone(), function.signature.clone());
+            }
+        }
+        self.undefined_functions = declared.difference(&defined).cloned().collect();
+        errors
+    }
+
+    fn check_statement(&mut self, statement: &Statement) -> TypeCheckerResult<()> {
+        match &statement.kind {
+            StatementKind::VariableDeclaration(var_decl) => self.check_var_decl(var_decl),
+            StatementKind::Expression(expr) => self.check_expr(expr).map(|_| ()),
+            StatementKind::Assignment { identifier, expr } => {
+                self.check_assignment(identifier, expr, statement.span)
+            }
+            StatementKind::Store { target, expr } => self.check_store(target, expr),
+            StatementKind::CompoundAssignment {
+                identifier,
+                operator,
+                expr,
+            } => self.check_compound_assignment(identifier, *operator, expr, statement.span),
+            StatementKind::IncrementDecrement {
+                identifier,
+                operator,
+            } => self.check_increment_decrement(identifier, *operator, statement.span),
+            StatementKind::FunctionDeclaration(func_decl) => self.check_func_decl(func_decl),
+            // Registered up front, only top-level declarations are allowed
+            StatementKind::TypeDeclaration {
+                name, contracts, ..
+            } => {
+                if !self.scopes.at_top_level() {
+                    return Err(Diagnostic::error(
+                        "E0006",
+                        format!("Type '{}' must be declared at the top level", name),
+                        statement.span,
+                    )
+                    .into());
+                }
+                for method in contracts.iter().flat_map(|contract| &contract.methods) {
+                    if let FunctionBody::Statements(body) = &method.body {
+                        self.check_callable(
+                            &format!("{}.{}", name, method.signature.name),
+                            &method.signature.parameters,
+                            &method.signature.return_type,
+                            body,
+                            false,
+                            method.span,
+                        )?;
+                    }
+                }
+                Ok(())
+            }
+            StatementKind::Return(expr) => self.check_return(expr, statement.span),
+            StatementKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => self.check_if(condition, then_branch, else_branch, statement.span),
+            StatementKind::For { condition, body } => {
+                self.check_for(condition, body, statement.span)
+            }
+            StatementKind::Match { scrutinee, arms } => {
+                self.check_match(scrutinee, arms, statement.span)
+            }
+            StatementKind::Hinted { hints, statement } => {
+                match &statement.kind {
+                    StatementKind::VariableDeclaration(var_decl)
+                        if hints.iter().any(|hint| hint.name == "raw_cast") =>
+                    {
+                        self.check_raw_cast(var_decl)?
+                    }
+                    _ => self.check_statement(statement)?,
+                }
+                if let StatementKind::VariableDeclaration(var_decl) = &statement.kind {
+                    if let Some(nonnull) = hints.iter().find(|hint| hint.name == "nonnull")
+                        && let Some(expression) = &var_decl.expression
+                        && matches!(expression.kind, ExpressionKind::Literal(Literal::Null))
+                    {
+                        return Err(Diagnostic::error(
+                            "E0404",
+                            "A variable declared @nonnull cannot be initialized with null",
+                            expression.span,
+                        )
+                        .with_label(nonnull.span, "declared @nonnull here")
+                        .into());
+                    }
+                    // Hints on a destructuring let apply to every name it binds
+                    for name in var_decl.pattern.bindings() {
+                        if hints.iter().any(hint_facts::forbids_write) {
+                            self.immutable.insert(name.clone());
+                        }
+                        self.facts
+                            .entry(name.clone())
+                            .or_default()
+                            .extend(hints.iter().cloned());
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn check_if(
+        &mut self,
+        condition: &Expression,
+        then_branch: &[Statement],
+        else_branch: &Option<Vec<Statement>>,
+        span: Span,
+    ) -> TypeCheckerResult<()> {
+        let condition_type = self.check_expr(condition)?;
+        if !self.equivalent(&condition_type, &TypeIdentifier::Boolean) {
+            return Err(Diagnostic::error(
+                "E0101",
+                "If condition has to be boolean",
+                condition.span,
+            )
+            .into());
+        };
+        let null_test = null_test(condition);
+        let before = self.unassigned.clone();
+        // Each branch starts from the facts known before the 'if', and what
+        // it learns lasts only as long as the path through it
+        let facts_before = self.facts.clone();
+        self.narrow(null_test, true, condition.span);
+        self.check_block(then_branch)?;
+        let then_facts = std::mem::replace(&mut self.facts, facts_before.clone());
+        let then_state = std::mem::replace(&mut self.unassigned, before.clone());
+        let then_falls_through = !always_returns(then_branch);
+        let then_state = then_falls_through.then_some(then_state);
+        self.narrow(null_test, false, condition.span);
+        let else_state = match else_branch {
+            Some(else_statements) => {
+                self.check_block(else_statements)?;
+                (!always_returns(else_statements)).then(|| self.unassigned.clone())
+            }
+            None => Some(before.clone()),
+        };
+        let else_facts = std::mem::replace(&mut self.facts, facts_before);
+        // A fact holds after the 'if' when it holds at the end of every
+        // branch that falls through. A branch that reassigned the variable of
+        // the null test no longer holds what the test proved about it.
+        match (then_falls_through, else_state.is_some()) {
+            (true, true) => self.facts = common_facts(&then_facts, &else_facts),
+            (true, false) => self.facts = then_facts,
+            (false, true) => self.facts = else_facts,
+            (false, false) => {}
+        }
+        self.unassigned =
+            Unassigned::join(&before, then_state, else_state, else_branch.is_some(), span);
+        Ok(())
+    }
+
+    /// Check a for loop. Its body runs any number of times, none included,
+    /// and each run starts from what the runs before it left.
+    fn check_for(
+        &mut self,
+        condition: &Expression,
+        body: &[Statement],
+        span: Span,
+    ) -> TypeCheckerResult<()> {
+        // A later run cannot count on what an earlier one knew about the
+        // variables the body assigns
+        for name in assigned_names(body) {
+            self.facts.remove(name);
+        }
+        let condition_type = self.check_expr(condition)?;
+        if !self.equivalent(&condition_type, &TypeIdentifier::Boolean) {
+            return Err(Diagnostic::error(
+                "E0101",
+                "For condition has to be boolean",
+                condition.span,
+            )
+            .into());
+        };
+        let null_test = null_test(condition);
+        let facts_before = self.facts.clone();
+        let before = self.unassigned.clone();
+        self.narrow(null_test, true, condition.span);
+        self.check_block(body)?;
+        let after_body = std::mem::replace(&mut self.unassigned, before.clone());
+        if after_body.moved_since(&before) {
+            self.facts = facts_before.clone();
+            self.narrow(null_test, true, condition.span);
+            let entry = Unassigned::join_loop(&before, Some(after_body.clone()), span);
+            self.check_later_run(body, entry);
+        }
+        // The loop ends when its condition is false
+        self.facts = facts_before;
+        self.narrow(null_test, false, condition.span);
+        let after_body = (!always_returns(body)).then_some(after_body);
+        self.unassigned = Unassigned::join_loop(&before, after_body, span);
+        Ok(())
+    }
+
+    /// Check the body of a `for` once more, from `entry`, the
+    /// state a run after the first starts in, for uses of values an earlier
+    /// run moved out. Everything else this finds the first check found, so
+    /// it is dropped along with all this check learned.
+    fn check_later_run(&mut self, body: &[Statement], entry: Unassigned) {
+        let errors = self.errors.len();
+        let warnings = self.warnings.len();
+        let lambdas = self.lambdas.len();
+        let annotations = self.annotations.clone();
+        let owners = self.owners.clone();
+        let facts = self.facts.clone();
+        let immutable = self.immutable.clone();
+        let unassigned = std::mem::replace(&mut self.unassigned, entry);
+        if let Err(err) = self.check_block(body) {
+            self.errors.push(*err);
+        }
+        let moved: Vec<Diagnostic> = self
+            .errors
+            .drain(errors..)
+            .filter(|error| error.code == "E0402")
+            .collect();
+        self.warnings.truncate(warnings);
+        self.lambdas.truncate(lambdas);
+        self.annotations = annotations;
+        self.owners = owners;
+        self.facts = facts;
+        self.immutable = immutable;
+        self.unassigned = unassigned;
+        for error in moved {
+            if !self.errors.iter().any(|known| {
+                known.code == error.code
+                    && known.span == error.span
+                    && known.message == error.message
+            }) {
+                self.errors.push(error);
+            }
+        }
+    }
+
+    /// Record that the variable of a null test is not null where the test
+    /// has `outcome`.
+    fn narrow(&mut self, null_test: Option<(&String, bool)>, outcome: bool, condition: Span) {
+        if let Some((name, nonnull_when_true)) = null_test
+            && nonnull_when_true == outcome
+        {
+            self.facts
+                .entry(name.clone())
+                .or_default()
+                .extend(hint_facts::never_null(condition));
+        }
+    }
+
+    /// Check a match statement. Each arm is a block of its own holding the
+    /// names its pattern binds, and together the patterns must cover every
+    /// value of the scrutinee.
+    fn check_match(
+        &mut self,
+        scrutinee: &Expression,
+        arms: &[MatchArm],
+        span: Span,
+    ) -> TypeCheckerResult<()> {
+        let value_type = self.check_expr(scrutinee)?;
+        let before = self.unassigned.clone();
+        let mut arm_states = Vec::new();
+        for arm in arms {
+            self.check_scoped(|checker| {
+                checker.bind_pattern(&arm.pattern, &value_type, true)?;
+                checker.check_statements(&arm.body);
+                Ok(())
+            })?;
+            let state = std::mem::replace(&mut self.unassigned, before.clone());
+            arm_states.push((
+                (!always_returns(&arm.body)).then_some(state),
+                arm.pattern.span,
+            ));
+        }
+        let patterns: Vec<&Pattern> = arms.iter().map(|arm| &arm.pattern).collect();
+        if let Some(missing) =
+            exhaustiveness::missing_case(&patterns, &value_type, &self.named_types)
+        {
+            return Err(Diagnostic::error(
+                "E0203",
+                format!(
+                    "Match on a value of type {} does not cover {}",
+                    value_type, missing
+                ),
+                span,
+            )
+            .with_note("add an arm for it, or a '_' arm for every value left")
+            .into());
+        }
+        self.unassigned = Unassigned::join_arms(&before, arm_states, span);
+        Ok(())
+    }
+
+    /// Check statements in a block of their own.
+    fn check_block(&mut self, statements: &[Statement]) -> TypeCheckerResult<()> {
+        self.check_scoped(|checker| {
+            checker.check_statements(statements);
+            Ok(())
+        })
+    }
+
+    /// Check each statement, recording the error of one that has any and
+    /// going on with the next.
+    fn check_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            if let Err(err) = self.check_statement(statement) {
+                self.errors.push(*err);
+            }
+        }
+    }
+
+    /// Run `check` in a block of its own. Variables declared in it vanish at
+    /// its end, and the outer variables they shadowed get their state back.
+    fn check_scoped(
+        &mut self,
+        check: impl FnOnce(&mut Self) -> TypeCheckerResult<()>,
+    ) -> TypeCheckerResult<()> {
+        let immutable = self.immutable.clone();
+        let facts = self.facts.clone();
+        let unassigned = self.unassigned.clone();
+        self.scopes.push();
+        let result = check(self);
+        for name in self.scopes.pop() {
+            if immutable.contains(&name) {
+                self.immutable.insert(name.clone());
+            } else {
+                self.immutable.remove(&name);
+            }
+            match facts.get(&name) {
+                Some(outer_facts) => self.facts.insert(name.clone(), outer_facts.clone()),
+                None => self.facts.remove(&name),
+            };
+            self.unassigned.restore(&name, &unassigned);
+        }
+        result
+    }
+
+    /// Declare a variable in the innermost block. Inner blocks may shadow an
+    /// outer variable, but a block cannot declare the same name twice.
+    fn declare_variable(
+        &mut self,
+        name: &str,
+        var_type: TypeIdentifier,
+        span: Span,
+    ) -> TypeCheckerResult<()> {
+        let id = self.annotations.declare(name, var_type.clone(), span);
+        self.scopes
+            .declare(name, id, var_type, span)
+            .map_err(|previous| {
+                Diagnostic::error(
+                    "E0002",
+                    format!("Variable '{}' is already declared in this scope", name),
+                    span,
+                )
+                .with_label(previous, "first declared here")
+                .into()
+            })
+    }
+
+    fn check_var_decl(&mut self, var_decl: &VariableDeclaration) -> TypeCheckerResult<()> {
+        let PatternKind::Binding {
+            name: ident,
+            binding_type,
+        } = &var_decl.pattern.kind
+        else {
+            return self.check_destructuring(
+                &var_decl.pattern,
+                &var_decl.expression,
+                var_decl.span,
+            );
+        };
+        if let Some(binding_type) = binding_type {
+            self.check_known_type(binding_type, var_decl.pattern.span)?;
+        }
+        let Some(expression) = &var_decl.expression else {
+            let Some(var_type) = binding_type.clone() else {
+                return Err(Diagnostic::error(
+                    "E0102",
+                    format!(
+                        "Cannot infer the type of '{}': add a type annotation or an initializer",
+                        ident
+                    ),
+                    var_decl.span,
+                )
+                .into());
+            };
+            self.declare_variable(ident, var_type.clone(), var_decl.pattern.span)?;
+            self.immutable.remove(ident);
+            self.facts.remove(ident);
+            self.unassigned.declare(ident, var_decl.span);
+            return Ok(());
+        };
+        let var_type = match binding_type {
+            // Check the initializer against the annotation
+            Some(var_type) => {
+                let expr_type = self.check_expr_expecting(expression, var_type)?;
+                if !self.assignable(var_type, &expr_type) {
+                    return Err(Diagnostic::error(
+                        "E0100",
+                        format!(
+                            "Type mismatch in variable declaration '{}': declared as {}, but got {}",
+                            ident, var_type, expr_type
+                        ),
+                        var_decl.span,
+                    ).into());
+                }
+                var_type.clone()
+            }
+            // Infer the binding's type from the initializer
+            None => self.check_moved(expression)?,
+        };
+        if var_type == TypeIdentifier::Null {
+            return Err(Diagnostic::error(
+                "E0102",
+                format!(
+                    "Cannot infer the pointer type of '{}' from null: add a type annotation",
+                    ident
+                ),
+                var_decl.span,
+            )
+            .into());
+        }
+        self.declare_variable(ident, var_type.clone(), var_decl.pattern.span)?;
+        self.immutable.remove(ident);
+        self.unassigned.assign(ident);
+        // Facts flow from the initializer into the new binding
+        let facts = self.facts_of(expression);
+        self.facts.insert(ident.clone(), facts);
+        Ok(())
+    }
+
+    /// Check a `@raw_cast` let. Its initializer may have any type of the size
+    /// of the annotation, the bits of the value are taken as they are.
+    fn check_raw_cast(&mut self, var_decl: &VariableDeclaration) -> TypeCheckerResult<()> {
+        let (
+            PatternKind::Binding {
+                name,
+                binding_type: Some(target),
+            },
+            Some(expression),
+        ) = (&var_decl.pattern.kind, &var_decl.expression)
+        else {
+            return Err(Diagnostic::error(
+                "E0107",
+                "'@raw_cast' needs a single variable with a type annotation and an initializer",
+                var_decl.span,
+            )
+            .into());
+        };
+        self.check_known_type(target, var_decl.pattern.span)?;
+        let value_type = self.check_expr(expression)?;
+        let size = |value_type| casts::size_of(value_type, &self.named_types);
+        match (size(&value_type), size(target)) {
+            (Some(from), Some(to)) if from == to => {}
+            (from, to) => {
+                let describe = |size: Option<usize>| match size {
+                    Some(1) => "1 byte".to_string(),
+                    Some(size) => format!("{} bytes", size),
+                    None => "of unknown size".to_string(),
+                };
+                return Err(Diagnostic::error(
+                    "E0107",
+                    format!(
+                        "Cannot raw cast {} to {}: {} is {}, but {} is {}",
+                        value_type,
+                        target,
+                        value_type,
+                        describe(from),
+                        target,
+                        describe(to)
+                    ),
+                    expression.span,
+                )
+                .with_label(var_decl.pattern.span, format!("declared as {}", target))
+                .with_note(
+                    "a raw cast reinterprets the bits, so both types must have the same size",
+                )
+                .into());
+            }
+        }
+        self.declare_variable(name, target.clone(), var_decl.pattern.span)?;
+        self.immutable.remove(name);
+        self.facts.remove(name);
+        self.unassigned.assign(name);
+        self.annotations.record_cast(var_decl.span, CastKind::Raw);
+        Ok(())
+    }
+
+    /// Check a `let` whose left side is a tuple or labeled pattern, binding each
+    /// name to the matching part of the initializer's type.
+    fn check_destructuring(
+        &mut self,
+        pattern: &Pattern,
+        expression: &Option<Expression>,
+        span: Span,
+    ) -> TypeCheckerResult<()> {
+        let Some(expression) = expression else {
+            return Err(Diagnostic::error(
+                "E0201",
+                "A destructuring 'let' needs an initializer",
+                span,
+            )
+            .into());
+        };
+        let value_type = self.check_expr(expression)?;
+        self.bind_pattern(pattern, &value_type, false)
+    }
+
+    /// Bind the names of `pattern` to the parts of a value of `value_type`.
+    /// Only a match arm's pattern is `refutable`, a let pattern must match
+    /// every value.
+    fn bind_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value_type: &TypeIdentifier,
+        refutable: bool,
+    ) -> TypeCheckerResult<()> {
+        match (&pattern.kind, self.underlying(value_type)) {
+            (PatternKind::Wildcard, _) => {}
+            (PatternKind::Binding { name, binding_type }, _) => {
+                if let Some(binding_type) = binding_type {
+                    self.check_known_type(binding_type, pattern.span)?;
+                }
+                if let Some(binding_type) = binding_type
+                    && !self.equivalent(binding_type, value_type)
+                {
+                    return Err(Diagnostic::error(
+                        "E0100",
+                        format!(
+                            "Type mismatch in destructuring: '{}' is declared as {}, but the value is {}",
+                            name, binding_type, value_type
+                        ),
+                        pattern.span,
+                    ).into());
+                }
+                self.declare_variable(name, value_type.clone(), pattern.span)?;
+                self.immutable.remove(name);
+                self.facts.remove(name);
+                self.unassigned.assign(name);
+            }
+            (PatternKind::Literal(Literal::Unit), TypeIdentifier::Unit) => {}
+            (PatternKind::Literal(literal), _) if refutable => {
+                let literal_type = literal_type(literal);
+                if !self.equivalent(&literal_type, value_type) {
+                    return Err(Diagnostic::error(
+                        "E0200",
+                        format!(
+                            "Literal pattern {} is {}, but the value is {}",
+                            literal, literal_type, value_type
+                        ),
+                        pattern.span,
+                    )
+                    .into());
+                }
+            }
+            (PatternKind::Literal(literal), _) => {
+                return Err(Diagnostic::error(
+                    "E0201",
+                    format!(
+                        "A 'let' pattern must always match, but the literal pattern {} may not",
+                        literal
+                    ),
+                    pattern.span,
+                )
+                .into());
+            }
+            (PatternKind::Tuple(elements), TypeIdentifier::Tuple(element_types)) => {
+                if elements.len() != element_types.len() {
+                    return Err(Diagnostic::error(
+                        "E0200",
+                        format!(
+                            "Tuple pattern has {} elements, but the value of type {} has {}",
+                            elements.len(),
+                            value_type,
+                            element_types.len()
+                        ),
+                        pattern.span,
+                    )
+                    .into());
+                }
+                for (element, element_type) in elements.iter().zip(&element_types) {
+                    self.bind_pattern(element, element_type, refutable)?;
+                }
+            }
+            (PatternKind::Labeled(fields), TypeIdentifier::Labeled(field_types)) => {
+                if let Some((label, field)) = repeated_label(fields) {
+                    return Err(Diagnostic::error(
+                        "E0202",
+                        format!("Field '{}' is bound more than once", label),
+                        field.span,
+                    )
+                    .into());
+                }
+                for (label, field) in fields {
+                    let Some((_, field_type)) = field_types.iter().find(|(l, _)| l == label) else {
+                        return Err(Diagnostic::error(
+                            "E0200",
+                            format!("Type {} has no field '{}'", value_type, label),
+                            field.span,
+                        )
+                        .into());
+                    };
+                    self.bind_pattern(field, field_type, refutable)?;
+                }
+            }
+            (PatternKind::Variant { label, .. }, _) if !refutable => {
+                return Err(Diagnostic::error(
+                    "E0201",
+                    format!(
+                        "A 'let' pattern must always match, but the variant pattern '{} may not",
+                        label
+                    ),
+                    pattern.span,
+                )
+                .with_note("use a 'match' to handle every variant")
+                .into());
+            }
+            (PatternKind::Variant { label, payload }, TypeIdentifier::Sum(variants)) => {
+                let Some((_, payload_type)) = variants.iter().find(|(l, _)| l == label) else {
+                    return Err(Diagnostic::error(
+                        "E0200",
+                        format!("Type {} has no variant '{}", value_type, label),
+                        pattern.span,
+                    )
+                    .into());
+                };
+                // Leaving the payload pattern out ignores the payload
+                match (payload, payload_type) {
+                    (Some(payload), Some(payload_type)) => {
+                        self.bind_pattern(payload, payload_type, refutable)?
+                    }
+                    (Some(payload), None) => {
+                        return Err(Diagnostic::error(
+                            "E0200",
+                            format!("Variant '{} of {} carries no value", label, value_type),
+                            payload.span,
+                        )
+                        .into());
+                    }
+                    (None, _) => {}
+                }
+            }
+            (PatternKind::Variant { .. }, _) => {
+                return Err(Diagnostic::error(
+                    "E0200",
+                    format!(
+                        "Cannot match a value of type {} with a variant pattern",
+                        value_type
+                    ),
+                    pattern.span,
+                )
+                .into());
+            }
+            (PatternKind::Tuple(_), _) => {
+                return Err(Diagnostic::error(
+                    "E0200",
+                    format!(
+                        "Cannot destructure a value of type {} with a tuple pattern",
+                        value_type
+                    ),
+                    pattern.span,
+                )
+                .into());
+            }
+            (PatternKind::Labeled(_), _) => {
+                return Err(Diagnostic::error(
+                    "E0200",
+                    format!(
+                        "Cannot destructure a value of type {} with a labeled pattern",
+                        value_type
+                    ),
+                    pattern.span,
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    fn check_assignment(
+        &mut self,
+        identifier: &String,
+        expr: &Expression,
+        span: Span,
+    ) -> TypeCheckerResult<()> {
+        let expr_type = match self.scopes.get(identifier).cloned() {
+            Some(var_type) => self.check_expr_expecting(expr, &var_type)?,
+            None => self.check_expr(expr)?,
+        };
+        match self.scopes.get(identifier) {
+            Some(var_type) => {
+                if !self.assignable(var_type, &expr_type) {
+                    return Err(Diagnostic::error(
+                        "E0100",
+                        format!(
+                            "Type mismatch in assignment to '{}': variable is {}, but got {}",
+                            identifier, var_type, expr_type
+                        ),
+                        expr.span,
+                    )
+                    .into());
+                }
+                self.check_mutable(identifier, span)?;
+                self.check_promises_on_assignment(identifier, &self.facts_of(expr), expr.span)?;
+                if let Some(id) = self.scopes.resolve(identifier) {
+                    self.annotations.resolve(span, Resolution::Local(id));
+                }
+                self.unassigned.assign(identifier);
+                let facts = self.facts_of(expr);
+                self.facts.insert(identifier.clone(), facts);
+                Ok(())
+            }
+            None => Err(self.not_a_variable(identifier, span)),
+        }
+    }
+
+    /// The error for assigning to `identifier`, which is not a variable in scope.
+    fn not_a_variable(&self, identifier: &str, span: Span) -> Box<Diagnostic> {
+        if self.functions.contains_key(identifier) {
+            return Diagnostic::error(
+                "E0300",
+                format!("Cannot assign to function '{}'", identifier),
+                span,
+            )
+            .into();
+        }
+        Diagnostic::error(
+            "E0001",
+            format!("Assignment to undeclared variable '{}'", identifier),
+            span,
+        )
+        .into()
+    }
+
+    /// Check a write through a pointer. The 'deref' target is checked like a
+    /// read, so a raw pointer must be known not to be null here too.
+    fn check_store(&mut self, target: &Expression, expr: &Expression) -> TypeCheckerResult<()> {
+        let target_type = self.check_expr(target)?;
+        let expr_type = self.check_expr_expecting(expr, &target_type)?;
+        if !self.assignable(&target_type, &expr_type) {
+            return Err(Diagnostic::error(
+                "E0100",
+                format!(
+                    "Type mismatch in write through a pointer: it points to {}, but got {}",
+                    target_type, expr_type
+                ),
+                expr.span,
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    fn check_compound_assignment(
+        &mut self,
+        identifier: &String,
+        operator: Operator,
+        expr: &Expression,
+        span: Span,
+    ) -> TypeCheckerResult<()> {
+        let var_type = self.check_update_target(identifier, operator, span)?;
+        let expr_type = self.check_expr(expr)?;
+        if !self.equivalent(&expr_type, &var_type) {
+            return Err(Diagnostic::error(
+                "E0100",
+                format!(
+                    "Type mismatch in compound assignment to '{}': variable is {}, but got {}",
+                    identifier, var_type, expr_type
+                ),
+                expr.span,
+            )
+            .into());
+        }
+        // The new value is computed, nothing known about the old one still holds
+        self.check_promises_on_assignment(identifier, &[], span)?;
+        self.facts.remove(identifier);
+        Ok(())
+    }
+
+    fn check_increment_decrement(
+        &mut self,
+        identifier: &String,
+        operator: Operator,
+        span: Span,
+    ) -> TypeCheckerResult<()> {
+        self.check_update_target(identifier, operator, span)?;
+        self.check_promises_on_assignment(identifier, &[], span)?;
+        self.facts.remove(identifier);
+        Ok(())
+    }
+
+    /// Check that `identifier` can be updated in place by `operator`: it must be
+    /// a declared, mutable and readable variable of a type the operation takes.
+    /// `++` and `--` only count integers.
+    fn check_update_target(
+        &mut self,
+        identifier: &String,
+        operator: Operator,
+        span: Span,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        let Some(var_type) = self.scopes.get(identifier).cloned() else {
+            return Err(self.not_a_variable(identifier, span));
+        };
+        self.check_mutable(identifier, span)?;
+        // Updating in place reads the old value
+        self.check_readable(identifier, span)?;
+        self.check_assigned(identifier, span)?;
+        let (accepted, expected) = match operator
+            .compound_operation()
+            .and_then(operators::binary_rule)
+        {
+            Some(rule) => ((rule.accepts)(&var_type, &self.named_types), rule.expected),
+            None => (self.underlying(&var_type) == TypeIdentifier::Number, "int"),
+        };
+        if !accepted {
+            return Err(Diagnostic::error(
+                "E0101",
+                format!(
+                    "Operator '{}' requires a variable of type {}, but '{}' is {}",
+                    operator, expected, identifier, var_type
+                ),
+                span,
+            )
+            .into());
+        }
+        if let Some(id) = self.scopes.resolve(identifier) {
+            self.annotations.resolve(span, Resolution::Local(id));
+        }
+        Ok(var_type)
+    }
+
+    fn check_mutable(&self, identifier: &String, span: Span) -> TypeCheckerResult<()> {
+        if let Some(promise) = self
+            .promises
+            .get(identifier)
+            .and_then(|promises| promises.iter().find(|p| hint_facts::forbids_write(p)))
+        {
+            return Err(Diagnostic::error(
+                "E0300",
+                format!(
+                    "Function promises '{}({})' but assigns to '{}'",
+                    hint_facts::render(promise),
+                    identifier,
+                    identifier
+                ),
+                span,
+            )
+            .into());
+        }
+        if self.immutable.contains(identifier) {
+            return Err(Diagnostic::error(
+                "E0300",
+                format!("Cannot assign to immutable variable '{}'", identifier),
+                span,
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    fn check_readable(&self, identifier: &String, span: Span) -> TypeCheckerResult<()> {
+        if let Some(promise) = self
+            .promises
+            .get(identifier)
+            .and_then(|promises| promises.iter().find(|p| hint_facts::forbids_read(p)))
+        {
+            return Err(Diagnostic::error(
+                "E0301",
+                format!(
+                    "Function promises '{}({})' but reads '{}'",
+                    hint_facts::render(promise),
+                    identifier,
+                    identifier
+                ),
+                span,
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Check that `identifier` has a value on every path that reaches `span`.
+    fn check_assigned(&self, identifier: &str, span: Span) -> TypeCheckerResult<()> {
+        let Some(path) = self.unassigned.path(identifier) else {
+            return Ok(());
+        };
+        let note = format!(
+            "path: {} -> '{}' is read at {}",
+            path.describe(),
+            identifier,
+            span
+        );
+        if let Some(moved_at) = path.moved_at {
+            return Err(Diagnostic::error(
+                "E0402",
+                format!("Use of moved value '{}'", identifier),
+                span,
+            )
+            .with_label(moved_at, "value moved here")
+            .with_note(note)
+            .into());
+        }
+        Err(Diagnostic::error(
+            "E0400",
+            format!(
+                "Variable '{}' is read before it is definitely assigned",
+                identifier
+            ),
+            span,
+        )
+        .with_note(note)
+        .into())
+    }
+
+    /// Move the value out of the variable `expr` names, when its type holds a
+    /// unique pointer. The variable cannot be read again until it is assigned.
+    fn consume(&mut self, expr: &Expression, expr_type: &TypeIdentifier) {
+        match &expr.kind {
+            ExpressionKind::Grouping(expression) | ExpressionKind::Hinted { expression, .. } => {
+                self.consume(expression, expr_type)
+            }
+            ExpressionKind::Identifier(name)
+                if self.scopes.resolve(name).is_some() && expr_type.moves(&self.named_types) =>
+            {
+                self.unassigned.moved(name, expr.span);
+                self.annotations.record_move(expr.span);
+            }
+            _ => {}
+        }
+    }
+
+    /// Type an expression whose value is stored somewhere new, moving it out
+    /// of the variable it names.
+    fn check_moved(&mut self, expr: &Expression) -> TypeCheckerResult<TypeIdentifier> {
+        let expr_type = self.check_expr(expr)?;
+        self.consume(expr, &expr_type);
+        Ok(expr_type)
+    }
+
+    /// Check `addressof` where a unique or shared pointer is expected. The
+    /// pointer becomes the owner of the variable, which can have only one.
+    fn check_take_ownership(
+        &mut self,
+        expression: &Expression,
+        ownership: Ownership,
+        pointee: &TypeIdentifier,
+        span: Span,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        let address_type = self.check_unary(Operator::AddressOf, expression, span)?;
+        let (ExpressionKind::Identifier(name), TypeIdentifier::Pointer(var_type)) =
+            (&expression.kind, &address_type)
+        else {
+            return Ok(address_type);
+        };
+        // The caller reports the mismatch
+        if !self.equivalent(var_type, pointee) {
+            return Ok(address_type);
+        }
+        let id = self
+            .scopes
+            .resolve(name)
+            .expect("the address of a variable was taken");
+        if let Some((owner, taken_at)) = self.owners.get(&id) {
+            return Err(Diagnostic::error(
+                "E0403",
+                format!(
+                    "'{}' is already owned by the {} pointer at {}",
+                    name, owner, taken_at
+                ),
+                span,
+            )
+            .with_label(*taken_at, "first owner taken here")
+            .with_note("copy the shared pointer or move the unique one instead of taking the address again")
+            .into());
+        }
+        self.owners.insert(id, (ownership, span));
+        Ok(TypeIdentifier::SafePointer(ownership, var_type.clone()))
+    }
+
+    /// Check that assigning `expr` to `identifier` keeps every promise the
+    /// current function made about it.
+    fn check_promises_on_assignment(
+        &self,
+        identifier: &String,
+        facts: &[Hint],
+        span: Span,
+    ) -> TypeCheckerResult<()> {
+        let Some(promises) = self.promises.get(identifier) else {
+            return Ok(());
+        };
+        for promise in promises {
+            if hint_facts::forbids_write(promise) || hint_facts::forbids_read(promise) {
+                continue;
+            }
+            if !facts
+                .iter()
+                .any(|fact| hint_facts::satisfies(fact, promise))
+            {
+                return Err(Diagnostic::error(
+                    "E0302",
+                    format!(
+                        "Assignment to '{}' breaks the promise '{}({})': the assigned value is not known to be {}",
+                        identifier,
+                        hint_facts::render(promise),
+                        identifier,
+                        hint_facts::render(promise)
+                    ),
+                    span,
+                ).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Hints known to hold for the value of an expression.
+    fn facts_of(&self, expr: &Expression) -> Vec<Hint> {
+        match &expr.kind {
+            ExpressionKind::Identifier(ident) => self.facts.get(ident).cloned().unwrap_or_default(),
+            ExpressionKind::Grouping(expr)
+            | ExpressionKind::Hinted {
+                expression: expr, ..
+            } => self.facts_of(expr),
+            ExpressionKind::Literal(Literal::Null) => Vec::new(),
+            ExpressionKind::Literal(_)
+            | ExpressionKind::Unary {
+                operator: Operator::AddressOf,
+                ..
+            } => hint_facts::never_null(expr.span),
+            _ => Vec::new(),
+        }
+    }
+
+    fn check_func_decl(&mut self, function: &Function) -> TypeCheckerResult<()> {
+        let function_name = function.signature.name.clone();
+        let statements = match &function.body {
+            FunctionBody::Statements(statements) => statements,
+            // Forward declarations were matched against their definition up front
+            FunctionBody::Empty => return Ok(()),
+        };
+        if let Some(receiver) = function
+            .signature
+            .parameters
+            .iter()
+            .find(|parameter| parameter.parameter_type.is_none())
+        {
+            return Err(Diagnostic::error(
+                "E0007",
+                format!(
+                    "Parameter '{}' of '{}' has no type: only the 'self' of a method can leave it out",
+                    receiver.parameter_name, function_name
+                ),
+                receiver.span,
+            )
+            .into());
+        }
+        self.check_signature_types(
+            &function.signature.parameters,
+            &function.signature.return_type,
+            function.span,
+        )?;
+        self.functions
+            .insert(function_name.clone(), function.signature.clone());
+
+        // Functions see the globals and their own parameters, never the locals around them
+        self.check_callable(
+            &function_name,
+            &function.signature.parameters,
+            &function.signature.return_type,
+            statements,
+            false,
+            function.span,
+        )
+    }
+
+    fn check_lambda(
+        &mut self,
+        lambda: &Lambda,
+        span: Span,
+        expected: Option<&TypeIdentifier>,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        self.check_signature_types(&lambda.parameters, &lambda.return_type, span)?;
+        let expected = expected.map(|expected| self.underlying(expected));
+        let parameters = infer_parameters(lambda, expected.as_ref())?;
+        // Only locals of an enclosing function need capturing; globals outlive the lambda
+        let captures: Vec<String> = if self.function_depth > 0 {
+            captures::free_variables(lambda)
+                .into_iter()
+                .filter(|name| self.scopes.is_local(name))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let capture_ids = captures
+            .iter()
+            .filter_map(|name| self.scopes.resolve(name))
+            .collect();
+        self.annotations.record_captures(span, capture_ids);
+        // Lambdas see the bindings of the scope they are created in
+        self.check_callable(
+            "<anonymous function>",
+            &parameters,
+            &lambda.return_type,
+            &lambda.body,
+            true,
+            span,
+        )?;
+        self.lambdas.push(if captures.is_empty() {
+            LambdaKind::Pure
+        } else {
+            LambdaKind::Closure { captures }
+        });
+
+        Ok(TypeIdentifier::Function {
+            parameters: parameters.iter().map(parameter_type).collect(),
+            return_type: Box::new(return_type(&lambda.return_type)),
+        })
+    }
+
+    /// Check the body of a function or lambda in a new block holding its
+    /// parameters. Unless `sees_locals` is set, the block sits right on top of
+    /// the globals.
+    fn check_callable(
+        &mut self,
+        name: &str,
+        parameters: &[FunctionParameter],
+        declared_return_type: &Option<TypeIdentifier>,
+        statements: &[Statement],
+        sees_locals: bool,
+        span: Span,
+    ) -> TypeCheckerResult<()> {
+        let return_type = return_type(declared_return_type);
+        // Swap in the new local scope for the body, then restore the outer scope
+        let scopes = if sees_locals {
+            self.scopes.clone()
+        } else {
+            self.scopes.globals()
+        };
+        let outer_scopes = std::mem::replace(&mut self.scopes, scopes);
+        let immutable = self
+            .immutable
+            .iter()
+            .filter(|name| self.scopes.contains(name))
+            .cloned()
+            .collect();
+        let outer_immutable = std::mem::replace(&mut self.immutable, immutable);
+        let unassigned = self.unassigned.filter(|name| self.scopes.contains(name));
+        let outer_unassigned = std::mem::replace(&mut self.unassigned, unassigned);
+        // A lambda captures copies of the locals it reads, which keep what was known of them
+        let facts = if sees_locals {
+            self.facts
+                .iter()
+                .filter(|(name, _)| self.scopes.contains(name))
+                .map(|(name, facts)| (name.clone(), facts.clone()))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        let outer_facts = std::mem::replace(&mut self.facts, facts);
+        let outer_promises = std::mem::take(&mut self.promises);
+        self.scopes.push();
+        for parameter in parameters {
+            if let Err(err) = self.declare_variable(
+                &parameter.parameter_name,
+                parameter_type(parameter),
+                parameter.span,
+            ) {
+                self.errors.push(*err);
+            }
+            self.immutable.remove(&parameter.parameter_name);
+            self.unassigned.assign(&parameter.parameter_name);
+            // Requirements are proven by every caller, so the body may rely on them
+            let (promises, requirements): (Vec<Hint>, Vec<Hint>) = parameter
+                .hints
+                .iter()
+                .cloned()
+                .partition(|hint| hint.placement == HintPlacement::Promise);
+            self.facts
+                .insert(parameter.parameter_name.clone(), requirements);
+            if !promises.is_empty() {
+                self.promises
+                    .insert(parameter.parameter_name.clone(), promises);
+            }
+        }
+
+        let outer_function = self
+            .current_function
+            .replace((name.to_string(), return_type.clone()));
+        self.function_depth += 1;
+        self.check_statements(statements);
+        self.function_depth -= 1;
+        self.current_function = outer_function;
+
+        // restore outer scope after checking the body
+        self.scopes = outer_scopes;
+        self.facts = outer_facts;
+        self.promises = outer_promises;
+        self.immutable = outer_immutable;
+        self.unassigned = outer_unassigned;
+
+        for (unreachable, returning) in unreachable_statements(statements) {
+            self.warnings.push(
+                Diagnostic::warning("W0001", "Unreachable statement", unreachable)
+                    .with_label(returning, "this statement always returns or panics"),
+            );
+        }
+        // A function returning a value must return on every path
+        if !self.equivalent(&return_type, &TypeIdentifier::Unit)
+            && let Some(path) = fall_through_path(statements)
+        {
+            let mut diagnostic = Diagnostic::error(
+                "E0401",
+                format!(
+                    "Function '{}' can reach the end of its body without returning a value",
+                    name
+                ),
+                span,
+            );
+            if !path.is_empty() {
+                diagnostic = diagnostic.with_note(format!("path: {}", path.join(" -> ")));
+            }
+            return Err(diagnostic.into());
+        }
+        Ok(())
+    }
+
+    /// Check a return statement against the declared return type of the
+    /// function it is in.
+    fn check_return(&mut self, expr: &Option<Expression>, span: Span) -> TypeCheckerResult<()> {
+        let Some((name, expected)) = self.current_function.clone() else {
+            if let Some(expr) = expr {
+                self.check_expr(expr)?;
+            }
+            return Ok(());
+        };
+        let ret_type = match expr {
+            Some(expr) => self.check_expr_expecting(expr, &expected)?,
+            None => TypeIdentifier::Unit,
+        };
+        if !self.assignable(&expected, &ret_type) {
+            return Err(Diagnostic::error(
+                "E0100",
+                format!(
+                    "Function '{}' returns {}, but declared as {}",
+                    name, ret_type, expected
+                ),
+                span,
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Check an expression against the type its context expects. Only lambdas
+    /// use the expected type, to infer the types of their parameters, variants
+    /// take it as their sum type, and tuple and struct literals pass it on to
+    /// their components; every other expression synthesizes its type and the
+    /// caller compares it.
+    fn check_expr_expecting(
+        &mut self,
+        expr: &Expression,
+        expected: &TypeIdentifier,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        let expr_type = match &expr.kind {
+            ExpressionKind::Lambda(lambda) => self.check_lambda(lambda, expr.span, Some(expected)),
+            ExpressionKind::Variant {
+                label,
+                payload,
+                payload_type,
+            } => self.check_variant(label, payload, payload_type, expected, expr.span),
+            ExpressionKind::Grouping(expression) | ExpressionKind::Hinted { expression, .. } => {
+                self.check_expr_expecting(expression, expected)
+            }
+            ExpressionKind::Unary {
+                operator: Operator::AddressOf,
+                expression,
+            } => match self.underlying(expected) {
+                TypeIdentifier::SafePointer(
+                    ownership @ (Ownership::Unique | Ownership::Shared),
+                    pointee,
+                ) => self.check_take_ownership(expression, ownership, &pointee, expr.span),
+                _ => self.check_expr(expr),
+            },
+            ExpressionKind::Tuple(elements) => match &self.underlying(expected) {
+                TypeIdentifier::Tuple(element_types) if element_types.len() == elements.len() => {
+                    Ok(TypeIdentifier::Tuple(
+                        elements
+                            .iter()
+                            .zip(element_types)
+                            .map(|(element, element_type)| {
+                                self.check_expr_expecting(element, element_type)
+                            })
+                            .collect::<TypeCheckerResult<_>>()?,
+                    ))
+                }
+                _ => self.check_expr(expr),
+            },
+            ExpressionKind::Struct(fields) => match &self.underlying(expected) {
+                TypeIdentifier::Labeled(field_types) => {
+                    if let Some((label, value)) = repeated_label(fields) {
+                        return Err(Diagnostic::error(
+                            "E0202",
+                            format!("Field '{}' is given more than once", label),
+                            value.span,
+                        )
+                        .into());
+                    }
+                    let mut types = Vec::new();
+                    for (label, value) in fields {
+                        let value_type = match field_types.iter().find(|(l, _)| l == label) {
+                            Some((_, field_type)) => {
+                                self.check_expr_expecting(value, field_type)?
+                            }
+                            None => self.check_expr(value)?,
+                        };
+                        types.push((label.clone(), value_type));
+                    }
+                    Ok(TypeIdentifier::Labeled(types))
+                }
+                _ => self.check_expr(expr),
+            },
+            _ => self.check_expr(expr),
+        }?;
+        // A tuple or struct literal takes the name of the type it is written for
+        let expr_type = match &expr.kind {
+            ExpressionKind::Tuple(_) | ExpressionKind::Struct(_)
+                if self.equivalent(expected, &expr_type) =>
+            {
+                expected.clone()
+            }
+            _ => expr_type,
+        };
+        // What is checked against an expected type is stored somewhere new
+        self.consume(expr, &expr_type);
+        self.annotations.record_type(expr.span, expr_type.clone());
+        Ok(expr_type)
+    }
+
+    fn check_expr(&mut self, expr: &Expression) -> TypeCheckerResult<TypeIdentifier> {
+        let expr_type = match &expr.kind {
+            ExpressionKind::Binary {
+                left,
+                operator,
+                right,
+            } => self.check_binary(left, *operator, right, expr.span)?,
+            ExpressionKind::Identifier(ident) => {
+                self.check_readable(ident, expr.span)?;
+                self.check_assigned(ident, expr.span)?;
+                self.resolve_name(ident, expr.span)?
+            }
+            ExpressionKind::Literal(literal) => literal_type(literal),
+            ExpressionKind::Grouping(expr) => self.check_expr(expr)?,
+            ExpressionKind::Hinted { expression, .. } => self.check_expr(expression)?,
+            ExpressionKind::Lambda(lambda) => self.check_lambda(lambda, expr.span, None)?,
+            ExpressionKind::Call { callee, args } => self.check_call(callee, args, expr.span)?,
+            ExpressionKind::Tuple(elements) => TypeIdentifier::Tuple(
+                elements
+                    .iter()
+                    .map(|element| self.check_moved(element))
+                    .collect::<TypeCheckerResult<_>>()?,
+            ),
+            ExpressionKind::Struct(fields) => {
+                if let Some((label, value)) = repeated_label(fields) {
+                    return Err(Diagnostic::error(
+                        "E0202",
+                        format!("Field '{}' is given more than once", label),
+                        value.span,
+                    )
+                    .into());
+                }
+                let mut field_types = Vec::new();
+                for (label, value) in fields {
+                    field_types.push((label.clone(), self.check_moved(value)?));
+                }
+                TypeIdentifier::Labeled(field_types)
+            }
+            ExpressionKind::Unary {
+                operator: op,
+                expression,
+            } => self.check_unary(*op, expression, expr.span)?,
+            ExpressionKind::Cast { expression, target } => {
+                self.check_cast(expression, target, expr.span)?
+            }
+            ExpressionKind::Variant { label, .. } => {
+                return Err(Diagnostic::error(
+                    "E0102",
+                    format!("Cannot infer the sum type of variant '{}", label),
+                    expr.span,
+                )
+                .with_note(
+                    "use the variant where its sum type is expected, such as an annotated 'let'",
+                )
+                .into());
+            }
+            ExpressionKind::Field { receiver, field } => {
+                let receiver_type = self.check_expr(receiver)?;
+                let TypeIdentifier::Labeled(fields) = self.underlying(&receiver_type) else {
+                    return Err(Diagnostic::error(
+                        "E0110",
+                        format!("Type {} has no fields", receiver_type),
+                        expr.span,
+                    )
+                    .into());
+                };
+                match fields.into_iter().find(|(label, _)| label == field) {
+                    Some((_, field_type)) => field_type,
+                    None => {
+                        return Err(Diagnostic::error(
+                            "E0110",
+                            format!("Type {} has no field '{}'", receiver_type, field),
+                            expr.span,
+                        )
+                        .into());
+                    }
+                }
+            }
+            ExpressionKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => self.check_method_call(receiver, method, args, expr.span)?,
+            ExpressionKind::Propagate(expression) => self.check_propagate(expression, expr.span)?,
+            ExpressionKind::Panic(message) => {
+                if let Some(message) = message {
+                    let message_type =
+                        self.check_expr_expecting(message, &TypeIdentifier::String)?;
+                    if !self.equivalent(&message_type, &TypeIdentifier::String) {
+                        return Err(Diagnostic::error(
+                            "E0100",
+                            format!(
+                                "The message of 'panic' must be a string, but it is {}",
+                                message_type
+                            ),
+                            message.span,
+                        )
+                        .into());
+                    }
+                }
+                TypeIdentifier::Never
+            }
+        };
+        self.annotations.record_type(expr.span, expr_type.clone());
+        Ok(expr_type)
+    }
+
+    /// Check a variant used where a value of type `expected` is required,
+    /// which must be a sum type with that variant. A struct literal payload
+    /// may name its type, which must fit the variant.
+    fn check_variant(
+        &mut self,
+        label: &String,
+        payload: &Option<Box<Expression>>,
+        payload_type: &Option<String>,
+        expected: &TypeIdentifier,
+        span: Span,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        let TypeIdentifier::Sum(variants) = self.underlying(expected) else {
+            return Err(Diagnostic::error(
+                "E0100",
+                format!(
+                    "Expected a value of type {}, but got variant '{}",
+                    expected, label
+                ),
+                span,
+            )
+            .into());
+        };
+        let Some((_, carried)) = variants.into_iter().find(|(l, _)| l == label) else {
+            return Err(Diagnostic::error(
+                "E0108",
+                format!("Type {} has no variant '{}", expected, label),
+                span,
+            )
+            .into());
+        };
+        match (payload, carried) {
+            (None, None) => {}
+            (Some(payload), Some(carried)) => {
+                let target = match payload_type {
+                    Some(name) => {
+                        let named = TypeIdentifier::Named(name.clone());
+                        self.check_known_type(&named, payload.span)?;
+                        named
+                    }
+                    None => carried.clone(),
+                };
+                let actual = self.check_expr_expecting(payload, &target)?;
+                if !self.assignable(&target, &actual) || !self.assignable(&carried, &actual) {
+                    return Err(Diagnostic::error(
+                        "E0100",
+                        format!(
+                            "Type mismatch in the payload of variant '{}: expected {}, got {}",
+                            label, carried, actual
+                        ),
+                        payload.span,
+                    )
+                    .into());
+                }
+            }
+            (None, Some(carried)) => {
+                return Err(Diagnostic::error(
+                    "E0108",
+                    format!(
+                        "Variant '{} of {} carries a value of type {}, but none is given",
+                        label, expected, carried
+                    ),
+                    span,
+                )
+                .into());
+            }
+            (Some(payload), None) => {
+                return Err(Diagnostic::error(
+                    "E0108",
+                    format!("Variant '{} of {} carries no value", label, expected),
+                    payload.span,
+                )
+                .into());
+            }
+        }
+        Ok(expected.clone())
+    }
+
+    /// Check a method call. A receiver whose type is a contract can call the
+    /// methods the contract requires, any other the methods its type, or a
+    /// type it is named after, defines.
+    fn check_method_call(
+        &mut self,
+        receiver: &Expression,
+        method: &String,
+        args: &[Expression],
+        span: Span,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        let receiver_type = self.check_expr(receiver)?;
+        let Some(TypeIdentifier::Function {
+            parameters,
+            return_type,
+        }) = self.method_type(&receiver_type, method)
+        else {
+            return Err(Diagnostic::error(
+                "E0110",
+                format!("Type {} has no method '{}'", receiver_type, method),
+                span,
+            )
+            .into());
+        };
+        if args.len() != parameters.len() {
+            return Err(Diagnostic::error(
+                "E0104",
+                format!(
+                    "Method '{}' expects {} arguments, got {}",
+                    method,
+                    parameters.len(),
+                    args.len()
+                ),
+                span,
+            )
+            .into());
+        }
+        for (i, (arg, expected_ty)) in args.iter().zip(&parameters).enumerate() {
+            let arg_ty = self.check_expr_expecting(arg, expected_ty)?;
+            if !self.assignable(expected_ty, &arg_ty) {
+                return Err(Diagnostic::error(
+                    "E0100",
+                    format!(
+                        "Type mismatch in argument {} of method '{}': expected {}, got {}",
+                        i + 1,
+                        method,
+                        expected_ty,
+                        arg_ty
+                    ),
+                    arg.span,
+                )
+                .into());
+            }
+        }
+        Ok(*return_type)
+    }
+
+    /// The type of method `method` of a value of `receiver_type`, 'self' left out.
+    fn method_type(&self, receiver_type: &TypeIdentifier, method: &str) -> Option<TypeIdentifier> {
+        let mut current = receiver_type;
+        while let TypeIdentifier::Named(name) = current {
+            if let Some(contract) = self.contracts.get(name) {
+                return contract
+                    .iter()
+                    .find(|(required, _)| required == method)
+                    .map(|(_, method_type)| method_type.clone());
+            }
+            if let Some(signature) = self.methods.get(&(name.clone(), method.to_string())) {
+                return Some(method_type(signature));
+            }
+            current = self.named_types.get(name)?;
+        }
+        None
+    }
+
+    /// Check a `?`. Its operand is a result, a sum of an 'Ok and an 'Err
+    /// variant; an 'Err is returned from the enclosing function, whose return
+    /// type must have an 'Err variant that can hold it, and an 'Ok gives its
+    /// payload.
+    fn check_propagate(
+        &mut self,
+        expression: &Expression,
+        span: Span,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        let result_type = self.check_expr(expression)?;
+        let variant = |variants: &[(String, Option<TypeIdentifier>)], label: &str| {
+            variants
+                .iter()
+                .find(|(l, _)| l == label)
+                .map(|(_, payload)| payload.clone())
+        };
+        let (ok, err) = match self.underlying(&result_type) {
+            TypeIdentifier::Sum(variants) if variants.len() == 2 => {
+                (variant(&variants, "Ok"), variant(&variants, "Err"))
+            }
+            _ => (None, None),
+        };
+        let (Some(ok), Some(err)) = (ok, err) else {
+            return Err(Diagnostic::error(
+                "E0109",
+                format!(
+                    "'?' needs a result, a sum type of an 'Ok and an 'Err variant, but got {}",
+                    result_type
+                ),
+                expression.span,
+            )
+            .into());
+        };
+        let Some((function, returned)) = self.current_function.clone() else {
+            return Err(Diagnostic::error(
+                "E0109",
+                "'?' can only be used inside a function, which returns the 'Err",
+                span,
+            )
+            .into());
+        };
+        let accepted = match self.underlying(&returned) {
+            TypeIdentifier::Sum(variants) => variant(&variants, "Err"),
+            _ => None,
+        };
+        let fits = match (&accepted, &err) {
+            (Some(Some(accepted)), Some(err)) => self.assignable(accepted, err),
+            (Some(None), None) => true,
+            _ => false,
+        };
+        if !fits {
+            return Err(Diagnostic::error(
+                "E0109",
+                format!(
+                    "'?' returns the 'Err of {}, but function '{}' returns {}",
+                    result_type, function, returned
+                ),
+                span,
+            )
+            .with_note("the return type needs an 'Err variant that can hold the error")
+            .into());
+        }
+        Ok(ok.unwrap_or(TypeIdentifier::Unit))
+    }
+
+    /// Check a safe cast against the table of conversions, recording the run
+    /// time check it needs, if any, for lowering. A cast with a check gives a
+    /// result, handled with `?` or a match, rather than the target type.
+    fn check_cast(
+        &mut self,
+        expression: &Expression,
+        target: &TypeIdentifier,
+        span: Span,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        let value_type = self.check_expr(expression)?;
+        let Some(kind) = casts::safe_cast(&value_type, target, &self.named_types) else {
+            return Err(Diagnostic::error(
+                "E0106",
+                format!("Cannot cast {} to {}", value_type, target),
+                span,
+            )
+            .with_label(expression.span, format!("this is {}", value_type))
+            .with_note("safe casts convert between int and float, char or bool")
+            .into());
+        };
+        self.annotations.record_cast(span, kind);
+        match kind {
+            CastKind::Safe(Some(_)) => Ok(casts::checked_result(target)),
+            _ => Ok(target.clone()),
+        }
+    }
+
+    /// Check a binary operation. Each operand must be of a type the operator
+    /// accepts, and both of equivalent types; an error points at the operand
+    /// that breaks the rule. Strict comparisons also look at type identity,
+    /// which is known here, so one between different types gets a warning.
+    fn check_binary(
+        &mut self,
+        left: &Expression,
+        operator: Operator,
+        right: &Expression,
+        span: Span,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        let left_type = self.check_expr(left)?;
+        let right_type = self.check_expr(right)?;
+        if matches!(operator, Operator::Plus | Operator::Minus)
+            && operators::is_pointer(&left_type, &self.named_types)
+        {
+            return self.check_pointer_arithmetic(left, &left_type, operator, right, &right_type);
+        }
+        let Some(rule) = operators::binary_rule(operator) else {
+            return Err(Diagnostic::error(
+                "E0101",
+                "Unsupported operator in binary expression",
+                span,
+            )
+            .into());
+        };
+        for (side, operand, operand_type) in
+            [("left", left, &left_type), ("right", right, &right_type)]
+        {
+            if !(rule.accepts)(operand_type, &self.named_types) {
+                return Err(Diagnostic::error(
+                    "E0101",
+                    format!(
+                        "The {} operand of '{}' must be {}, but it is {}",
+                        side, operator, rule.expected, operand_type
+                    ),
+                    operand.span,
+                )
+                .into());
+            }
+        }
+        // Any pointer may be compared with null
+        let null_comparison = [(&left_type, &right_type), (&right_type, &left_type)]
+            .into_iter()
+            .any(|(null, pointer)| {
+                *null == TypeIdentifier::Null && operators::is_pointer(pointer, &self.named_types)
+            });
+        if !self.equivalent(&left_type, &right_type) && !null_comparison {
+            return Err(Diagnostic::error(
+                "E0100",
+                format!(
+                    "Mismatched operands of '{}': the left operand is {}, but the right operand is {}",
+                    operator, left_type, right_type
+                ),
+                right.span,
+            )
+            .with_label(left.span, format!("this is {}", left_type))
+            .into());
+        }
+        if operators::is_strict(operator)
+            && !left_type.is_identical(&right_type)
+            && !null_comparison
+        {
+            let outcome = if operator == Operator::StrictEquals {
+                "false"
+            } else {
+                "true"
+            };
+            self.warnings.push(
+                Diagnostic::warning(
+                    "W0002",
+                    format!(
+                        "'{}' is always {}: {} and {} are different types",
+                        operator, outcome, left_type, right_type
+                    ),
+                    span,
+                )
+                .with_label(left.span, format!("this is {}", left_type))
+                .with_label(right.span, format!("this is {}", right_type))
+                .with_note("use '==' or '!=' to compare the values alone"),
+            );
+        }
+        // Mixing a named type with its definition gives a value of the definition
+        let result = if left_type.is_identical(&right_type) {
+            left_type
+        } else {
+            self.underlying(&left_type)
+        };
+        Ok(rule.result.unwrap_or(result))
+    }
+
+    /// Check `pointer + offset` or `pointer - offset`, which moves a raw
+    /// pointer by a number of elements. Safe pointers do not allow it.
+    fn check_pointer_arithmetic(
+        &self,
+        left: &Expression,
+        left_type: &TypeIdentifier,
+        operator: Operator,
+        right: &Expression,
+        right_type: &TypeIdentifier,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        if !matches!(self.underlying(left_type), TypeIdentifier::Pointer(_)) {
+            return Err(Diagnostic::error(
+                "E0101",
+                format!(
+                    "Pointer arithmetic is only allowed on raw pointers, but the left operand of '{}' is {}",
+                    operator, left_type
+                ),
+                left.span,
+            )
+            .into());
+        }
+        if !operators::is_integer(right_type, &self.named_types) {
+            return Err(Diagnostic::error(
+                "E0101",
+                format!(
+                    "The offset added to a pointer with '{}' must be int, but it is {}",
+                    operator, right_type
+                ),
+                right.span,
+            )
+            .into());
+        }
+        Ok(left_type.clone())
+    }
+
+    fn check_unary(
+        &mut self,
+        op: Operator,
+        expression: &Expression,
+        span: Span,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        if op == Operator::AddressOf {
+            // Only a named variable has an address
+            let ExpressionKind::Identifier(ident) = &expression.kind else {
+                return Err(Diagnostic::error(
+                    "E0103",
+                    "Can only take the address of a variable",
+                    span,
+                )
+                .into());
+            };
+            let Some(var_type) = self.scopes.get(ident) else {
+                return Err(Diagnostic::error(
+                    "E0103",
+                    format!(
+                        "Cannot take the address of '{}', it is not a variable",
+                        ident
+                    ),
+                    span,
+                )
+                .into());
+            };
+            let var_type = var_type.clone();
+            self.resolve_name(ident, expression.span)?;
+            return Ok(TypeIdentifier::Pointer(Box::new(var_type)));
+        }
+
+        let expr_type = self.check_expr(expression)?;
+        match op {
+            Operator::Not => {
+                if self.underlying(&expr_type) != TypeIdentifier::Boolean {
+                    return Err(Diagnostic::error(
+                        "E0101",
+                        "Logical operators require boolean types",
+                        span,
+                    )
+                    .into());
+                }
+                Ok(TypeIdentifier::Boolean)
+            }
+            Operator::Minus => {
+                if !operators::is_numeric(&expr_type, &self.named_types) {
+                    return Err(Diagnostic::error(
+                        "E0101",
+                        format!("Cannot negate a value of type {}", expr_type),
+                        span,
+                    )
+                    .into());
+                }
+                Ok(expr_type)
+            }
+            Operator::BitNot => {
+                if self.underlying(&expr_type) != TypeIdentifier::Number {
+                    return Err(Diagnostic::error(
+                        "E0101",
+                        format!(
+                            "Bitwise NOT requires an integer, got a value of type {}",
+                            expr_type
+                        ),
+                        span,
+                    )
+                    .into());
+                }
+                Ok(expr_type)
+            }
+            Operator::Deref => match self.underlying(&expr_type) {
+                TypeIdentifier::SafePointer(Ownership::Weak, _) => Err(Diagnostic::error(
+                    "E0101",
+                    format!(
+                        "Cannot dereference a value of type {}: a weak pointer must be upgraded to a shared pointer first",
+                        expr_type
+                    ),
+                    span,
+                )
+                .into()),
+                TypeIdentifier::Pointer(pointee) => {
+                    self.check_nonnull(expression, span)?;
+                    Ok(*pointee)
+                }
+                TypeIdentifier::SafePointer(_, pointee) => Ok(*pointee),
+                _ => Err(Diagnostic::error(
+                    "E0101",
+                    format!("Cannot dereference a value of type {}", expr_type),
+                    span,
+                )
+                .into()),
+            },
+            _ => Err(
+                Diagnostic::error("E0101", "Unsupported operator in unary expression", span).into(),
+            ),
+        }
+    }
+
+    /// Check that a raw pointer is known not to be null where it is
+    /// dereferenced. Only variables are tracked: a pointer read from memory or
+    /// returned by a call is checked when it is dereferenced at run time.
+    fn check_nonnull(&self, pointer: &Expression, span: Span) -> TypeCheckerResult<()> {
+        match &pointer.kind {
+            ExpressionKind::Grouping(expression) | ExpressionKind::Hinted { expression, .. } => {
+                self.check_nonnull(expression, span)
+            }
+            ExpressionKind::Identifier(name)
+                if !hint_facts::proves_nonnull(&self.facts_of(pointer)) =>
+            {
+                Err(Diagnostic::error(
+                    "E0404",
+                    format!("Cannot dereference '{}': it may be null", name),
+                    span,
+                )
+                .with_note(format!(
+                    "compare it with null first, as in 'if {} != null {{ ... }}', or declare it @nonnull",
+                    name
+                ))
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_call(
+        &mut self,
+        callee: &Expression,
+        args: &[Expression],
+        span: Span,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        // Calls to named functions check against the full signature, including hints
+        if let ExpressionKind::Identifier(func_name) = &callee.kind
+            && !self.scopes.contains(func_name)
+        {
+            // Lookup function signature
+            let function_signature = self.functions.get(func_name).cloned().ok_or_else(|| {
+                Box::new(Diagnostic::error(
+                    "E0001",
+                    format!("Call to undefined function '{}'", func_name),
+                    callee.span,
+                ))
+            })?;
+            if self.undefined_functions.contains(func_name) {
+                return Err(Diagnostic::error(
+                    "E0004",
+                    format!(
+                        "Call to function '{}' which is declared but never defined",
+                        func_name
+                    ),
+                    callee.span,
+                )
+                .into());
+            }
+
+            self.annotations
+                .resolve(callee.span, Resolution::Function(func_name.clone()));
+            self.annotations
+                .record_type(callee.span, function_type(&function_signature));
+
+            // Check argument count
+            if args.len() != function_signature.parameters.len() {
+                return Err(Diagnostic::error(
+                    "E0104",
+                    format!(
+                        "Function '{}' expects {} arguments, got {}",
+                        func_name,
+                        function_signature.parameters.len(),
+                        args.len()
+                    ),
+                    span,
+                )
+                .into());
+            }
+
+            // Check argument types
+            for (i, (arg, expected_ty)) in args
+                .iter()
+                .zip(function_signature.parameters.iter())
+                .enumerate()
+            {
+                let arg_ty = self.check_argument(arg, expected_ty)?;
+                let parameter_ty = parameter_type(expected_ty);
+                if !self.assignable(&parameter_ty, &arg_ty) {
+                    return Err(Diagnostic::error(
+                        "E0100",
+                        format!(
+                            "Type mismatch in argument {} of '{}': expected {}, got {}",
+                            i + 1,
+                            func_name,
+                            parameter_ty,
+                            arg_ty
+                        ),
+                        arg.span,
+                    )
+                    .into());
+                }
+                self.check_requirements(func_name, i, arg, expected_ty)?;
+            }
+
+            return Ok(return_type(&function_signature.return_type));
+        }
+
+        // Anything else is called through its function type
+        let callee_type = self.check_expr(callee)?;
+        let TypeIdentifier::Function {
+            parameters,
+            return_type,
+        } = self.underlying(&callee_type)
+        else {
+            return Err(Diagnostic::error(
+                "E0105",
+                format!("Cannot call a value of type {}", callee_type),
+                callee.span,
+            )
+            .into());
+        };
+        if args.len() != parameters.len() {
+            return Err(Diagnostic::error(
+                "E0104",
+                format!(
+                    "Function value expects {} arguments, got {}",
+                    parameters.len(),
+                    args.len()
+                ),
+                span,
+            )
+            .into());
+        }
+        for (i, (arg, expected_ty)) in args.iter().zip(parameters.iter()).enumerate() {
+            let arg_ty = self.check_expr_expecting(arg, expected_ty)?;
+            if !self.assignable(expected_ty, &arg_ty) {
+                return Err(Diagnostic::error(
+                    "E0100",
+                    format!(
+                        "Type mismatch in argument {} of function value: expected {}, got {}",
+                        i + 1,
+                        expected_ty,
+                        arg_ty
+                    ),
+                    arg.span,
+                )
+                .into());
+            }
+        }
+        Ok(*return_type)
+    }
+
+    /// Type of a name used as a value: a variable, or a named function used as
+    /// a function value. The name is resolved to what it refers to.
+    fn resolve_name(&mut self, ident: &String, span: Span) -> TypeCheckerResult<TypeIdentifier> {
+        let (resolution, var_type) = if let Some(id) = self.scopes.resolve(ident) {
+            let var_type = self.scopes.get(ident).cloned();
+            (
+                Resolution::Local(id),
+                var_type.expect("a resolved name has a type"),
+            )
+        } else if let Some(signature) = self.functions.get(ident) {
+            (
+                Resolution::Function(ident.clone()),
+                function_type(signature),
+            )
+        } else {
+            return Err(Diagnostic::error(
+                "E0001",
+                format!("Use of undeclared variable '{}'", ident),
+                span,
+            )
+            .into());
+        };
+        self.annotations.resolve(span, resolution);
+        self.annotations.record_type(span, var_type.clone());
+        Ok(var_type)
+    }
+
+    /// Type an argument. Handing a write-only parameter straight to another
+    /// function that also promises write-only access does not count as a read.
+    fn check_argument(
+        &mut self,
+        arg: &Expression,
+        parameter: &FunctionParameter,
+    ) -> TypeCheckerResult<TypeIdentifier> {
+        if let ExpressionKind::Identifier(ident) = &arg.kind
+            && parameter.hints.iter().any(|hint| {
+                hint.placement == HintPlacement::Promise && hint_facts::forbids_read(hint)
+            })
+        {
+            return self.resolve_name(ident, arg.span);
+        }
+        self.check_expr_expecting(arg, &parameter_type(parameter))
+    }
+
+    /// Check that an argument is known to satisfy every requirement hint of
+    /// the parameter it is passed to.
+    fn check_requirements(
+        &self,
+        func_name: &String,
+        index: usize,
+        arg: &Expression,
+        parameter: &FunctionParameter,
+    ) -> TypeCheckerResult<()> {
+        let facts = self.facts_of(arg);
+        for requirement in parameter
+            .hints
+            .iter()
+            .filter(|h| hint_facts::is_requirement(h))
+        {
+            if facts
+                .iter()
+                .any(|fact| hint_facts::satisfies(fact, requirement))
+            {
+                continue;
+            }
+            // The callee gets the only copy of a temporary, nobody else writes to it
+            if hint_facts::forbids_write(requirement) && is_temporary(arg) {
+                continue;
+            }
+            let note = match &arg.kind {
+                ExpressionKind::Identifier(ident) if facts.is_empty() => format!(
+                    "nothing is known about '{}'; declare it with '{}' to prove it",
+                    ident,
+                    hint_facts::render(requirement)
+                ),
+                ExpressionKind::Identifier(ident) => format!(
+                    "'{}' is only known to be {}",
+                    ident,
+                    facts
+                        .iter()
+                        .map(hint_facts::render)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                ),
+                _ => "bind the argument to a hinted variable to prove it".to_string(),
+            };
+            return Err(Diagnostic::error(
+                "E0303",
+                format!(
+                    "Argument {} of '{}' does not prove requirement '{}' of parameter '{}'",
+                    index + 1,
+                    func_name,
+                    hint_facts::render(requirement),
+                    parameter.parameter_name
+                ),
+                arg.span,
+            )
+            .with_label(parameter.span, "parameter declared here")
+            .with_note(note)
+            .into());
+        }
+        Ok(())
+    }
+}